@@ -6,6 +6,8 @@
 use clap::{crate_version, value_parser, Arg, ArgAction, Command};
 use linux_raw_sys::ioctl::*;
 #[cfg(target_os = "linux")]
+use serde::Serialize;
+#[cfg(target_os = "linux")]
 use std::collections::BTreeMap;
 #[cfg(target_os = "linux")]
 use uucore::error::USimpleError;
@@ -14,6 +16,19 @@ use uucore::{error::UResult, format_usage, help_about, help_usage};
 const ABOUT: &str = help_about!("blockdev.md");
 const USAGE: &str = help_usage!("blockdev.md");
 
+// One line of --report, or one object of --report --json
+#[cfg(target_os = "linux")]
+#[derive(Debug, Serialize)]
+struct DeviceReport {
+    ro: bool,
+    ra: u64,
+    ssz: u64,
+    bsz: u64,
+    startsec: u64,
+    size: u64,
+    device: String,
+}
+
 #[derive(Copy, Clone, Debug)]
 enum IoctlArgType {
     Short,
@@ -27,7 +42,7 @@ enum IoctlArgType {
 #[derive(Clone, Debug)]
 enum IoctlCommand {
     GetAttribute(IoctlArgType),
-    SetAttribute,
+    SetAttribute(IoctlArgType),
     Operation(u32),
 }
 
@@ -36,6 +51,8 @@ enum IoctlCommand {
 enum BlockdevCommand {
     SetVerbosity(bool),
     Ioctl(&'static str, u32, IoctlCommand),
+    // Attributes without an ioctl, read from the device's queue directory in sysfs
+    SysfsAttribute(&'static str, &'static str),
 }
 
 const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
@@ -61,6 +78,14 @@ const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
             IoctlCommand::GetAttribute(IoctlArgType::Int),
         ),
     ),
+    (
+        "getdiscardgran",
+        BlockdevCommand::SysfsAttribute("get discard granularity in bytes", "discard_granularity"),
+    ),
+    (
+        "getdiscardmax",
+        BlockdevCommand::SysfsAttribute("get max discard size in bytes", "discard_max_bytes"),
+    ),
     (
         "getdiscardzeroes",
         BlockdevCommand::Ioctl(
@@ -69,6 +94,14 @@ const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
             IoctlCommand::GetAttribute(IoctlArgType::Int),
         ),
     ),
+    (
+        "getdiskseq",
+        BlockdevCommand::Ioctl(
+            "get disk sequence number",
+            BLKGETDISKSEQ,
+            IoctlCommand::GetAttribute(IoctlArgType::U64),
+        ),
+    ),
     (
         "getfra",
         BlockdevCommand::Ioctl(
@@ -101,6 +134,14 @@ const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
             IoctlCommand::GetAttribute(IoctlArgType::Short),
         ),
     ),
+    (
+        "getnr_zones",
+        BlockdevCommand::Ioctl(
+            "get number of zones",
+            BLKGETNRZONES,
+            IoctlCommand::GetAttribute(IoctlArgType::Int),
+        ),
+    ),
     (
         "getpbsz",
         BlockdevCommand::Ioctl(
@@ -157,6 +198,14 @@ const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
             IoctlCommand::GetAttribute(IoctlArgType::U64Sectors),
         ),
     ),
+    (
+        "getzonesz",
+        BlockdevCommand::Ioctl(
+            "get zone size in 512-byte sectors",
+            BLKGETZONESZ,
+            IoctlCommand::GetAttribute(IoctlArgType::Int),
+        ),
+    ),
     (
         "rereadpt",
         BlockdevCommand::Ioctl(
@@ -167,19 +216,27 @@ const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
     ),
     (
         "setbsz",
-        BlockdevCommand::Ioctl("set blocksize", BLKBSZSET, IoctlCommand::SetAttribute),
+        BlockdevCommand::Ioctl(
+            "set blocksize on file descriptor opening the block device",
+            BLKBSZSET,
+            IoctlCommand::SetAttribute(IoctlArgType::Int),
+        ),
     ),
     (
         "setfra",
         BlockdevCommand::Ioctl(
             "set filesystem readahead",
             BLKFRASET,
-            IoctlCommand::SetAttribute,
+            IoctlCommand::SetAttribute(IoctlArgType::Long),
         ),
     ),
     (
         "setra",
-        BlockdevCommand::Ioctl("set readahead", BLKRASET, IoctlCommand::SetAttribute),
+        BlockdevCommand::Ioctl(
+            "set readahead",
+            BLKRASET,
+            IoctlCommand::SetAttribute(IoctlArgType::Long),
+        ),
     ),
     (
         "setro",
@@ -194,15 +251,27 @@ const BLOCKDEV_ACTIONS: &[(&str, BlockdevCommand)] = &[
 #[cfg(target_os = "linux")]
 mod linux {
     use crate::*;
+    use std::io::Read;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Path, PathBuf};
     use std::{fs::File, io, os::fd::AsRawFd};
-    use std::{io::Read, os::unix::fs::MetadataExt, path::Path};
-    use uucore::{error::UIoError, libc};
+    use uucore::error::FromIo;
 
-    unsafe fn uu_ioctl<T>(device_file: &File, ioctl_code: u32, input: T) -> UResult<()> {
-        if libc::ioctl(device_file.as_raw_fd(), ioctl_code.into(), input) < 0 {
-            Err(Box::new(UIoError::from(io::Error::last_os_error())))
+    /// Issues `ioctl_code` on `device_file`, returning the non-negative result of the ioctl.
+    ///
+    /// # Safety
+    ///
+    /// `input` must be what the ioctl expects, e.g. a pointer to a value of the right type.
+    pub unsafe fn uu_ioctl<T>(
+        device_file: &File,
+        ioctl_code: u32,
+        input: T,
+    ) -> io::Result<libc::c_int> {
+        let ret = libc::ioctl(device_file.as_raw_fd(), ioctl_code.into(), input);
+        if ret < 0 {
+            Err(io::Error::last_os_error())
         } else {
-            Ok(())
+            Ok(ret)
         }
     }
 
@@ -216,7 +285,8 @@ mod linux {
             ioctl_code: u32,
         ) -> UResult<u64> {
             let mut retval: T = Default::default();
-            uu_ioctl(device, ioctl_code, &mut retval as *mut T as usize).map(|_| retval.into())
+            uu_ioctl(device, ioctl_code, &mut retval as *mut T as usize)?;
+            Ok(retval.into())
         }
 
         match ioctl_type {
@@ -228,24 +298,89 @@ mod linux {
         }
     }
 
-    fn get_partition_offset(device_file: &File) -> UResult<usize> {
+    unsafe fn set_ioctl_attribute(
+        device_file: &File,
+        ioctl_code: u32,
+        ioctl_type: IoctlArgType,
+        value: usize,
+    ) -> UResult<()> {
+        match ioctl_type {
+            // BLKBSZSET reads its argument through a pointer, unlike the readahead setters.
+            IoctlArgType::Int => {
+                let value = libc::c_int::try_from(value)
+                    .map_err(|_| USimpleError::new(1, format!("invalid value: {}", value)))?;
+                uu_ioctl(device_file, ioctl_code, &value as *const libc::c_int)?
+            }
+            _ => uu_ioctl(device_file, ioctl_code, value)?,
+        };
+        Ok(())
+    }
+
+    // The sysfs directory of the device, e.g. /sys/dev/block/8:1 for /dev/sda1
+    fn sysfs_path(device_file: &File) -> UResult<PathBuf> {
         let rdev = device_file.metadata()?.rdev();
-        let major = libc::major(rdev);
-        let minor = libc::minor(rdev);
-        if Path::new(&format!("/sys/dev/block/{}:{}/partition", major, minor)).exists() {
-            let mut start_fd = File::open(format!("/sys/dev/block/{}:{}/start", major, minor))?;
-            let mut str = String::new();
-            start_fd.read_to_string(&mut str)?;
-            return str
-                .trim()
-                .parse()
-                .map_err(|_| USimpleError::new(1, "Unable to parse partition start offset"));
+        Ok(PathBuf::from(format!(
+            "/sys/dev/block/{}:{}",
+            libc::major(rdev),
+            libc::minor(rdev)
+        )))
+    }
+
+    fn read_sysfs_value(path: &Path) -> UResult<u64> {
+        let mut str = String::new();
+        File::open(path)?.read_to_string(&mut str)?;
+        str.trim().parse().map_err(|_| {
+            USimpleError::new(1, format!("Unable to parse {}", path.to_string_lossy()))
+        })
+    }
+
+    fn get_partition_offset(device_file: &File) -> UResult<u64> {
+        let sysfs_path = sysfs_path(device_file)?;
+        if sysfs_path.join("partition").exists() {
+            return read_sysfs_value(&sysfs_path.join("start"));
         }
         Ok(0)
     }
 
-    pub fn do_report(device_path: &str) -> UResult<()> {
-        let device_file = File::open(device_path)?;
+    // Partitions share the request queue of the whole disk, which is their parent in sysfs.
+    fn get_queue_attribute(device_file: &File, attribute: &str) -> UResult<u64> {
+        let sysfs_path = sysfs_path(device_file)?;
+        let queue_path = if sysfs_path.join("partition").exists() {
+            sysfs_path.join("../queue")
+        } else {
+            sysfs_path.join("queue")
+        };
+        read_sysfs_value(&queue_path.join(attribute))
+    }
+
+    // Devices listed in /proc/partitions, used by --report when no device is given
+    pub fn all_devices() -> UResult<Vec<String>> {
+        let mut str = String::new();
+        File::open("/proc/partitions")?.read_to_string(&mut str)?;
+
+        // The first line is a header: "major minor  #blocks  name"
+        Ok(str
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let fields: Vec<_> = line.split_whitespace().collect();
+                match fields[..] {
+                    [major, minor, blocks, name]
+                        if [major, minor, blocks]
+                            .iter()
+                            .all(|field| field.parse::<u64>().is_ok()) =>
+                    {
+                        Some(format!("/dev/{}", name))
+                    }
+                    _ => None,
+                }
+            })
+            .collect())
+    }
+
+    pub fn get_report(device_path: &str) -> UResult<DeviceReport> {
+        let device_file =
+            File::open(device_path).map_err_context(|| format!("cannot open {}", device_path))?;
         let partition_offset = get_partition_offset(&device_file)?;
         let report_ioctls = &["getro", "getra", "getss", "getbsz", "getsize64"];
         let ioctl_values = report_ioctls
@@ -261,17 +396,32 @@ mod linux {
                 unsafe { get_ioctl_attribute(&device_file, *ioctl_code, *ioctl_type) }
             })
             .collect::<Result<Vec<u64>, _>>()?;
+        Ok(DeviceReport {
+            ro: ioctl_values[0] == 1,
+            ra: ioctl_values[1],
+            ssz: ioctl_values[2],
+            bsz: ioctl_values[3],
+            startsec: partition_offset,
+            size: ioctl_values[4],
+            device: device_path.to_string(),
+        })
+    }
+
+    pub fn print_report(report: &DeviceReport) {
         println!(
             "{} {:5} {:5} {:5} {:15} {:15}   {}",
-            if ioctl_values[0] == 1 { "ro" } else { "rw" },
-            ioctl_values[1],
-            ioctl_values[2],
-            ioctl_values[3],
-            partition_offset,
-            ioctl_values[4],
-            device_path
+            if report.ro { "ro" } else { "rw" },
+            report.ra,
+            report.ssz,
+            report.bsz,
+            report.startsec,
+            report.size,
+            report.device
         );
-        Ok(())
+    }
+
+    pub fn print_json_report(reports: &[DeviceReport]) {
+        uutable::print_json(&serde_json::json!({ "blockdevices": reports }));
     }
 
     pub fn do_ioctl_command(
@@ -291,14 +441,17 @@ mod linux {
                     println!("{}", ret);
                 }
             }
-            IoctlCommand::SetAttribute => {
-                unsafe { uu_ioctl(device, ioctl_code, arg)? };
+            IoctlCommand::SetAttribute(ioctl_type) => {
+                unsafe { set_ioctl_attribute(device, ioctl_code, *ioctl_type, arg)? };
                 if verbose {
                     println!("{} succeeded.", name);
                 }
             }
             IoctlCommand::Operation(param) => {
                 unsafe { uu_ioctl(device, ioctl_code, param)? };
+                if ioctl_code == BLKROSET {
+                    confirm_read_only(device, *param != 0)?;
+                }
                 if verbose {
                     println!("{} succeeded.", name);
                 }
@@ -306,25 +459,84 @@ mod linux {
         };
         Ok(())
     }
+
+    // BLKROSET only changes the flag of the block device, so a disk which is read-only in
+    // hardware stays read-only even though the ioctl succeeded.
+    fn confirm_read_only(device: &File, read_only: bool) -> UResult<()> {
+        let current = unsafe { get_ioctl_attribute(device, BLKROGET, IoctlArgType::Int)? };
+        if (current != 0) != read_only {
+            return Err(USimpleError::new(
+                1,
+                if read_only {
+                    "the device is still read-write"
+                } else {
+                    "the device is still read-only"
+                },
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn do_sysfs_command(
+        device: &File,
+        name: &str,
+        attribute: &str,
+        verbose: bool,
+    ) -> UResult<()> {
+        let ret = get_queue_attribute(device, attribute)?;
+        if verbose {
+            println!("{}: {}", name, ret);
+        } else {
+            println!("{}", ret);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(target_os = "linux")]
 use linux::*;
 
+#[cfg(target_os = "linux")]
+pub use linux::uu_ioctl;
+
 #[cfg(target_os = "linux")]
 #[uucore::main]
 pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     use std::fs::File;
 
     let matches: clap::ArgMatches = uu_app().try_get_matches_from(args)?;
-    let devices = matches
+    let devices: Vec<String> = matches
         .get_many::<String>("devices")
-        .expect("Required command-line argument");
+        .map(|devices| devices.cloned().collect())
+        .unwrap_or_default();
 
     if matches.get_flag("report") {
-        println!("RO    RA   SSZ   BSZ        StartSec            Size   Device");
-        for device_path in devices {
-            uucore::show_if_err!(do_report(device_path));
+        let devices = if devices.is_empty() {
+            all_devices()?
+        } else {
+            devices
+        };
+
+        if matches.get_flag("json") {
+            let reports: Vec<_> = devices
+                .iter()
+                .filter_map(|device_path| match get_report(device_path) {
+                    Ok(report) => Some(report),
+                    Err(e) => {
+                        uucore::show!(e);
+                        None
+                    }
+                })
+                .collect();
+            print_json_report(&reports);
+        } else {
+            println!("RO    RA   SSZ   BSZ        StartSec            Size   Device");
+            for device_path in &devices {
+                match get_report(device_path) {
+                    Ok(report) => print_report(&report),
+                    Err(e) => uucore::show!(e),
+                }
+            }
         }
         Ok(())
     } else {
@@ -342,7 +554,7 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
             }
         }
 
-        for device_path in devices {
+        for device_path in &devices {
             let mut verbose = false;
             let device_file = File::open(device_path)?;
             for (operation, value) in operations.values() {
@@ -364,6 +576,16 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
                             return Err(e);
                         }
                     }
+                    BlockdevCommand::SysfsAttribute(description, attribute) => {
+                        if let Err(e) =
+                            do_sysfs_command(&device_file, description, attribute, verbose)
+                        {
+                            if verbose {
+                                println!("{} failed.", description);
+                            }
+                            return Err(e);
+                        }
+                    }
                 }
             }
         }
@@ -383,7 +605,19 @@ pub fn uu_app() -> Command {
                 .help("print report for specified devices")
                 .action(ArgAction::SetTrue),
         )
-        .arg(Arg::new("devices").required(true).action(ArgAction::Append));
+        .arg(
+            Arg::new("json")
+                .short('J')
+                .long("json")
+                .help("use JSON output format for --report")
+                .requires("report")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("devices")
+                .required_unless_present("report")
+                .action(ArgAction::Append),
+        );
 
     for (flag, action) in BLOCKDEV_ACTIONS {
         let mut arg = Arg::new(flag)
@@ -399,13 +633,13 @@ pub fn uu_app() -> Command {
             BlockdevCommand::SetVerbosity(false) => {
                 arg = arg.short('q').help("quiet mode");
             }
-            BlockdevCommand::Ioctl(name, _, _) => {
+            BlockdevCommand::Ioctl(name, _, _) | BlockdevCommand::SysfsAttribute(name, _) => {
                 arg = arg.help(name);
             }
         }
 
         match action {
-            BlockdevCommand::Ioctl(_, _, IoctlCommand::SetAttribute) => {
+            BlockdevCommand::Ioctl(_, _, IoctlCommand::SetAttribute(_)) => {
                 arg = arg.num_args(1);
             }
             _ => {
@@ -0,0 +1,38 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FindmntError {
+    #[error("can't read {0}: {1}")]
+    Read(String, io::Error),
+
+    #[error("{0}: parse error at line {1} -- ignored: {2}")]
+    Parse(String, usize, ParseMountError),
+
+    #[error("poll failed: {0}")]
+    Poll(io::Error),
+}
+
+impl uucore::error::UError for FindmntError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        false
+    }
+}
+
+/// Reasons for a line of a mount table to be rejected.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseMountError {
+    #[error("missing {0}")]
+    MissingField(&'static str),
+
+    #[error("invalid {0} '{1}'")]
+    InvalidField(&'static str, String),
+}
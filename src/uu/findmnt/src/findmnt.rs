@@ -0,0 +1,923 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+mod errors;
+mod mountinfo;
+
+use std::ffi::CString;
+use std::fs::{self, File};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgAction, ArgMatches, Command, ValueEnum, crate_version, value_parser};
+use uucore::error::{UResult, set_exit_code};
+use uucore::{format_usage, help_about, help_usage, show_warning};
+use uutable::{Row, Table, TableColumn, ValueKind};
+
+pub use crate::errors::{FindmntError, ParseMountError};
+pub use crate::mountinfo::{Mount, TableFormat, parse_table, unescape};
+
+const ABOUT: &str = help_about!("findmnt.md");
+const USAGE: &str = help_usage!("findmnt.md");
+
+pub const _PATH_PROC_MOUNTINFO: &str = "/proc/self/mountinfo";
+const _PATH_MNTTAB: &str = "/etc/fstab";
+const _PATH_MOUNTED: &str = "/etc/mtab";
+
+mod options {
+    pub const ASCII: &str = "ascii";
+    pub const BYTES: &str = "bytes";
+    pub const FIRST_ONLY: &str = "first-only";
+    pub const FSTAB: &str = "fstab";
+    pub const JSON: &str = "json";
+    pub const KERNEL: &str = "kernel";
+    pub const LIST: &str = "list";
+    pub const MTAB: &str = "mtab";
+    pub const NOFSROOT: &str = "nofsroot";
+    pub const NOHEADINGS: &str = "noheadings";
+    pub const OPTIONS: &str = "options";
+    pub const OUTPUT: &str = "output";
+    pub const OUTPUT_ALL: &str = "output-all";
+    pub const PAIRS: &str = "pairs";
+    pub const POLL: &str = "poll";
+    pub const RAW: &str = "raw";
+    pub const SOURCE: &str = "source";
+    pub const TAB_FILE: &str = "tab-file";
+    pub const TARGET: &str = "target";
+    pub const TIMEOUT: &str = "timeout";
+    pub const TYPES: &str = "types";
+    pub const ARGS: &str = "args";
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Source,
+    Target,
+    FsType,
+    Options,
+    VfsOptions,
+    FsOptions,
+    MajorMinor,
+    FsRoot,
+    Id,
+    Parent,
+    OptFields,
+    Propagation,
+    Freq,
+    PassNo,
+    Size,
+    Avail,
+    Used,
+    UsePercent,
+    Action,
+    OldTarget,
+    OldOptions,
+}
+
+const DEFAULT_COLS: &[Column] = &[
+    Column::Target,
+    Column::Source,
+    Column::FsType,
+    Column::Options,
+];
+
+const DEFAULT_POLL_COLS: &[Column] = &[
+    Column::Action,
+    Column::Target,
+    Column::Source,
+    Column::FsType,
+    Column::Options,
+];
+
+impl TableColumn for Column {
+    const ALL: &'static [Self] = &[
+        Self::Source,
+        Self::Target,
+        Self::FsType,
+        Self::Options,
+        Self::VfsOptions,
+        Self::FsOptions,
+        Self::MajorMinor,
+        Self::FsRoot,
+        Self::Id,
+        Self::Parent,
+        Self::OptFields,
+        Self::Propagation,
+        Self::Freq,
+        Self::PassNo,
+        Self::Size,
+        Self::Avail,
+        Self::Used,
+        Self::UsePercent,
+        Self::Action,
+        Self::OldTarget,
+        Self::OldOptions,
+    ];
+
+    fn header_text(&self) -> &'static str {
+        match self {
+            Self::Source => "SOURCE",
+            Self::Target => "TARGET",
+            Self::FsType => "FSTYPE",
+            Self::Options => "OPTIONS",
+            Self::VfsOptions => "VFS-OPTIONS",
+            Self::FsOptions => "FS-OPTIONS",
+            Self::MajorMinor => "MAJ:MIN",
+            Self::FsRoot => "FSROOT",
+            Self::Id => "ID",
+            Self::Parent => "PARENT",
+            Self::OptFields => "OPT-FIELDS",
+            Self::Propagation => "PROPAGATION",
+            Self::Freq => "FREQ",
+            Self::PassNo => "PASSNO",
+            Self::Size => "SIZE",
+            Self::Avail => "AVAIL",
+            Self::Used => "USED",
+            Self::UsePercent => "USE%",
+            Self::Action => "ACTION",
+            Self::OldTarget => "OLD-TARGET",
+            Self::OldOptions => "OLD-OPTIONS",
+        }
+    }
+
+    fn get_help(&self) -> &'static str {
+        match self {
+            Self::Source => "source device",
+            Self::Target => "mountpoint",
+            Self::FsType => "filesystem type",
+            Self::Options => "all mount options",
+            Self::VfsOptions => "VFS specific mount options",
+            Self::FsOptions => "FS specific mount options",
+            Self::MajorMinor => "major:minor device number",
+            Self::FsRoot => "filesystem root",
+            Self::Id => "mount ID",
+            Self::Parent => "mount parent ID",
+            Self::OptFields => "optional mount fields",
+            Self::Propagation => "VFS propagation flags",
+            Self::Freq => "dump(8) period in days [fstab only]",
+            Self::PassNo => "pass number on parallel fsck(8) [fstab only]",
+            Self::Size => "filesystem size",
+            Self::Avail => "filesystem size available",
+            Self::Used => "filesystem size used",
+            Self::UsePercent => "filesystem use percentage",
+            Self::Action => "action detected by --poll",
+            Self::OldTarget => "old mountpoint saved by --poll",
+            Self::OldOptions => "old mount options saved by --poll",
+        }
+    }
+
+    fn get_float_right(&self) -> bool {
+        self.value_kind() != ValueKind::String || self == &Self::UsePercent
+    }
+
+    fn value_kind(&self) -> ValueKind {
+        match self {
+            Self::Id | Self::Parent | Self::Freq | Self::PassNo => ValueKind::Number,
+            Self::Size | Self::Avail | Self::Used => ValueKind::Size,
+            _ => ValueKind::String,
+        }
+    }
+}
+
+/// The kinds of changes to the mount table reported by `--poll`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Mount,
+    Umount,
+    Remount,
+    Move,
+}
+
+impl Action {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mount => "mount",
+            Self::Umount => "umount",
+            Self::Remount => "remount",
+            Self::Move => "move",
+        }
+    }
+}
+
+impl ValueEnum for Action {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Mount, Self::Umount, Self::Remount, Self::Move]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(PossibleValue::new(self.as_str()))
+    }
+}
+
+fn parse_device_number(s: &str) -> Option<(u32, u32)> {
+    let (major, minor) = s.split_once(':')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
+// A list of types prefixed with "no" matches every other type, e.g. `-t noproc,sysfs`
+fn match_fstype(fstype: &str, pattern: &str) -> bool {
+    let (negate, list) = match pattern.strip_prefix("no") {
+        Some(list) => (true, list),
+        None => (false, pattern),
+    };
+    list.split(',').any(|name| name == fstype) != negate
+}
+
+// Every option of the list has to match. "noatime" requires "atime" to be absent, unless it
+// is written as "+noatime", and "mode=755" also compares the value.
+fn match_options(mount: &Mount, pattern: &str) -> bool {
+    pattern
+        .split(',')
+        .filter(|item| !item.is_empty())
+        .all(|item| {
+            let (negate, item) = match (item.strip_prefix('+'), item.strip_prefix("no")) {
+                (Some(item), _) => (false, item),
+                (None, Some(item)) => (true, item),
+                (None, None) => (false, item),
+            };
+            let (name, value) = match item.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (item, None),
+            };
+            let found = mount
+                .option(name)
+                .is_some_and(|actual| value.is_none_or(|value| value == actual));
+            found != negate
+        })
+}
+
+fn match_source(mount: &Mount, source: &str) -> bool {
+    if mount.source == source {
+        return true;
+    }
+
+    if let Some(device) = parse_device_number(source) {
+        return mount.device == Some(device);
+    }
+
+    // Devices may be given through other paths, e.g. links in /dev/disk/by-uuid.
+    let Ok(metadata) = fs::metadata(source) else {
+        return false;
+    };
+    if metadata.file_type().is_block_device() {
+        let rdev = metadata.rdev();
+        if mount.device == Some((libc::major(rdev), libc::minor(rdev))) {
+            return true;
+        }
+    }
+    mount.source.starts_with('/')
+        && fs::canonicalize(&mount.source).ok() == fs::canonicalize(source).ok()
+}
+
+fn match_target(mount: &Mount, target: &str) -> bool {
+    mount.target == target
+        || fs::canonicalize(target).is_ok_and(|path| Path::new(&mount.target) == path)
+}
+
+// Walks up from `path` to the mountpoint of the filesystem which contains it.
+fn find_mountpoint(mounts: &[Mount], path: &str) -> Option<String> {
+    let mut path = fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    loop {
+        if let Some(mount) = mounts.iter().find(|mount| Path::new(&mount.target) == path) {
+            return Some(mount.target.clone());
+        }
+        if !path.pop() {
+            return None;
+        }
+    }
+}
+
+struct Filter {
+    types: Option<String>,
+    options: Option<String>,
+    source: Option<String>,
+    target: Option<String>,
+    // A single positional argument is either a source or a target.
+    source_or_target: Option<String>,
+}
+
+impl Filter {
+    fn matches(&self, mount: &Mount) -> bool {
+        self.types
+            .as_ref()
+            .is_none_or(|types| match_fstype(&mount.fstype, types))
+            && self
+                .options
+                .as_ref()
+                .is_none_or(|options| match_options(mount, options))
+            && self
+                .source
+                .as_ref()
+                .is_none_or(|source| match_source(mount, source))
+            && self
+                .target
+                .as_ref()
+                .is_none_or(|target| match_target(mount, target))
+            && self
+                .source_or_target
+                .as_ref()
+                .is_none_or(|arg| match_source(mount, arg) || match_target(mount, arg))
+    }
+
+    // Looking up a single filesystem doesn't need the tree.
+    fn is_lookup(&self) -> bool {
+        self.source.is_some() || self.target.is_some() || self.source_or_target.is_some()
+    }
+}
+
+struct OutputOptions {
+    cols: Vec<Column>,
+    bytes: bool,
+    json: bool,
+    list: bool,
+    noheadings: bool,
+    nofsroot: bool,
+    pairs: bool,
+    raw: bool,
+    ascii: bool,
+    // Usage of the filesystems is only known for the mounts of the running system.
+    statvfs: bool,
+}
+
+impl OutputOptions {
+    // Filesystems are only drawn as a tree when there is a TARGET column to hang it on.
+    fn tree(&self) -> bool {
+        !self.list && !self.raw && !self.pairs && self.cols.contains(&Column::Target)
+    }
+
+    fn table(&self) -> Table<'_, Column> {
+        Table {
+            cols: &self.cols,
+            noheadings: self.noheadings,
+            tree: self.tree().then_some(Column::Target),
+            ascii: self.ascii,
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// What `--poll` saw happen to a filesystem.
+struct Change<'a> {
+    action: Action,
+    old: Option<&'a Mount>,
+}
+
+/// Builds the row of a filesystem, the ones mounted below it are added by the caller.
+fn mount_row(mount: &Mount, change: Option<&Change>, opts: &OutputOptions) -> Row {
+    let mut stat = None;
+    let values = opts
+        .cols
+        .iter()
+        .map(|col| get_value(mount, change, col, &mut stat, opts))
+        .collect();
+
+    Row {
+        values,
+        children: Vec::new(),
+    }
+}
+
+fn statvfs(path: &str) -> Option<libc::statvfs> {
+    let path = CString::new(path).ok()?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } == -1 {
+        return None;
+    }
+    Some(stat)
+}
+
+fn get_value(
+    mount: &Mount,
+    change: Option<&Change>,
+    col: &Column,
+    stat: &mut Option<Option<libc::statvfs>>,
+    opts: &OutputOptions,
+) -> Option<String> {
+    let size = |bytes: u64| {
+        if opts.bytes {
+            bytes.to_string()
+        } else {
+            uutable::size_to_human_string(bytes)
+        }
+    };
+    let mut stat = || {
+        if !opts.statvfs {
+            return None;
+        }
+        *stat.get_or_insert_with(|| statvfs(&mount.target))
+    };
+
+    match col {
+        Column::Source => match &mount.root {
+            // Bind mounts show which directory of the filesystem is mounted.
+            Some(root) if root != "/" && !opts.nofsroot => {
+                Some(format!("{}[{}]", mount.source, root))
+            }
+            _ => Some(mount.source.clone()),
+        },
+        Column::Target => Some(mount.target.clone()),
+        Column::FsType => Some(mount.fstype.clone()),
+        Column::Options => Some(mount.options()),
+        Column::VfsOptions => mount.vfs_options.clone(),
+        Column::FsOptions => Some(mount.fs_options.clone()),
+        Column::MajorMinor => mount
+            .device
+            .map(|(major, minor)| format!("{major}:{minor}")),
+        Column::FsRoot => mount.root.clone(),
+        Column::Id => mount.id.map(|id| id.to_string()),
+        Column::Parent => mount.parent_id.map(|id| id.to_string()),
+        Column::OptFields => {
+            Some(mount.optional_fields.join(" ")).filter(|fields| !fields.is_empty())
+        }
+        Column::Propagation => {
+            mount.id?;
+            let mut flags: Vec<&str> = Vec::new();
+            for field in &mount.optional_fields {
+                match field.split_once(':').map_or(field.as_str(), |(tag, _)| tag) {
+                    "shared" => flags.push("shared"),
+                    "master" => flags.push("slave"),
+                    "unbindable" => flags.push("unbindable"),
+                    _ => (),
+                }
+            }
+            if flags.is_empty() {
+                flags.push("private");
+            }
+            Some(flags.join(","))
+        }
+        Column::Freq => mount.freq.map(|freq| freq.to_string()),
+        Column::PassNo => mount.passno.map(|passno| passno.to_string()),
+        Column::Size => stat().map(|stat| size(stat.f_blocks.saturating_mul(stat.f_frsize))),
+        Column::Avail => stat().map(|stat| size(stat.f_bavail.saturating_mul(stat.f_frsize))),
+        Column::Used => stat().map(|stat| {
+            let used = stat.f_blocks.saturating_sub(stat.f_bfree);
+            size(used.saturating_mul(stat.f_frsize))
+        }),
+        Column::UsePercent => stat().map(|stat| {
+            let used = stat.f_blocks.saturating_sub(stat.f_bfree);
+            let percent = match stat.f_blocks {
+                0 => 0.0,
+                blocks => used as f64 / blocks as f64 * 100.0,
+            };
+            format!("{percent:.0}%")
+        }),
+        Column::Action => change.map(|change| change.action.as_str().to_string()),
+        Column::OldTarget => change
+            .filter(|change| change.action == Action::Move)
+            .and_then(|change| Some(change.old?.target.clone())),
+        Column::OldOptions => change
+            .filter(|change| change.action == Action::Remount)
+            .and_then(|change| Some(change.old?.options())),
+    }
+}
+
+// Builds the rows of the filesystems mounted on `parent`. Filesystems which don't match the
+// filter are left out, and the ones below them move up to take their place.
+fn tree_rows(mounts: &[Mount], parent: &Mount, filter: &Filter, opts: &OutputOptions) -> Vec<Row> {
+    let mut rows = Vec::new();
+
+    let children = mounts
+        .iter()
+        .filter(|mount| mount.parent_id == parent.id && mount.id != parent.id);
+    for child in children {
+        let grandchildren = tree_rows(mounts, child, filter, opts);
+        if filter.matches(child) {
+            let mut row = mount_row(child, None, opts);
+            row.children = grandchildren;
+            rows.push(row);
+        } else {
+            rows.extend(grandchildren);
+        }
+    }
+
+    rows
+}
+
+fn build_rows(
+    mounts: &[Mount],
+    filter: &Filter,
+    first_only: bool,
+    opts: &OutputOptions,
+) -> Vec<Row> {
+    // Only mountinfo knows how filesystems are stacked on top of each other.
+    let is_tree = opts.tree() && !first_only && mounts.iter().all(|mount| mount.id.is_some());
+    if !is_tree {
+        let matching = mounts.iter().filter(|mount| filter.matches(mount));
+        let limit = if first_only { 1 } else { usize::MAX };
+        return matching
+            .take(limit)
+            .map(|mount| mount_row(mount, None, opts))
+            .collect();
+    }
+
+    // The root of the tree has no parent in the table, or is its own parent.
+    let is_root = |mount: &Mount| {
+        mount.parent_id == mount.id || !mounts.iter().any(|other| other.id == mount.parent_id)
+    };
+
+    let mut rows = Vec::new();
+    for root in mounts.iter().filter(|mount| is_root(mount)) {
+        let children = tree_rows(mounts, root, filter, opts);
+        if filter.matches(root) {
+            let mut row = mount_row(root, None, opts);
+            row.children = children;
+            rows.push(row);
+        } else {
+            rows.extend(children);
+        }
+    }
+    rows
+}
+
+fn print_rows(rows: &[Row], opts: &OutputOptions) {
+    let table = opts.table();
+    if opts.json {
+        table.print_json("filesystems", rows);
+    } else if opts.pairs {
+        table.print_pairs(rows);
+    } else if opts.raw {
+        table.print_raw(rows);
+    } else {
+        table.print_table(rows);
+    }
+}
+
+/// Reads a mount table, warning about the lines which can't be parsed.
+fn read_table(path: &str) -> Result<Vec<Mount>, FindmntError> {
+    let content =
+        fs::read_to_string(path).map_err(|err| FindmntError::Read(path.to_string(), err))?;
+
+    let (mounts, errors) = parse_table(&content, TableFormat::detect(&content));
+    for (line, err) in errors {
+        show_warning!("{}", FindmntError::Parse(path.to_string(), line, err));
+    }
+    Ok(mounts)
+}
+
+// Mounts keep their ID when they are moved or remounted.
+fn diff_tables<'a>(
+    old: &'a [Mount],
+    new: &'a [Mount],
+) -> Vec<(Action, &'a Mount, Option<&'a Mount>)> {
+    let same = |a: &Mount, b: &Mount| match (a.id, b.id) {
+        (Some(a), Some(b)) => a == b,
+        _ => a.target == b.target,
+    };
+
+    let mut changes = Vec::new();
+    for mount in old {
+        if !new.iter().any(|other| same(mount, other)) {
+            changes.push((Action::Umount, mount, None));
+        }
+    }
+    for mount in new {
+        match old.iter().find(|other| same(mount, other)) {
+            None => changes.push((Action::Mount, mount, None)),
+            Some(old) if old.target != mount.target => {
+                changes.push((Action::Move, mount, Some(old)));
+            }
+            Some(old) if old.options() != mount.options() => {
+                changes.push((Action::Remount, mount, Some(old)));
+            }
+            Some(_) => (),
+        }
+    }
+    changes
+}
+
+// The kernel flags the mount table with POLLPRI whenever it changes.
+fn poll_table(
+    path: &str,
+    actions: &[Action],
+    timeout: i32,
+    filter: &Filter,
+    first_only: bool,
+    mut opts: OutputOptions,
+) -> UResult<()> {
+    let file = File::open(path).map_err(|err| FindmntError::Read(path.to_string(), err))?;
+    let mut mounts = read_table(path)?;
+    let mut found = false;
+
+    loop {
+        let mut fds = [libc::pollfd {
+            fd: file.as_raw_fd(),
+            events: libc::POLLPRI | libc::POLLERR,
+            revents: 0,
+        }];
+        let ret = unsafe { libc::poll(fds.as_mut_ptr(), 1, timeout) };
+        if ret == -1 {
+            let err = io::Error::last_os_error();
+            if err.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(FindmntError::Poll(err).into());
+        }
+        if ret == 0 {
+            break;
+        }
+
+        let new_mounts = read_table(path)?;
+        let rows: Vec<_> = diff_tables(&mounts, &new_mounts)
+            .into_iter()
+            .filter(|(action, mount, _)| actions.contains(action) && filter.matches(mount))
+            .map(|(action, mount, old)| mount_row(mount, Some(&Change { action, old }), &opts))
+            .collect();
+
+        if !rows.is_empty() {
+            print_rows(&rows, &opts);
+            // Only the first batch of changes has headings.
+            opts.noheadings = true;
+            found = true;
+            if first_only {
+                break;
+            }
+        }
+        mounts = new_mounts;
+    }
+
+    if !found {
+        set_exit_code(1);
+    }
+    Ok(())
+}
+
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches: ArgMatches = uu_app().try_get_matches_from(args)?;
+
+    let (path, kernel) = if let Some(path) = matches.get_one::<String>(options::TAB_FILE) {
+        (path.as_str(), false)
+    } else if matches.get_flag(options::FSTAB) {
+        (_PATH_MNTTAB, false)
+    } else if matches.get_flag(options::MTAB) {
+        (_PATH_MOUNTED, false)
+    } else {
+        (_PATH_PROC_MOUNTINFO, true)
+    };
+
+    let args: Vec<&String> = matches
+        .get_many::<String>(options::ARGS)
+        .unwrap_or_default()
+        .collect();
+    let mut filter = Filter {
+        types: matches.get_one::<String>(options::TYPES).cloned(),
+        options: matches.get_one::<String>(options::OPTIONS).cloned(),
+        source: matches.get_one::<String>(options::SOURCE).cloned(),
+        target: matches.get_one::<String>(options::TARGET).cloned(),
+        source_or_target: None,
+    };
+    match args.as_slice() {
+        [arg] => filter.source_or_target = Some(arg.to_string()),
+        [source, target] => {
+            filter.source = Some(source.to_string());
+            filter.target = Some(target.to_string());
+        }
+        _ => (),
+    }
+
+    let poll = matches.get_many::<Action>(options::POLL);
+    let raw = matches.get_flag(options::RAW);
+    let pairs = matches.get_flag(options::PAIRS);
+    let opts = OutputOptions {
+        cols: if let Some(cols) = matches.get_one::<Vec<Column>>(options::OUTPUT) {
+            cols.clone()
+        } else if matches.get_flag(options::OUTPUT_ALL) {
+            Column::ALL.to_vec()
+        } else if poll.is_some() {
+            Vec::from(DEFAULT_POLL_COLS)
+        } else {
+            Vec::from(DEFAULT_COLS)
+        },
+        bytes: matches.get_flag(options::BYTES),
+        json: matches.get_flag(options::JSON),
+        list: matches.get_flag(options::LIST) || raw || pairs || filter.is_lookup(),
+        noheadings: matches.get_flag(options::NOHEADINGS),
+        nofsroot: matches.get_flag(options::NOFSROOT),
+        pairs,
+        raw,
+        ascii: matches.get_flag(options::ASCII),
+        statvfs: kernel,
+    };
+    let first_only = matches.get_flag(options::FIRST_ONLY);
+
+    if let Some(actions) = poll {
+        let mut actions: Vec<Action> = actions.copied().collect();
+        if actions.is_empty() {
+            actions = Action::value_variants().to_vec();
+        }
+        let timeout = matches
+            .get_one::<i32>(options::TIMEOUT)
+            .copied()
+            .unwrap_or(-1);
+        return poll_table(path, &actions, timeout, &filter, first_only, opts);
+    }
+
+    let mounts = read_table(path)?;
+
+    // The target may be any path, the filesystem containing it is the one looked up.
+    let mut last_only = false;
+    if let Some(target) = &filter.target
+        && let Some(mountpoint) = find_mountpoint(&mounts, target)
+    {
+        filter.target = Some(mountpoint);
+        // Only the topmost of the filesystems mounted on top of each other is visible.
+        last_only = args.len() < 2;
+    }
+
+    let mut rows = build_rows(&mounts, &filter, first_only, &opts);
+    if last_only {
+        rows.drain(..rows.len().saturating_sub(1));
+    }
+
+    if rows.is_empty() {
+        set_exit_code(1);
+        return Ok(());
+    }
+
+    print_rows(&rows, &opts);
+    Ok(())
+}
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::ASCII)
+                .short('a')
+                .long(options::ASCII)
+                .help("use ASCII chars for tree formatting")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::BYTES)
+                .short('b')
+                .long(options::BYTES)
+                .help("print sizes in bytes rather than in human readable format")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::FIRST_ONLY)
+                .short('f')
+                .long(options::FIRST_ONLY)
+                .help("print the first found filesystem only")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::TAB_FILE)
+                .short('F')
+                .long(options::TAB_FILE)
+                .value_name("path")
+                .help("alternative file for -s, -m or -k options")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new(options::FSTAB)
+                .short('s')
+                .long(options::FSTAB)
+                .help("search in static table of filesystems")
+                .conflicts_with_all([options::MTAB, options::KERNEL])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::MTAB)
+                .short('m')
+                .long(options::MTAB)
+                .help("search in table of mounted filesystems")
+                .conflicts_with(options::KERNEL)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::KERNEL)
+                .short('k')
+                .long(options::KERNEL)
+                .help("search in kernel table of mounted filesystems (default)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::JSON)
+                .short('J')
+                .long(options::JSON)
+                .help("use JSON output format")
+                .conflicts_with_all([options::PAIRS, options::RAW])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::LIST)
+                .short('l')
+                .long(options::LIST)
+                .help("use list format output")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NOHEADINGS)
+                .short('n')
+                .long(options::NOHEADINGS)
+                .help("don't print column headings")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::OPTIONS)
+                .short('O')
+                .long(options::OPTIONS)
+                .value_name("list")
+                .help("limit the set of filesystems by mount options")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new(options::OUTPUT)
+                .short('o')
+                .long(options::OUTPUT)
+                .value_name("list")
+                .help("output columns")
+                .value_parser(|list: &str| uutable::parse_columns(list, DEFAULT_COLS)),
+        )
+        .arg(
+            Arg::new(options::OUTPUT_ALL)
+                .long(options::OUTPUT_ALL)
+                .help("output all available columns")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::PAIRS)
+                .short('P')
+                .long(options::PAIRS)
+                .help("use key=\"value\" output format")
+                .conflicts_with(options::RAW)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::POLL)
+                .short('p')
+                .long(options::POLL)
+                .value_name("list")
+                .help("monitor changes in table of mounted filesystems")
+                .num_args(0..=1)
+                .require_equals(true)
+                .value_delimiter(',')
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(Action)),
+        )
+        .arg(
+            Arg::new(options::RAW)
+                .short('r')
+                .long(options::RAW)
+                .help("use raw output format")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::SOURCE)
+                .short('S')
+                .long(options::SOURCE)
+                .value_name("string")
+                .help("the device to mount (by name, maj:min, LABEL=, UUID=, ...)")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new(options::TARGET)
+                .short('T')
+                .long(options::TARGET)
+                .value_name("path")
+                .help("the path to the filesystem to use")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new(options::TYPES)
+                .short('t')
+                .long(options::TYPES)
+                .value_name("list")
+                .help("limit the set of filesystems by FS types")
+                .value_parser(value_parser!(String)),
+        )
+        .arg(
+            Arg::new(options::NOFSROOT)
+                .short('v')
+                .long(options::NOFSROOT)
+                .help("don't print [/dir] for bind or btrfs mounts")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::TIMEOUT)
+                .short('w')
+                .long(options::TIMEOUT)
+                .value_name("num")
+                .help("upper limit in milliseconds that --poll will block")
+                .requires(options::POLL)
+                .value_parser(value_parser!(i32)),
+        )
+        .arg(
+            Arg::new(options::ARGS)
+                .value_name("device|mountpoint")
+                .num_args(0..=2)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(String)),
+        )
+        .after_help(uutable::columns_help::<Column>())
+}
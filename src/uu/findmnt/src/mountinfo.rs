@@ -0,0 +1,303 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::errors::ParseMountError;
+
+/// The two formats of mount tables: /proc/self/mountinfo, and the one shared by /etc/fstab,
+/// /etc/mtab and /proc/self/mounts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableFormat {
+    MountInfo,
+    Fstab,
+}
+
+impl TableFormat {
+    /// Guesses the format from the first entry: mountinfo lines start with the mount ID.
+    pub fn detect(content: &str) -> Self {
+        let first_entry = content
+            .lines()
+            .map(str::trim_start)
+            .find(|line| !line.is_empty() && !line.starts_with('#'));
+
+        match first_entry {
+            Some(line) if Mount::from_mountinfo(line).is_ok() => Self::MountInfo,
+            _ => Self::Fstab,
+        }
+    }
+}
+
+/// An entry of a mount table. Which fields are set depends on the format of the table.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Mount {
+    pub id: Option<u32>,
+    pub parent_id: Option<u32>,
+    pub device: Option<(u32, u32)>,
+    /// The directory of the filesystem which is mounted, "/" unless it is a bind mount.
+    pub root: Option<String>,
+    pub target: String,
+    pub source: String,
+    pub fstype: String,
+    /// Per-mount options, only known for mountinfo.
+    pub vfs_options: Option<String>,
+    /// Superblock options for mountinfo, and all the options for fstab.
+    pub fs_options: String,
+    /// Optional fields of mountinfo, e.g. "shared:1" or "master:2".
+    pub optional_fields: Vec<String>,
+    pub freq: Option<u32>,
+    pub passno: Option<u32>,
+}
+
+impl Mount {
+    /// Parses a line of /proc/self/mountinfo, see proc_pid_mountinfo(5):
+    /// `36 35 98:0 /mnt1 /mnt2 rw,noatime master:1 - ext3 /dev/root rw,errors=continue`
+    pub fn from_mountinfo(line: &str) -> Result<Self, ParseMountError> {
+        let mut fields = line.split_whitespace();
+        let mut next_field = |name| fields.next().ok_or(ParseMountError::MissingField(name));
+
+        let id = parse_number(next_field("mount ID")?, "mount ID")?;
+        let parent_id = parse_number(next_field("parent ID")?, "parent ID")?;
+
+        let device = next_field("major:minor")?;
+        let device = device
+            .split_once(':')
+            .and_then(|(major, minor)| Some((major.parse().ok()?, minor.parse().ok()?)))
+            .ok_or_else(|| ParseMountError::InvalidField("major:minor", device.to_string()))?;
+
+        let root = unescape(next_field("root")?);
+        let target = unescape(next_field("mount point")?);
+        let vfs_options = next_field("mount options")?.to_string();
+
+        // A variable number of optional fields is terminated by a single hyphen.
+        let mut optional_fields = Vec::new();
+        loop {
+            match next_field("separator")? {
+                "-" => break,
+                field => optional_fields.push(field.to_string()),
+            }
+        }
+
+        let fstype = unescape(next_field("filesystem type")?);
+        let source = unescape(next_field("mount source")?);
+        let fs_options = next_field("super options")?.to_string();
+
+        Ok(Self {
+            id: Some(id),
+            parent_id: Some(parent_id),
+            device: Some(device),
+            root: Some(root),
+            target,
+            source,
+            fstype,
+            vfs_options: Some(vfs_options),
+            fs_options,
+            optional_fields,
+            ..Default::default()
+        })
+    }
+
+    /// Parses a line of /etc/fstab or /proc/self/mounts, see fstab(5):
+    /// `/dev/sda1 /boot vfat defaults 0 2`
+    pub fn from_fstab(line: &str) -> Result<Self, ParseMountError> {
+        let mut fields = line.split_whitespace();
+        let mut next_field = |name| fields.next().ok_or(ParseMountError::MissingField(name));
+
+        let source = unescape(next_field("mount source")?);
+        let target = unescape(next_field("mount point")?);
+        let fstype = unescape(next_field("filesystem type")?);
+
+        // The remaining fields are optional.
+        let fs_options = fields.next().unwrap_or("defaults").to_string();
+        let freq = fields
+            .next()
+            .map_or(Ok(0), |freq| parse_number(freq, "dump frequency"))?;
+        let passno = fields
+            .next()
+            .map_or(Ok(0), |passno| parse_number(passno, "pass number"))?;
+
+        Ok(Self {
+            target,
+            source,
+            fstype,
+            fs_options,
+            freq: Some(freq),
+            passno: Some(passno),
+            ..Default::default()
+        })
+    }
+
+    /// All options, the way mount(8) shows them: the per-mount options first, then the
+    /// filesystem specific ones.
+    pub fn options(&self) -> String {
+        let Some(vfs_options) = &self.vfs_options else {
+            return self.fs_options.clone();
+        };
+
+        // "rw" and "ro" are part of both lists.
+        let fs_options = self
+            .fs_options
+            .split(',')
+            .filter(|option| !matches!(*option, "rw" | "ro" | ""));
+        let options: Vec<&str> = vfs_options.split(',').chain(fs_options).collect();
+        options.join(",")
+    }
+
+    /// The value of a mount option, or `Some("")` for options without a value.
+    pub fn option(&self, name: &str) -> Option<String> {
+        self.options().split(',').find_map(|option| {
+            let (key, value) = option.split_once('=').unwrap_or((option, ""));
+            (key == name).then(|| value.to_string())
+        })
+    }
+}
+
+/// Parses a whole mount table, skipping comments and blank lines. Lines which can't be parsed
+/// are returned separately with their line number, so that the caller can report them.
+pub fn parse_table(
+    content: &str,
+    format: TableFormat,
+) -> (Vec<Mount>, Vec<(usize, ParseMountError)>) {
+    let mut mounts = Vec::new();
+    let mut errors = Vec::new();
+
+    for (i, line) in content.lines().enumerate() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mount = match format {
+            TableFormat::MountInfo => Mount::from_mountinfo(line),
+            TableFormat::Fstab => Mount::from_fstab(line),
+        };
+        match mount {
+            Ok(mount) => mounts.push(mount),
+            Err(err) => errors.push((i + 1, err)),
+        }
+    }
+
+    (mounts, errors)
+}
+
+fn parse_number(value: &str, name: &'static str) -> Result<u32, ParseMountError> {
+    value
+        .parse()
+        .map_err(|_| ParseMountError::InvalidField(name, value.to_string()))
+}
+
+/// Undoes the octal escapes of the kernel, which escapes spaces, tabs, newlines and backslashes
+/// in the paths of the mount table and /proc/swaps, e.g. "\040".
+pub fn unescape(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 4).filter(|digits| {
+            bytes[i] == b'\\' && digits.iter().all(|digit| (b'0'..=b'7').contains(digit))
+        });
+        match octal.and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok())
+        {
+            Some(byte) => {
+                unescaped.push(byte);
+                i += 4;
+            }
+            None => {
+                unescaped.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mountinfo_line() {
+        let mount = Mount::from_mountinfo(
+            "36 35 98:0 /mnt1 /mnt\\0402 rw,noatime master:1 shared:7 - ext3 /dev/root rw,errors=continue",
+        )
+        .unwrap();
+
+        assert_eq!(mount.id, Some(36));
+        assert_eq!(mount.parent_id, Some(35));
+        assert_eq!(mount.device, Some((98, 0)));
+        assert_eq!(mount.root.as_deref(), Some("/mnt1"));
+        assert_eq!(mount.target, "/mnt 2");
+        assert_eq!(mount.vfs_options.as_deref(), Some("rw,noatime"));
+        assert_eq!(mount.optional_fields, ["master:1", "shared:7"]);
+        assert_eq!(mount.fstype, "ext3");
+        assert_eq!(mount.source, "/dev/root");
+        assert_eq!(mount.fs_options, "rw,errors=continue");
+        assert_eq!(mount.options(), "rw,noatime,errors=continue");
+        assert_eq!(mount.option("errors").as_deref(), Some("continue"));
+        assert_eq!(mount.option("noatime").as_deref(), Some(""));
+        assert_eq!(mount.option("atime"), None);
+    }
+
+    #[test]
+    fn test_invalid_mountinfo_lines() {
+        assert_eq!(
+            Mount::from_mountinfo("36 35 98:0 / /mnt rw - ext3 /dev/root"),
+            Err(ParseMountError::MissingField("super options"))
+        );
+        assert_eq!(
+            Mount::from_mountinfo("36 35 98:0 / /mnt rw shared:1 ext3 /dev/root rw"),
+            Err(ParseMountError::MissingField("separator"))
+        );
+        assert_eq!(
+            Mount::from_mountinfo("36 35 98 / /mnt rw - ext3 /dev/root rw"),
+            Err(ParseMountError::InvalidField("major:minor", "98".into()))
+        );
+        assert_eq!(
+            Mount::from_mountinfo("x 35 98:0 / /mnt rw - ext3 /dev/root rw"),
+            Err(ParseMountError::InvalidField("mount ID", "x".into()))
+        );
+    }
+
+    #[test]
+    fn test_fstab_line() {
+        let mount = Mount::from_fstab("UUID=1234\t/home\\040dir  ext4 noatime 0 2").unwrap();
+        assert_eq!(mount.source, "UUID=1234");
+        assert_eq!(mount.target, "/home dir");
+        assert_eq!(mount.fstype, "ext4");
+        assert_eq!(mount.options(), "noatime");
+        assert_eq!((mount.freq, mount.passno), (Some(0), Some(2)));
+
+        let mount = Mount::from_fstab("proc /proc proc").unwrap();
+        assert_eq!(mount.options(), "defaults");
+        assert_eq!((mount.freq, mount.passno), (Some(0), Some(0)));
+
+        assert_eq!(
+            Mount::from_fstab("/dev/sda1 /boot"),
+            Err(ParseMountError::MissingField("filesystem type"))
+        );
+        assert_eq!(
+            Mount::from_fstab("/dev/sda1 /boot vfat defaults x"),
+            Err(ParseMountError::InvalidField("dump frequency", "x".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_table() {
+        let content = "# <source> <target> <type>\n\
+                       \n\
+                       /dev/sda1 / ext4 defaults 0 1\n\
+                       broken\n\
+                       \t/dev/sda2 none swap sw 0 0\n";
+
+        assert_eq!(TableFormat::detect(content), TableFormat::Fstab);
+        let (mounts, errors) = parse_table(content, TableFormat::Fstab);
+        let targets: Vec<_> = mounts.iter().map(|mount| mount.target.as_str()).collect();
+        assert_eq!(targets, ["/", "none"]);
+        assert_eq!(errors, [(4, ParseMountError::MissingField("mount point"))]);
+
+        let content = "22 1 8:1 / / rw - ext4 /dev/sda1 rw\n";
+        assert_eq!(TableFormat::detect(content), TableFormat::MountInfo);
+    }
+}
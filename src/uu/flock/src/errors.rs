@@ -0,0 +1,62 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+
+// Exit codes from sysexits.h, used by the original implementation.
+pub(crate) const EX_DATAERR: i32 = 65;
+pub(crate) const EX_NOINPUT: i32 = 66;
+pub(crate) const EX_UNAVAILABLE: i32 = 69;
+pub(crate) const EX_OSERR: i32 = 71;
+pub(crate) const EX_CANTCREAT: i32 = 73;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FlockError {
+    #[error("bad file descriptor: '{0}'")]
+    BadFileDescriptor(String),
+
+    #[error("cannot open lock file {0}: {1}")]
+    Open(String, io::Error),
+
+    #[error("{0}: {1}")]
+    Lock(String, io::Error),
+
+    #[error("failed to execute {0}: {1}")]
+    Execute(String, io::Error),
+
+    #[error("failed to wait on PID {0}: {1}")]
+    Wait(u32, io::Error),
+}
+
+impl uucore::error::UError for FlockError {
+    fn code(&self) -> i32 {
+        let is_out_of_resources = |err: &io::Error| {
+            matches!(
+                err.raw_os_error(),
+                Some(libc::ENOMEM | libc::EMFILE | libc::ENFILE | libc::ENOLCK)
+            )
+        };
+
+        match self {
+            Self::BadFileDescriptor(_) => 1,
+            Self::Open(_, err) | Self::Lock(_, err) | Self::Execute(_, err)
+                if is_out_of_resources(err) =>
+            {
+                EX_OSERR
+            }
+            Self::Open(_, err) => match err.raw_os_error() {
+                Some(libc::EROFS | libc::ENOSPC) => EX_CANTCREAT,
+                _ => EX_NOINPUT,
+            },
+            Self::Lock(_, _) => EX_DATAERR,
+            Self::Execute(_, _) => EX_UNAVAILABLE,
+            Self::Wait(_, _) => EX_OSERR,
+        }
+    }
+
+    fn usage(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,351 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+mod errors;
+
+use std::ffi::{OsStr, OsString};
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::os::fd::{AsRawFd, RawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process;
+use std::time::{Duration, Instant};
+
+use clap::{Arg, ArgAction, ArgMatches, Command, crate_version, value_parser};
+use uu_lslocks::LockMode;
+use uucore::error::{UResult, set_exit_code};
+use uucore::{format_usage, help_about, help_usage};
+
+use crate::errors::{EX_OSERR, FlockError};
+
+mod options {
+    pub const ARGUMENTS: &str = "arguments";
+    pub const CLOSE: &str = "close";
+    pub const COMMAND: &str = "command";
+    pub const CONFLICT_EXIT_CODE: &str = "conflict-exit-code";
+    pub const EXCLUSIVE: &str = "exclusive";
+    pub const FILE: &str = "file";
+    pub const NO_FORK: &str = "no-fork";
+    pub const NONBLOCK: &str = "nonblock";
+    pub const SHARED: &str = "shared";
+    pub const TIMEOUT: &str = "timeout";
+    pub const UNLOCK: &str = "unlock";
+}
+
+const ABOUT: &str = help_about!("flock.md");
+const USAGE: &str = help_usage!("flock.md");
+
+// flock(2) has no timeout of its own, so waiting for a limited time is done by polling.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Either a file we opened ourselves, or a file descriptor inherited from the shell.
+enum LockTarget {
+    File {
+        path: OsString,
+        file: File,
+        read_write: bool,
+    },
+    Descriptor(RawFd),
+}
+
+impl LockTarget {
+    fn open(path: &OsStr, read_write: bool) -> Result<Self, FlockError> {
+        let mut open_options = OpenOptions::new();
+        open_options
+            .read(true)
+            .write(read_write)
+            .custom_flags(libc::O_NOCTTY | libc::O_CREAT)
+            .mode(0o666);
+
+        // O_CREAT fails on directories, which can only be opened read-only.
+        let result = match open_options.open(path) {
+            Err(err) if err.raw_os_error() == Some(libc::EISDIR) => OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NOCTTY)
+                .open(path)
+                .map(|file| (file, false)),
+            result => result.map(|file| (file, read_write)),
+        };
+
+        let (file, read_write) =
+            result.map_err(|err| FlockError::Open(path.to_string_lossy().into_owned(), err))?;
+
+        Ok(Self::File {
+            path: path.to_owned(),
+            file,
+            read_write,
+        })
+    }
+
+    fn fd(&self) -> RawFd {
+        match self {
+            Self::File { file, .. } => file.as_raw_fd(),
+            Self::Descriptor(fd) => *fd,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            Self::File { path, .. } => path.to_string_lossy().into_owned(),
+            Self::Descriptor(fd) => fd.to_string(),
+        }
+    }
+
+    // flock(2) is emulated with fcntl(2) on NFSv4, which needs the file to be opened for writing
+    // to take an exclusive lock.
+    fn reopen_read_write(&mut self) -> Option<Result<(), FlockError>> {
+        let Self::File {
+            path,
+            read_write: false,
+            ..
+        } = self
+        else {
+            return None;
+        };
+
+        OpenOptions::new().write(true).open(&path).ok()?;
+        Some(Self::open(&path.clone(), true).map(|target| *self = target))
+    }
+
+    fn lock(&self, mode: LockMode, nonblock: bool) -> io::Result<()> {
+        let mut operation = match mode {
+            LockMode::Read => libc::LOCK_SH,
+            LockMode::Write => libc::LOCK_EX,
+            LockMode::Unlock => libc::LOCK_UN,
+        };
+
+        if nonblock {
+            operation |= libc::LOCK_NB;
+        }
+
+        if unsafe { libc::flock(self.fd(), operation) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+
+    // Files are opened with O_CLOEXEC, but the command should keep holding the lock.
+    fn inherit(&self) -> io::Result<()> {
+        let fd = self.fd();
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+        if flags == -1 || unsafe { libc::fcntl(fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC) } == -1
+        {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("invalid timeout value: '{s}'"))
+}
+
+// Returns the command to run and its name, for error messages.
+fn command_to_run(matches: &ArgMatches) -> Option<(process::Command, OsString)> {
+    if let Some(command) = matches.get_one::<OsString>(options::COMMAND) {
+        let shell = std::env::var_os("SHELL").unwrap_or_else(|| OsString::from("/bin/sh"));
+        let mut to_run = process::Command::new(&shell);
+        to_run.arg("-c").arg(command);
+        return Some((to_run, shell));
+    }
+
+    let mut arguments = matches.get_many::<OsString>(options::ARGUMENTS)?;
+    let executable = arguments.next()?;
+    let mut to_run = process::Command::new(executable);
+    to_run.args(arguments);
+    Some((to_run, executable.clone()))
+}
+
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches = uu_app().try_get_matches_from(args)?;
+
+    let mode = if matches.get_flag(options::SHARED) {
+        LockMode::Read
+    } else if matches.get_flag(options::UNLOCK) {
+        LockMode::Unlock
+    } else {
+        LockMode::Write
+    };
+
+    let timeout = matches.get_one::<Duration>(options::TIMEOUT).copied();
+    let nonblock = matches.get_flag(options::NONBLOCK) || timeout == Some(Duration::ZERO);
+    let conflict_exit_code = *matches.get_one::<u8>(options::CONFLICT_EXIT_CODE).unwrap();
+    let close = matches.get_flag(options::CLOSE);
+    let no_fork = matches.get_flag(options::NO_FORK);
+
+    let file = matches.get_one::<OsString>(options::FILE).unwrap();
+    let command = command_to_run(&matches);
+
+    let mut target = if command.is_some() {
+        LockTarget::open(file, false)?
+    } else {
+        let fd = file
+            .to_str()
+            .and_then(|fd| fd.parse().ok())
+            .ok_or_else(|| FlockError::BadFileDescriptor(file.to_string_lossy().into_owned()))?;
+        LockTarget::Descriptor(fd)
+    };
+
+    let deadline = timeout
+        .filter(|_| !nonblock)
+        .map(|timeout| Instant::now() + timeout);
+
+    while let Err(err) = target.lock(mode, nonblock || deadline.is_some()) {
+        match err.raw_os_error() {
+            Some(libc::EWOULDBLOCK) => {
+                let now = Instant::now();
+                match deadline {
+                    Some(deadline) if now < deadline => {
+                        std::thread::sleep(POLL_INTERVAL.min(deadline - now));
+                    }
+                    _ => {
+                        set_exit_code(conflict_exit_code.into());
+                        return Ok(());
+                    }
+                }
+            }
+            Some(libc::EINTR) => {}
+            Some(libc::EIO | libc::EBADF) => match target.reopen_read_write() {
+                Some(result) => result?,
+                None => return Err(FlockError::Lock(target.name(), err).into()),
+            },
+            _ => return Err(FlockError::Lock(target.name(), err).into()),
+        }
+    }
+
+    let Some((mut to_run, executable)) = command else {
+        return Ok(());
+    };
+    let executable = executable.to_string_lossy().into_owned();
+
+    if !close {
+        target
+            .inherit()
+            .map_err(|err| FlockError::Lock(target.name(), err))?;
+    }
+
+    if no_fork {
+        let err = to_run.exec();
+        return Err(FlockError::Execute(executable, err).into());
+    }
+
+    let mut child = to_run
+        .spawn()
+        .map_err(|err| FlockError::Execute(executable, err))?;
+    let status = child
+        .wait()
+        .map_err(|err| FlockError::Wait(child.id(), err))?;
+
+    let code = status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(EX_OSERR);
+    set_exit_code(code);
+
+    Ok(())
+}
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::SHARED)
+                .short('s')
+                .long(options::SHARED)
+                .help("get a shared lock")
+                .overrides_with_all([options::EXCLUSIVE, options::UNLOCK])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::EXCLUSIVE)
+                .short('x')
+                .short_alias('e')
+                .long(options::EXCLUSIVE)
+                .help("get an exclusive lock (default)")
+                .overrides_with_all([options::SHARED, options::UNLOCK])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::UNLOCK)
+                .short('u')
+                .long(options::UNLOCK)
+                .help("remove a lock")
+                .overrides_with_all([options::SHARED, options::EXCLUSIVE])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NONBLOCK)
+                .short('n')
+                .long(options::NONBLOCK)
+                .alias("nb")
+                .help("fail rather than wait")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::TIMEOUT)
+                .short('w')
+                .long(options::TIMEOUT)
+                .alias("wait")
+                .value_name("secs")
+                .help("wait for a limited amount of time")
+                .value_parser(parse_timeout),
+        )
+        .arg(
+            Arg::new(options::CONFLICT_EXIT_CODE)
+                .short('E')
+                .long(options::CONFLICT_EXIT_CODE)
+                .value_name("number")
+                .help("exit code after conflict or timeout")
+                .value_parser(value_parser!(u8))
+                .default_value("1"),
+        )
+        .arg(
+            Arg::new(options::CLOSE)
+                .short('o')
+                .long(options::CLOSE)
+                .help("close file descriptor before running command")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::COMMAND)
+                .short('c')
+                .long(options::COMMAND)
+                .value_name("command")
+                .help("run a single command string through the shell")
+                .value_parser(value_parser!(OsString))
+                .conflicts_with(options::ARGUMENTS),
+        )
+        .arg(
+            Arg::new(options::NO_FORK)
+                .short('F')
+                .long(options::NO_FORK)
+                .help("execute command without forking")
+                .conflicts_with(options::CLOSE)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::FILE)
+                .value_name("file|directory|fd")
+                .required(true)
+                .value_parser(value_parser!(OsString)),
+        )
+        .arg(
+            Arg::new(options::ARGUMENTS)
+                .value_name("command")
+                .num_args(1..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true)
+                .value_parser(value_parser!(OsString)),
+        )
+}
@@ -0,0 +1,37 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+use std::time::Duration;
+
+use uucore::error::strip_errno;
+
+#[derive(Debug, thiserror::Error)]
+pub enum FsfreezeError {
+    #[error("failed to {0} the filesystem: {msg}", msg = strip_errno(.1))]
+    Ioctl(&'static str, io::Error),
+
+    #[error("failed to block signals: {msg}", msg = strip_errno(.0))]
+    Signals(io::Error),
+
+    #[error("failed to execute {0}: {msg}", msg = strip_errno(.1))]
+    Execute(String, io::Error),
+
+    #[error("failed to wait on PID {0}: {msg}", msg = strip_errno(.1))]
+    Wait(u32, io::Error),
+
+    #[error("timed out after {}s, the filesystem was thawed", .0.as_secs_f64())]
+    Timeout(Duration),
+}
+
+impl uucore::error::UError for FsfreezeError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        false
+    }
+}
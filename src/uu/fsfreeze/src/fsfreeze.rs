@@ -3,41 +3,264 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-use clap::{crate_version, Arg, ArgAction, ArgGroup, Command};
 #[cfg(target_os = "linux")]
-use std::{fs::File, io, os::fd::AsRawFd};
+mod errors;
+
+use clap::{crate_version, Arg, ArgAction, ArgGroup, Command};
+use std::{ffi::OsString, time::Duration};
 #[cfg(target_os = "linux")]
-use uucore::{error::UIoError, libc};
+use std::{
+    fs::File,
+    io,
+    os::fd::AsRawFd,
+    os::unix::process::{CommandExt, ExitStatusExt},
+    process::{self, Child, ExitStatus},
+    ptr,
+    time::Instant,
+};
 use uucore::{error::UResult, format_usage, help_about, help_usage};
+#[cfg(target_os = "linux")]
+use uucore::{
+    error::{set_exit_code, UIoError},
+    libc,
+};
+
+#[cfg(target_os = "linux")]
+use crate::errors::FsfreezeError;
 
 const ABOUT: &str = help_about!("fsfreeze.md");
 const USAGE: &str = help_usage!("fsfreeze.md");
 
+mod options {
+    pub const COMMAND: &str = "command";
+    pub const FREEZE: &str = "freeze";
+    pub const MOUNTPOINT: &str = "mountpoint";
+    pub const TIMEOUT: &str = "timeout";
+    pub const UNFREEZE: &str = "unfreeze";
+}
+
+// Signals which would otherwise kill us while the filesystem is frozen.
+#[cfg(target_os = "linux")]
+const TERMINATING_SIGNALS: [libc::c_int; 4] =
+    [libc::SIGHUP, libc::SIGINT, libc::SIGQUIT, libc::SIGTERM];
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| format!("invalid timeout value: '{s}'"))
+}
+
+#[cfg(target_os = "linux")]
+fn ioctl(file: &File, op_name: &'static str, op_code: u32) -> Result<(), FsfreezeError> {
+    if unsafe { libc::ioctl(file.as_raw_fd(), op_code.into(), 0) } < 0 {
+        return Err(FsfreezeError::Ioctl(op_name, io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn freeze(file: &File) -> Result<(), FsfreezeError> {
+    ioctl(file, "freeze", linux_raw_sys::ioctl::FIFREEZE)
+}
+
+#[cfg(target_os = "linux")]
+fn thaw(file: &File) -> Result<(), FsfreezeError> {
+    ioctl(file, "unfreeze", linux_raw_sys::ioctl::FITHAW)
+}
+
+/// Blocks the terminating signals and SIGCHLD, so that they can be waited for with
+/// sigtimedwait(2) rather than interrupting us. Returns the previous signal mask.
+#[cfg(target_os = "linux")]
+fn block_signals(set: &mut libc::sigset_t) -> Result<libc::sigset_t, FsfreezeError> {
+    let mut old_set: libc::sigset_t = unsafe { std::mem::zeroed() };
+    unsafe {
+        libc::sigemptyset(set);
+        libc::sigaddset(set, libc::SIGCHLD);
+        for signal in TERMINATING_SIGNALS {
+            libc::sigaddset(set, signal);
+        }
+    }
+
+    let ret = unsafe { libc::pthread_sigmask(libc::SIG_BLOCK, set, &mut old_set) };
+    if ret != 0 {
+        return Err(FsfreezeError::Signals(io::Error::from_raw_os_error(ret)));
+    }
+    Ok(old_set)
+}
+
+/// What ended the wait while the filesystem was frozen.
+#[cfg(target_os = "linux")]
+enum Event {
+    Exited(ExitStatus),
+    Signal(libc::c_int),
+    Timeout,
+}
+
+#[cfg(target_os = "linux")]
+fn wait_for_event(
+    set: &libc::sigset_t,
+    child: &mut Option<Child>,
+    deadline: Option<Instant>,
+) -> Result<Event, FsfreezeError> {
+    loop {
+        if let Some(child) = child {
+            let status = child
+                .try_wait()
+                .map_err(|err| FsfreezeError::Wait(child.id(), err))?;
+            if let Some(status) = status {
+                return Ok(Event::Exited(status));
+            }
+        }
+
+        let timeout = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Ok(Event::Timeout);
+                }
+                Some(libc::timespec {
+                    tv_sec: remaining.as_secs() as libc::time_t,
+                    tv_nsec: remaining.subsec_nanos().into(),
+                })
+            }
+            None => None,
+        };
+        let timeout_ptr = timeout.as_ref().map_or(ptr::null(), |timeout| timeout);
+
+        let signal = unsafe { libc::sigtimedwait(set, ptr::null_mut(), timeout_ptr) };
+        match signal {
+            -1 => {
+                let err = io::Error::last_os_error();
+                match err.raw_os_error() {
+                    // The deadline is checked again at the top of the loop.
+                    Some(libc::EAGAIN | libc::EINTR) => (),
+                    _ => return Err(FsfreezeError::Signals(err)),
+                }
+            }
+            libc::SIGCHLD => (),
+            signal => return Ok(Event::Signal(signal)),
+        }
+    }
+}
+
+// Freezes the filesystem for as long as the command runs, or until the timeout expires, and
+// makes sure it is thawed again whatever happens.
+#[cfg(target_os = "linux")]
+fn freeze_while(
+    file: &File,
+    command: Option<Vec<&OsString>>,
+    timeout: Option<Duration>,
+) -> UResult<()> {
+    let mut set: libc::sigset_t = unsafe { std::mem::zeroed() };
+    let old_set = block_signals(&mut set)?;
+
+    freeze(file)?;
+
+    let mut child = match command {
+        Some(command) => {
+            let mut to_run = process::Command::new(command[0]);
+            to_run.args(&command[1..]);
+            // The command must not inherit the blocked signals.
+            unsafe {
+                to_run.pre_exec(move || {
+                    match libc::pthread_sigmask(libc::SIG_SETMASK, &old_set, ptr::null_mut()) {
+                        0 => Ok(()),
+                        err => Err(io::Error::from_raw_os_error(err)),
+                    }
+                });
+            }
+
+            match to_run.spawn() {
+                Ok(child) => Some(child),
+                Err(err) => {
+                    thaw(file)?;
+                    let name = command[0].to_string_lossy().into_owned();
+                    return Err(FsfreezeError::Execute(name, err).into());
+                }
+            }
+        }
+        None => None,
+    };
+
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let event = wait_for_event(&set, &mut child, deadline);
+    // The command is still stopped and reaped when thawing fails, before that is reported.
+    let thawed = thaw(file);
+    let result = event
+        .map_err(Into::into)
+        .and_then(|event| finish_command(event, child.as_mut(), timeout));
+    thawed?;
+    result
+}
+
+// Sets the exit code for how the command ended, or stops it if it ran out of time or if we
+// were interrupted.
+#[cfg(target_os = "linux")]
+fn finish_command(
+    event: Event,
+    child: Option<&mut Child>,
+    timeout: Option<Duration>,
+) -> UResult<()> {
+    let exit_code = |status: ExitStatus| {
+        status
+            .code()
+            .or_else(|| status.signal().map(|signal| 128 + signal))
+            .unwrap_or(1)
+    };
+
+    match (event, child, timeout) {
+        (Event::Exited(status), _, _) => set_exit_code(exit_code(status)),
+        (Event::Timeout, Some(child), Some(timeout)) => {
+            unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+            child
+                .wait()
+                .map_err(|err| FsfreezeError::Wait(child.id(), err))?;
+            return Err(FsfreezeError::Timeout(timeout).into());
+        }
+        // Without a command, the timeout is simply how long the filesystem stays frozen.
+        (Event::Timeout, _, _) => (),
+        (Event::Signal(signal), child, _) => {
+            if let Some(child) = child {
+                unsafe { libc::kill(child.id() as libc::pid_t, signal) };
+                child
+                    .wait()
+                    .map_err(|err| FsfreezeError::Wait(child.id(), err))?;
+            }
+            set_exit_code(128 + signal);
+        }
+    }
+    Ok(())
+}
+
 #[cfg(target_os = "linux")]
 #[uucore::main]
 pub fn uumain(args: impl uucore::Args) -> UResult<()> {
     let matches: clap::ArgMatches = uu_app().try_get_matches_from(args)?;
-    let mountpoint = matches.get_one::<String>("mountpoint").unwrap();
+    let mountpoint = matches.get_one::<String>(options::MOUNTPOINT).unwrap();
     let file = File::open(mountpoint)?;
     let metadata = file.metadata()?;
     if !metadata.is_dir() {
         return Err(UIoError::new(io::ErrorKind::InvalidData, "not a directory"));
     }
 
-    let (op_name, op_code) = if matches.get_flag("freeze") {
-        ("freeze", linux_raw_sys::ioctl::FIFREEZE)
-    } else {
-        ("unfreeze", linux_raw_sys::ioctl::FITHAW)
-    };
+    if matches.get_flag(options::UNFREEZE) {
+        thaw(&file)?;
+        return Ok(());
+    }
 
-    if unsafe { libc::ioctl(file.as_raw_fd(), op_code.into(), 0) } < 0 {
-        uucore::show_error!(
-            "failed to {} the filesystem: {}",
-            op_name,
-            UIoError::from(io::Error::last_os_error())
-        );
+    let command: Option<Vec<&OsString>> = matches
+        .get_many::<OsString>(options::COMMAND)
+        .map(|command| command.collect());
+    let timeout = matches.get_one::<Duration>(options::TIMEOUT).copied();
+
+    if command.is_none() && timeout.is_none() {
+        freeze(&file)?;
+        return Ok(());
     }
-    Ok(())
+
+    freeze_while(&file, command, timeout)
 }
 
 pub fn uu_app() -> Command {
@@ -47,29 +270,45 @@ pub fn uu_app() -> Command {
         .override_usage(format_usage(USAGE))
         .infer_long_args(true)
         .arg(
-            Arg::new("freeze")
+            Arg::new(options::FREEZE)
                 .short('f')
-                .long("freeze")
+                .long(options::FREEZE)
                 .help("freeze the filesystem")
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("unfreeze")
+            Arg::new(options::UNFREEZE)
                 .short('u')
-                .long("unfreeze")
+                .long(options::UNFREEZE)
                 .help("unfreeze the filesystem")
                 .action(ArgAction::SetTrue),
         )
         .arg(
-            Arg::new("mountpoint")
+            Arg::new(options::TIMEOUT)
+                .long(options::TIMEOUT)
+                .value_name("seconds")
+                .help("thaw the filesystem after the given time at the latest")
+                .conflicts_with(options::UNFREEZE)
+                .value_parser(parse_timeout),
+        )
+        .arg(
+            Arg::new(options::MOUNTPOINT)
                 .help("mountpoint of the filesystem")
                 .required(true)
                 .action(ArgAction::Set),
         )
+        .arg(
+            Arg::new(options::COMMAND)
+                .help("command to run while the filesystem is frozen")
+                .conflicts_with(options::UNFREEZE)
+                .trailing_var_arg(true)
+                .num_args(1..)
+                .value_parser(clap::value_parser!(OsString)),
+        )
         .group(
             ArgGroup::new("action")
                 .required(true)
-                .args(["freeze", "unfreeze"]),
+                .args([options::FREEZE, options::UNFREEZE]),
         )
 }
 
@@ -0,0 +1,189 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// Remove this if the tool is ported to Non-UNIX platforms.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::ffi::c_int;
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, crate_version};
+use uu_lsipc::LsIpcError;
+use uucore::{error::UResult, format_usage, help_about, help_usage};
+
+mod options {
+    pub static KEY: &str = "key";
+    pub static MODE: &str = "mode";
+    pub static QUEUE: &str = "queue";
+    pub static SEMAPHORE: &str = "semaphore";
+    pub static SHMEM: &str = "shmem";
+}
+
+const ABOUT: &str = help_about!("ipcmk.md");
+const USAGE: &str = help_usage!("ipcmk.md");
+
+const DEFAULT_MODE: c_int = 0o644;
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::SHMEM)
+                .short('M')
+                .long(options::SHMEM)
+                .value_name("size")
+                .value_parser(parse_size)
+                .action(ArgAction::Set)
+                .help("create shared memory segment of size <size>"),
+        )
+        .arg(
+            Arg::new(options::SEMAPHORE)
+                .short('S')
+                .long(options::SEMAPHORE)
+                .value_name("number")
+                .value_parser(parse_semaphore_count)
+                .action(ArgAction::Set)
+                .help("create semaphore array with <number> elements"),
+        )
+        .arg(
+            Arg::new(options::QUEUE)
+                .short('Q')
+                .long(options::QUEUE)
+                .action(ArgAction::SetTrue)
+                .help("create message queue"),
+        )
+        .arg(
+            Arg::new(options::MODE)
+                .short('p')
+                .long(options::MODE)
+                .value_name(options::MODE)
+                .value_parser(parse_mode)
+                .action(ArgAction::Set)
+                .help("permission for the resource (default is 0644)"),
+        )
+        .arg(
+            Arg::new(options::KEY)
+                .short('k')
+                .long(options::KEY)
+                .value_name(options::KEY)
+                .value_parser(parse_key)
+                .action(ArgAction::Set)
+                .help("key of the resource (default is random)"),
+        )
+        .group(
+            ArgGroup::new("ipc-kind")
+                .args([options::SHMEM, options::QUEUE, options::SEMAPHORE])
+                .multiple(true)
+                .required(true),
+        )
+}
+
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let args = uu_app().try_get_matches_from_mut(args)?;
+
+    ipcmk(&args).map_err(From::from)
+}
+
+fn parse_size(s: &str) -> Result<u64, LsIpcError> {
+    match uucore::parse_size::parse_size_u64(s) {
+        Ok(0) | Err(_) => Err(LsIpcError::InvalidSize(s.into())),
+        Ok(size) => Ok(size),
+    }
+}
+
+fn parse_semaphore_count(s: &str) -> Result<u64, LsIpcError> {
+    match s.parse() {
+        Ok(0) | Err(_) => Err(LsIpcError::InvalidSize(s.into())),
+        Ok(count) => Ok(count),
+    }
+}
+
+fn parse_mode(s: &str) -> Result<c_int, LsIpcError> {
+    c_int::from_str_radix(s, 8)
+        .ok()
+        .filter(|mode| (0..=0o777).contains(mode))
+        .ok_or_else(|| LsIpcError::InvalidMode(s.into()))
+}
+
+#[cfg(target_os = "linux")]
+fn parse_key(s: &str) -> Result<libc::key_t, LsIpcError> {
+    uu_lsipc::parse_key(s)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn parse_key(s: &str) -> Result<libc::key_t, LsIpcError> {
+    Err(LsIpcError::InvalidKey(s.into()))
+}
+
+#[cfg(target_os = "linux")]
+fn ipcmk(args: &ArgMatches) -> Result<(), LsIpcError> {
+    use uu_lsipc::{message_queue, semaphore, shared_memory};
+
+    let mode = args
+        .get_one::<c_int>(options::MODE)
+        .copied()
+        .unwrap_or(DEFAULT_MODE);
+
+    let key = args
+        .get_one::<libc::key_t>(options::KEY)
+        .copied()
+        .unwrap_or_else(random_key);
+
+    let flags = libc::IPC_CREAT | libc::IPC_EXCL | mode;
+
+    if let Some(&size) = args.get_one::<u64>(options::SHMEM) {
+        shared_memory::check_create_limits(size)?;
+
+        let size = usize::try_from(size).unwrap_or(usize::MAX);
+        let id = unsafe { libc::shmget(key, size, flags) };
+        if id == -1 {
+            return Err(LsIpcError::last_io0("create share memory failed"));
+        }
+        println!("Shared memory id: {id}");
+    }
+
+    if args.get_flag(options::QUEUE) {
+        message_queue::check_create_limits()?;
+
+        let id = unsafe { libc::msgget(key, flags) };
+        if id == -1 {
+            return Err(LsIpcError::last_io0("create message queue failed"));
+        }
+        println!("Message queue id: {id}");
+    }
+
+    if let Some(&nsems) = args.get_one::<u64>(options::SEMAPHORE) {
+        semaphore::check_create_limits(nsems)?;
+
+        let nsems = c_int::try_from(nsems).unwrap_or(c_int::MAX);
+        let id = unsafe { libc::semget(key, nsems, flags) };
+        if id == -1 {
+            return Err(LsIpcError::last_io0("create semaphore failed"));
+        }
+        println!("Semaphore id: {id}");
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn ipcmk(_args: &ArgMatches) -> Result<(), LsIpcError> {
+    unimplemented!()
+}
+
+// `IPC_PRIVATE` (zero) would always create a new resource, which is not what a
+// random key is meant to do, so it is skipped.
+#[cfg(target_os = "linux")]
+fn random_key() -> libc::key_t {
+    loop {
+        let key = rand::random::<libc::key_t>();
+        if key != libc::IPC_PRIVATE {
+            break key;
+        }
+    }
+}
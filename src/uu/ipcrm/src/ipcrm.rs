@@ -0,0 +1,304 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+// Remove this if the tool is ported to Non-UNIX platforms.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+use std::ffi::c_int;
+#[cfg(target_os = "linux")]
+use std::fmt::Display;
+#[cfg(target_os = "linux")]
+use std::io;
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command, crate_version, value_parser};
+use uu_lsipc::LsIpcError;
+use uucore::{error::UResult, format_usage, help_about, help_usage};
+
+mod options {
+    pub static ALL: &str = "all";
+    pub static QUEUE_ID: &str = "queue-id";
+    pub static QUEUE_KEY: &str = "queue-key";
+    pub static SEMAPHORE_ID: &str = "semaphore-id";
+    pub static SEMAPHORE_KEY: &str = "semaphore-key";
+    pub static SHMEM_ID: &str = "shmem-id";
+    pub static SHMEM_KEY: &str = "shmem-key";
+    pub static VERBOSE: &str = "verbose";
+    pub const SHM: &str = "shm";
+    pub const MSG: &str = "msg";
+    pub const SEM: &str = "sem";
+}
+
+const ABOUT: &str = help_about!("ipcrm.md");
+const USAGE: &str = help_usage!("ipcrm.md");
+
+pub fn uu_app() -> Command {
+    let id_arg = |name: &'static str, short: char, help: &'static str| {
+        Arg::new(name)
+            .short(short)
+            .long(name)
+            .value_name("id")
+            .value_parser(value_parser!(c_int))
+            .action(ArgAction::Append)
+            .help(help)
+    };
+
+    let key_arg = |name: &'static str, short: char, help: &'static str| {
+        Arg::new(name)
+            .short(short)
+            .long(name)
+            .value_name("key")
+            .value_parser(parse_key)
+            .action(ArgAction::Append)
+            .help(help)
+    };
+
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(id_arg(
+            options::SHMEM_ID,
+            'm',
+            "remove shared memory segment by id",
+        ))
+        .arg(key_arg(
+            options::SHMEM_KEY,
+            'M',
+            "remove shared memory segment by key",
+        ))
+        .arg(id_arg(options::QUEUE_ID, 'q', "remove message queue by id"))
+        .arg(key_arg(
+            options::QUEUE_KEY,
+            'Q',
+            "remove message queue by key",
+        ))
+        .arg(id_arg(options::SEMAPHORE_ID, 's', "remove semaphore by id"))
+        .arg(key_arg(
+            options::SEMAPHORE_KEY,
+            'S',
+            "remove semaphore by key",
+        ))
+        .arg(
+            Arg::new(options::ALL)
+                .short('a')
+                .long(options::ALL)
+                .value_name("shm|msg|sem")
+                .value_parser([options::SHM, options::MSG, options::SEM])
+                .num_args(0..=1)
+                .require_equals(true)
+                .action(ArgAction::Append)
+                .help("remove all (in the specified category)"),
+        )
+        .arg(
+            Arg::new(options::VERBOSE)
+                .short('v')
+                .long(options::VERBOSE)
+                .action(ArgAction::SetTrue)
+                .help("explain what is being done"),
+        )
+        .group(
+            ArgGroup::new("resources")
+                .args([
+                    options::SHMEM_ID,
+                    options::SHMEM_KEY,
+                    options::QUEUE_ID,
+                    options::QUEUE_KEY,
+                    options::SEMAPHORE_ID,
+                    options::SEMAPHORE_KEY,
+                    options::ALL,
+                ])
+                .multiple(true)
+                .required(true),
+        )
+}
+
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let args = uu_app().try_get_matches_from_mut(args)?;
+
+    ipcrm(&args).map_err(From::from)
+}
+
+#[cfg(target_os = "linux")]
+fn parse_key(s: &str) -> Result<libc::key_t, LsIpcError> {
+    uu_lsipc::parse_key(s)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn parse_key(s: &str) -> Result<libc::key_t, LsIpcError> {
+    Err(LsIpcError::InvalidKey(s.into()))
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum IpcKind {
+    SharedMemory,
+    MessageQueue,
+    Semaphore,
+}
+
+#[cfg(target_os = "linux")]
+impl IpcKind {
+    fn description(self) -> &'static str {
+        match self {
+            Self::SharedMemory => "shared memory segment",
+            Self::MessageQueue => "message queue",
+            Self::Semaphore => "semaphore",
+        }
+    }
+
+    fn id_from_key(self, key: libc::key_t) -> io::Result<c_int> {
+        let id = match self {
+            Self::SharedMemory => unsafe { libc::shmget(key, 0, 0) },
+            Self::MessageQueue => unsafe { libc::msgget(key, 0) },
+            Self::Semaphore => unsafe { libc::semget(key, 0, 0) },
+        };
+
+        if id == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(id)
+        }
+    }
+
+    fn list_keys_and_ids(self) -> Result<Vec<(libc::key_t, c_int)>, LsIpcError> {
+        match self {
+            Self::SharedMemory => uu_lsipc::shared_memory::list_keys_and_ids(),
+            Self::MessageQueue => uu_lsipc::message_queue::list_keys_and_ids(),
+            Self::Semaphore => uu_lsipc::semaphore::list_keys_and_ids(),
+        }
+    }
+
+    fn remove(self, id: c_int) -> Result<(), LsIpcError> {
+        match self {
+            Self::SharedMemory => uu_lsipc::shared_memory::remove(id),
+            Self::MessageQueue => uu_lsipc::message_queue::remove(id),
+            Self::Semaphore => uu_lsipc::semaphore::remove(id),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn ipcrm(args: &ArgMatches) -> Result<(), LsIpcError> {
+    let verbose = args.get_flag(options::VERBOSE);
+
+    let config_list = [
+        (
+            IpcKind::SharedMemory,
+            options::SHMEM_ID,
+            options::SHMEM_KEY,
+            options::SHM,
+        ),
+        (
+            IpcKind::MessageQueue,
+            options::QUEUE_ID,
+            options::QUEUE_KEY,
+            options::MSG,
+        ),
+        (
+            IpcKind::Semaphore,
+            options::SEMAPHORE_ID,
+            options::SEMAPHORE_KEY,
+            options::SEM,
+        ),
+    ];
+
+    // `--all` without a value selects every kind of resource.
+    let all: Option<Vec<&str>> = args.contains_id(options::ALL).then(|| {
+        args.get_many::<String>(options::ALL)
+            .map(|values| values.map(String::as_str).collect())
+            .unwrap_or_default()
+    });
+
+    for (kind, id_option, key_option, all_value) in config_list {
+        for &id in args.get_many::<c_int>(id_option).into_iter().flatten() {
+            remove_id(kind, id, &id, false, verbose)?;
+        }
+
+        let keys = args
+            .get_many::<libc::key_t>(key_option)
+            .into_iter()
+            .flatten();
+        let key_args = args.get_raw(key_option).into_iter().flatten();
+
+        for (&key, key_arg) in keys.zip(key_args) {
+            let key_arg = key_arg.to_string_lossy();
+
+            if key == libc::IPC_PRIVATE {
+                report(format!("illegal key ({key_arg})"));
+                continue;
+            }
+
+            match kind.id_from_key(key) {
+                Ok(id) => remove_id(kind, id, &key_arg, true, verbose)?,
+
+                Err(err) => {
+                    let message = match err.raw_os_error() {
+                        Some(libc::EACCES) => "permission denied for key",
+                        Some(libc::EIDRM) => "already removed key",
+                        Some(libc::ENOENT) => "invalid key",
+                        _ => return Err(LsIpcError::io0("key failed", err)),
+                    };
+                    report(format!("{message} ({key_arg})"));
+                }
+            }
+        }
+
+        if let Some(all) = &all {
+            if all.is_empty() || all.contains(&all_value) {
+                for (_key, id) in kind.list_keys_and_ids()? {
+                    remove_id(kind, id, &id, false, verbose)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn ipcrm(_args: &ArgMatches) -> Result<(), LsIpcError> {
+    unimplemented!()
+}
+
+#[cfg(target_os = "linux")]
+fn remove_id(
+    kind: IpcKind,
+    id: c_int,
+    arg: &dyn Display,
+    by_key: bool,
+    verbose: bool,
+) -> Result<(), LsIpcError> {
+    if verbose {
+        println!("removing {} id `{id}'", kind.description());
+    }
+
+    let Err(err) = kind.remove(id) else {
+        return Ok(());
+    };
+
+    let errno = match &err {
+        LsIpcError::IO0(_, io_err) => io_err.raw_os_error(),
+        _ => None,
+    };
+
+    let message = match (errno, by_key) {
+        (Some(libc::EACCES | libc::EPERM), false) => "permission denied for id",
+        (Some(libc::EACCES | libc::EPERM), true) => "permission denied for key",
+        (Some(libc::EINVAL), false) => "invalid id",
+        (Some(libc::EINVAL), true) => "invalid key",
+        (Some(libc::EIDRM), false) => "already removed id",
+        (Some(libc::EIDRM), true) => "already removed key",
+        _ => return Err(err),
+    };
+
+    report(format!("{message} ({arg})"));
+    Ok(())
+}
+
+fn report(message: String) {
+    uucore::error::set_exit_code(1);
+    uucore::show_error!("{message}");
+}
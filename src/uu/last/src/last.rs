@@ -10,6 +10,13 @@ mod platform;
 
 mod options {
     pub const SYSTEM: &str = "system";
+    pub const FAILED: &str = "failed";
+    pub const SINCE: &str = "since";
+    pub const UNTIL: &str = "until";
+    pub const PRESENT: &str = "present";
+    pub const JSON: &str = "json";
+    pub const RAW: &str = "raw";
+    pub const SEPARATOR: &str = "separator";
     pub const HOSTLAST: &str = "hostlast";
     pub const NO_HOST: &str = "nohostname";
     pub const LIMIT: &str = "limit";
@@ -36,7 +43,6 @@ pub fn uu_app() -> Command {
                 .short('f')
                 .long("file")
                 .action(ArgAction::Set)
-                .default_value("/var/log/wtmp")
                 .help("use a specific file instead of /var/log/wtmp")
                 .required(false),
         )
@@ -48,6 +54,13 @@ pub fn uu_app() -> Command {
                 .required(false)
                 .help("display system shutdown entries and run level changes"),
         )
+        .arg(
+            Arg::new(options::FAILED)
+                .long(options::FAILED)
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .help("show failed login attempts from /var/log/btmp, as lastb does"),
+        )
         .arg(
             Arg::new(options::DNS)
                 .short('d')
@@ -82,6 +95,34 @@ pub fn uu_app() -> Command {
                 .value_parser(clap::value_parser!(i32))
                 .allow_negative_numbers(true),
         )
+        .arg(
+            Arg::new(options::SINCE)
+                .short('s')
+                .long(options::SINCE)
+                .action(ArgAction::Set)
+                .required(false)
+                .value_name("time")
+                .help("display the lines since the specified time"),
+        )
+        .arg(
+            Arg::new(options::UNTIL)
+                .short('t')
+                .long(options::UNTIL)
+                .action(ArgAction::Set)
+                .required(false)
+                .value_name("time")
+                .help("display the lines until the specified time"),
+        )
+        .arg(
+            Arg::new(options::PRESENT)
+                .short('p')
+                .long(options::PRESENT)
+                .action(ArgAction::Set)
+                .required(false)
+                .value_name("time")
+                .conflicts_with_all([options::SINCE, options::UNTIL])
+                .help("display who were present at the specified time"),
+        )
         .arg(
             Arg::new(options::TIME_FORMAT)
                 .long(options::TIME_FORMAT)
@@ -90,5 +131,30 @@ pub fn uu_app() -> Command {
                 .help("show timestamps in the specified <format>: notime|short|full|iso")
                 .default_value("short"),
         )
+        .arg(
+            Arg::new(options::JSON)
+                .short('J')
+                .long(options::JSON)
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .help("use JSON output format"),
+        )
+        .arg(
+            Arg::new(options::RAW)
+                .long(options::RAW)
+                .action(ArgAction::SetTrue)
+                .required(false)
+                .conflicts_with(options::JSON)
+                .help("use raw output format, one record per line with tab separated fields"),
+        )
+        .arg(
+            Arg::new(options::SEPARATOR)
+                .long(options::SEPARATOR)
+                .action(ArgAction::Set)
+                .required(false)
+                .value_name("string")
+                .requires(options::RAW)
+                .help("separate the fields of the raw output with <string>"),
+        )
         .arg(Arg::new(options::USER_TTY).action(ArgAction::Append))
 }
@@ -19,7 +19,6 @@ use std::io;
 use std::net::Ipv4Addr;
 
 use std::os::unix::fs::MetadataExt;
-use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration;
 
@@ -31,6 +30,7 @@ fn get_long_usage() -> String {
 }
 
 const WTMP_PATH: &str = "/var/log/wtmp";
+const BTMP_PATH: &str = "/var/log/btmp";
 static TIME_FORMAT_STR: [&str; 4] = ["notime", "short", "full", "iso"];
 
 pub fn uumain(args: impl uucore::Args) -> UResult<()> {
@@ -62,12 +62,44 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
         Ok("short".to_string())
     }?;
 
+    let failed = matches.get_flag(options::FAILED);
+
     let file: String = if let Some(files) = matches.get_one::<String>(options::FILE) {
         files.to_string()
+    } else if failed {
+        BTMP_PATH.to_string()
     } else {
         WTMP_PATH.to_string()
     };
 
+    // A session is shown if it overlaps the window, --present being a window of an instant
+    let since = match matches.get_one::<String>(options::SINCE) {
+        Some(time) => Some(parse_time(time)?),
+        None => None,
+    };
+    let until = match matches.get_one::<String>(options::UNTIL) {
+        Some(time) => Some(parse_time(time)?),
+        None => None,
+    };
+    let (since, until) = match matches.get_one::<String>(options::PRESENT) {
+        Some(time) => {
+            let present = parse_time(time)?;
+            (Some(present), Some(present))
+        }
+        None => (since, until),
+    };
+
+    let output = if matches.get_flag(options::JSON) {
+        OutputFormat::Json
+    } else if matches.get_flag(options::RAW) {
+        let separator = matches
+            .get_one::<String>(options::SEPARATOR)
+            .map_or("\t", String::as_str);
+        OutputFormat::Raw(separator.to_string())
+    } else {
+        OutputFormat::Text
+    };
+
     let user: Option<Vec<String>> =
         if let Some(users) = matches.get_many::<String>(options::USER_TTY) {
             users
@@ -84,9 +116,11 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
         };
 
     let mut last = Last {
-        last_reboot_ut: None,
-        last_shutdown_ut: None,
+        last_reboot: None,
+        last_shutdown: None,
+        last_runlevel_change: None,
         last_dead_ut: vec![],
+        failed,
         system,
         dns,
         host_last: hostlast,
@@ -95,6 +129,10 @@ pub fn uumain(args: impl uucore::Args) -> UResult<()> {
         file: file.to_string(),
         users: user,
         time_format,
+        since,
+        until,
+        output,
+        records: vec![],
     };
 
     last.exec()
@@ -104,10 +142,59 @@ const RUN_LEVEL_STR: &str = "runlevel";
 const REBOOT_STR: &str = "reboot";
 const SHUTDOWN_STR: &str = "shutdown";
 
+// How a session ended, or a state of the system
+#[derive(Clone, Copy, PartialEq)]
+enum EndState {
+    StillLoggedIn,
+    StillRunning,
+    Gone,
+    Crash,
+    Down,
+}
+
+impl std::fmt::Display for EndState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::StillLoggedIn => "still logged in",
+            Self::StillRunning => "still running",
+            Self::Gone => "gone",
+            Self::Crash => "crash",
+            Self::Down => "down",
+        })
+    }
+}
+
+// A line of the listing, before it is formatted
+struct Record {
+    user: String,
+    line: String,
+    host: String,
+    login: OffsetDateTime,
+    logout: Option<OffsetDateTime>,
+    state: EndState,
+}
+
+impl Record {
+    fn duration(&self) -> Option<time::Duration> {
+        self.logout
+            .map(|logout| calculate_time_delta(&self.login, &logout))
+    }
+}
+
+enum OutputFormat {
+    Text,
+    // The fields of each record, separated by the given string
+    Raw(String),
+    Json,
+}
+
 struct Last {
-    last_reboot_ut: Option<Utmpx>,
-    last_shutdown_ut: Option<Utmpx>,
+    // The times of the closest system events after the record being printed
+    last_reboot: Option<OffsetDateTime>,
+    last_shutdown: Option<OffsetDateTime>,
+    last_runlevel_change: Option<OffsetDateTime>,
     last_dead_ut: Vec<Utmpx>,
+    failed: bool,
     system: bool,
     dns: bool,
     host_last: bool,
@@ -116,12 +203,30 @@ struct Last {
     time_format: String,
     users: Option<Vec<String>>,
     limit: i32,
+    since: Option<i64>,
+    until: Option<i64>,
+    output: OutputFormat,
+    // The records printed at the end, for the JSON output
+    records: Vec<Record>,
+}
+
+// Parses a time given on the command line into a unix timestamp
+fn parse_time(s: &str) -> UResult<i64> {
+    parse_datetime::parse_datetime(s)
+        .map(|time| time.timestamp())
+        .map_err(|_| USimpleError::new(1, format!("invalid time value \"{s}\"")))
 }
 
 fn is_numeric(s: &str) -> bool {
     s.chars().all(|c| c.is_numeric())
 }
 
+// The new and previous runlevels, which init stores in the pid of the record
+fn runlevels(ut: &Utmpx) -> (char, char) {
+    let pid = ut.pid();
+    ((pid % 256) as u8 as char, (pid / 256 % 256) as u8 as char)
+}
+
 #[inline]
 fn calculate_time_delta(
     curr_datetime: &OffsetDateTime,
@@ -174,7 +279,7 @@ impl Last {
     const START_TIME_SHORT_FMT: &'static str =
         "[weekday repr:short] [month repr:short] [day padding:space] [hour]:[minute]";
     const TIME_ISO_FMT: &'static str =
-        "[year]-[month]-[day]T[hour]:[minute]:[second]+[offset_hour]:[offset_minute]";
+        "[year]-[month]-[day]T[hour]:[minute]:[second][offset_hour sign:mandatory]:[offset_minute]";
 
     #[allow(clippy::cognitive_complexity)]
     fn exec(&mut self) -> UResult<()> {
@@ -200,7 +305,12 @@ impl Last {
             if counter >= self.limit && self.limit > 0 {
                 break;
             }
-            if ut.is_user_process() {
+            if self.failed {
+                // Every record of btmp is a failed login attempt
+                if self.print_failed(&ut) {
+                    counter += 1;
+                }
+            } else if ut.is_user_process() {
                 let mut dead_proc: Option<Utmpx> = None;
                 if let Some(pos) = self
                     .last_dead_ut
@@ -216,16 +326,21 @@ impl Last {
                 if self.print_runlevel(&ut) {
                     counter += 1;
                 }
+                self.last_runlevel_change = Some(ut.login_time());
+                // Switching to runlevel 0 or 6 shuts the system down
+                if matches!(runlevels(&ut).0, '0' | '6') {
+                    self.last_shutdown = Some(ut.login_time());
+                }
             } else if ut.user() == SHUTDOWN_STR {
                 if self.print_shutdown(&ut) {
                     counter += 1;
                 }
-                self.last_shutdown_ut = Some(ut);
+                self.last_shutdown = Some(ut.login_time());
             } else if ut.user() == REBOOT_STR {
                 if self.print_reboot(&ut) {
                     counter += 1;
                 }
-                self.last_reboot_ut = Some(ut);
+                self.last_reboot = Some(ut.login_time());
             } else if ut.user() == "" {
                 // Dead process end date
                 self.last_dead_ut.push(ut);
@@ -249,13 +364,13 @@ impl Last {
             ))?;
 
         if let Some(file_time) = first_ut_time {
-            println!("\n{} begins {}", path_str, file_time);
+            self.print_begins(path_str, &file_time);
         } else {
             let secs = fs::metadata(&self.file)?.ctime();
             let nsecs = fs::metadata(&self.file)?.ctime_nsec() as u64;
             let file_time = self.utmp_file_time(secs, nsecs);
 
-            println!("\n{} begins {}", path_str, file_time);
+            self.print_begins(path_str, &file_time);
         }
 
         Ok(())
@@ -264,6 +379,8 @@ impl Last {
     #[inline]
     fn utmp_file_time(&self, secs: i64, nsecs: u64) -> String {
         let description = match self.time_format.as_str() {
+            // The machine readable output formats always use ISO 8601
+            _ if !matches!(self.output, OutputFormat::Text) => Self::TIME_ISO_FMT,
             "short" | "full" => Self::TIME_FULL_FMT,
             "iso" => Self::TIME_ISO_FMT,
             _ => return "".to_string(),
@@ -286,7 +403,7 @@ impl Last {
     }
 
     #[inline]
-    fn time_string(&self, ut: &Utmpx) -> String {
+    fn time_string(&self, time: &OffsetDateTime) -> String {
         let description = match self.time_format.as_str() {
             "short" => Self::START_TIME_SHORT_FMT,
             "full" => Self::TIME_FULL_FMT,
@@ -297,89 +414,111 @@ impl Last {
         // "%b %e %H:%M"
         let time_format: Vec<time::format_description::FormatItem> =
             time::format_description::parse(description).unwrap_or_default();
-        ut.login_time().format(&time_format).unwrap_or_default()
+        time.format(&time_format).unwrap_or_default()
     }
 
     #[inline]
-    fn end_time_string(&self, user_process_str: Option<&str>, end_ut: &OffsetDateTime) -> String {
-        match user_process_str {
-            Some(val) => val.to_string(),
-            _ => {
-                let description = match self.time_format.as_str() {
-                    "short" => format!("- {}", Self::END_TIME_SHORT_FMT),
-                    "full" => format!("- {}", Self::TIME_FULL_FMT),
-                    "iso" => format!("- {}", Self::TIME_ISO_FMT),
-                    _ => return "".to_string(),
-                };
+    fn end_time_string(&self, end_ut: &OffsetDateTime) -> String {
+        let description = match self.time_format.as_str() {
+            "short" => format!("- {}", Self::END_TIME_SHORT_FMT),
+            "full" => format!("- {}", Self::TIME_FULL_FMT),
+            "iso" => format!("- {}", Self::TIME_ISO_FMT),
+            _ => return "".to_string(),
+        };
 
-                // "%H:%M"
-                let time_format: Vec<time::format_description::FormatItem> =
-                    time::format_description::parse(&description).unwrap_or_default();
-                end_ut.format(&time_format).unwrap_or_default()
-            }
-        }
+        // "%H:%M"
+        let time_format: Vec<time::format_description::FormatItem> =
+            time::format_description::parse(&description).unwrap_or_default();
+        end_ut.format(&time_format).unwrap_or_default()
+    }
+
+    // The time of a record in the machine readable output formats
+    #[inline]
+    fn iso_time_string(time: &OffsetDateTime) -> String {
+        let time_format: Vec<time::format_description::FormatItem> =
+            time::format_description::parse(Self::TIME_ISO_FMT).unwrap_or_default();
+        time.format(&time_format).unwrap_or_default()
     }
 
+    // When and how the session which started with the record ended
     #[inline]
-    fn end_state_string(&self, ut: &Utmpx, dead_ut: Option<&Utmpx>) -> (String, String) {
+    fn session_end(
+        &self,
+        ut: &Utmpx,
+        dead_ut: Option<&Utmpx>,
+    ) -> (Option<OffsetDateTime>, EndState) {
         // This function takes a considerable amount of CPU cycles to complete;
         // root cause seems to be the ut.login_time function, which reads a
         // file to determine local offset for UTC. Perhaps this function
         // should be updated to save that UTC offset for subsequent calls
-        let mut proc_status: Option<&str> = None;
-        let curr_datetime = ut.login_time();
-
         if let Some(dead) = dead_ut {
-            let dead_datetime = dead.login_time();
-            let time_delta = duration_string(calculate_time_delta(&curr_datetime, &dead_datetime));
-            return (
-                self.end_time_string(proc_status, &dead_datetime),
-                time_delta.to_string(),
-            );
+            return (Some(dead.login_time()), EndState::Gone);
         }
 
-        let reboot_datetime: Option<OffsetDateTime>;
-        let shutdown_datetime: Option<OffsetDateTime>;
-        if let Some(reboot) = &self.last_reboot_ut {
-            reboot_datetime = Some(reboot.login_time());
+        // Whichever comes first of the next shutdown and reboot tells how the session
+        // ended. A reboot which isn't preceded by a shutdown means the system crashed.
+        let crash = self
+            .last_reboot
+            .filter(|reboot| self.last_shutdown.is_none_or(|shutdown| *reboot < shutdown));
+        if let Some(reboot) = crash {
+            (Some(reboot), EndState::Crash)
+        } else if let Some(shutdown) = self.last_shutdown {
+            if ut.is_user_process() {
+                (Some(shutdown), EndState::Down)
+            } else {
+                (Some(shutdown), EndState::Gone)
+            }
+        } else if ut.is_user_process() {
+            (None, EndState::StillLoggedIn)
         } else {
-            reboot_datetime = None;
+            (None, EndState::StillRunning)
         }
+    }
 
-        if let Some(shutdown) = &self.last_shutdown_ut {
-            shutdown_datetime = Some(shutdown.login_time());
-        } else {
-            shutdown_datetime = None;
+    // The end of a state of the system which lasts until the given event, if any
+    #[inline]
+    fn state_end(end: Option<OffsetDateTime>) -> (Option<OffsetDateTime>, EndState) {
+        match end {
+            Some(end) => (Some(end), EndState::Gone),
+            None => (None, EndState::StillRunning),
         }
+    }
 
-        if shutdown_datetime.is_none() {
-            if ut.is_user_process() {
-                // If a reboot has occurred since the user logged in, but not shutdown is recorded
-                // then a crash must have occurred.
-                if reboot_datetime.is_some() && reboot_datetime.unwrap() > ut.login_time() {
-                    ("- crash".to_string(), "".to_string())
-                } else {
-                    ("  still logged in".to_string(), "".to_string())
-                }
-            } else {
-                ("  still running".to_string(), "".to_string())
-            }
+    #[inline]
+    fn in_time_window(&self, record: &Record) -> bool {
+        let started_in_time = self
+            .until
+            .is_none_or(|until| record.login.unix_timestamp() <= until);
+        let ended_in_time = self.since.is_none_or(|since| {
+            record
+                .logout
+                .is_none_or(|logout| logout.unix_timestamp() >= since)
+        });
+        started_in_time && ended_in_time
+    }
+
+    #[inline]
+    fn is_selected(&self, ut: &Utmpx) -> bool {
+        match &self.users {
+            Some(users) => users.iter().any(|val| {
+                val.as_str().trim() == ut.tty_device().as_str().trim()
+                    || val.as_str().trim() == ut.user().trim()
+            }),
+            None => true,
+        }
+    }
+
+    #[inline]
+    fn host(&self, ut: &Utmpx) -> String {
+        if self.dns {
+            find_dns_name(ut)
         } else {
-            let shutdown = shutdown_datetime
-                .unwrap_or_else(|| time::OffsetDateTime::from_unix_timestamp(0).unwrap());
-            let time_delta = duration_string(calculate_time_delta(&curr_datetime, &shutdown));
-            if ut.is_user_process() {
-                proc_status = Some("- down");
-            }
-            (
-                self.end_time_string(proc_status, &shutdown),
-                time_delta.to_string(),
-            )
+            ut.host()
         }
     }
 
     #[inline]
-    fn print_runlevel(&self, ut: &Utmpx) -> bool {
+    fn print_runlevel(&mut self, ut: &Utmpx) -> bool {
         if let Some(users) = &self.users {
             if !users
                 .iter()
@@ -389,30 +528,37 @@ impl Last {
             }
         }
         if self.system {
-            let curr = (ut.pid() % 256) as u8 as char;
-            let runlvline = format!("(to lvl {curr})");
-            let (end_date, delta) = self.end_state_string(ut, None);
-            let host = if self.dns {
-                find_dns_name(ut)
+            let (curr, previous) = runlevels(ut);
+            // The previous runlevel is 'N' or 0 when there wasn't any, e.g. at boot
+            let runlvline = if previous.is_ascii_alphanumeric() && previous != 'N' {
+                format!("(lvl {previous} to {curr})")
             } else {
-                ut.host()
+                format!("(to lvl {curr})")
             };
-            self.print_line(
-                RUN_LEVEL_STR,
-                &runlvline,
-                &self.time_string(ut),
-                &host,
-                &end_date,
-                &delta,
-            );
-            true
+            let end = [
+                self.last_runlevel_change,
+                self.last_shutdown,
+                self.last_reboot,
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+            let (logout, state) = Self::state_end(end);
+            self.print_record(Record {
+                user: RUN_LEVEL_STR.to_string(),
+                line: runlvline,
+                host: self.host(ut),
+                login: ut.login_time(),
+                logout,
+                state,
+            })
         } else {
             false
         }
     }
 
     #[inline]
-    fn print_shutdown(&self, ut: &Utmpx) -> bool {
+    fn print_shutdown(&mut self, ut: &Utmpx) -> bool {
         if let Some(users) = &self.users {
             if !users.iter().any(|val| {
                 val.as_str().trim() == "system down" || val.as_str().trim() == ut.user().trim()
@@ -420,29 +566,24 @@ impl Last {
                 return false;
             }
         }
-        let host = if self.dns {
-            find_dns_name(ut)
-        } else {
-            ut.host()
-        };
         if self.system {
-            let (end_date, delta) = self.end_state_string(ut, None);
-            self.print_line(
-                SHUTDOWN_STR,
-                "system down",
-                &self.time_string(ut),
-                &host,
-                &end_date,
-                &delta,
-            );
-            true
+            // The system is down until it boots again
+            let (logout, state) = Self::state_end(self.last_reboot);
+            self.print_record(Record {
+                user: SHUTDOWN_STR.to_string(),
+                line: "system down".to_string(),
+                host: self.host(ut),
+                login: ut.login_time(),
+                logout,
+                state,
+            })
         } else {
             false
         }
     }
 
     #[inline]
-    fn print_reboot(&self, ut: &Utmpx) -> bool {
+    fn print_reboot(&mut self, ut: &Utmpx) -> bool {
         if let Some(users) = &self.users {
             if !users.iter().any(|val| {
                 val.as_str().trim() == ut.user().trim() || val.as_str().trim() == "system boot"
@@ -450,56 +591,135 @@ impl Last {
                 return false;
             }
         }
-        let (end_date, delta) = self.end_state_string(ut, None);
-        let host = if self.dns {
-            find_dns_name(ut)
-        } else {
-            ut.host()
-        };
-        self.print_line(
-            REBOOT_STR,
-            "system boot",
-            &self.time_string(ut),
-            &host,
-            &end_date,
-            &delta,
-        );
-
-        true
+        let (logout, state) = self.session_end(ut, None);
+        self.print_record(Record {
+            user: REBOOT_STR.to_string(),
+            line: "system boot".to_string(),
+            host: self.host(ut),
+            login: ut.login_time(),
+            logout,
+            state,
+        })
     }
 
     #[inline]
-    fn print_user(&self, ut: &Utmpx, dead_ut: Option<&Utmpx>) -> bool {
-        if let Some(users) = &self.users {
-            if !users.iter().any(|val| {
-                val.as_str().trim() == ut.tty_device().as_str().trim()
-                    || val.as_str().trim() == ut.user().trim()
-            }) {
-                return false;
-            }
+    fn print_user(&mut self, ut: &Utmpx, dead_ut: Option<&Utmpx>) -> bool {
+        if !self.is_selected(ut) {
+            return false;
         }
-        let mut p = PathBuf::from("/dev");
-        p.push(ut.tty_device().as_str());
-        let host = if self.dns {
-            find_dns_name(ut)
-        } else {
-            ut.host()
-        };
+        let (logout, state) = self.session_end(ut, dead_ut);
+        self.print_record(Record {
+            user: ut.user(),
+            line: ut.tty_device(),
+            host: self.host(ut),
+            login: ut.login_time(),
+            logout,
+            state,
+        })
+    }
 
-        let (end_date, delta) = self.end_state_string(ut, dead_ut);
+    #[inline]
+    fn print_failed(&mut self, ut: &Utmpx) -> bool {
+        if !self.is_selected(ut) {
+            return false;
+        }
+        // A failed attempt ends as soon as it starts
+        let login = ut.login_time();
+        self.print_record(Record {
+            user: ut.user(),
+            line: ut.tty_device(),
+            host: self.host(ut),
+            login,
+            logout: Some(login),
+            state: EndState::Gone,
+        })
+    }
 
-        self.print_line(
-            ut.user().as_ref(),
-            ut.tty_device().as_ref(),
-            self.time_string(ut).as_str(),
-            &host,
-            &end_date,
-            &delta,
-        );
+    // Prints the record, or keeps it for the JSON output. Returns false if it is outside
+    // of the time window.
+    fn print_record(&mut self, record: Record) -> bool {
+        if !self.in_time_window(&record) {
+            return false;
+        }
 
+        match &self.output {
+            OutputFormat::Text => {
+                let end_date = match (record.state, record.logout) {
+                    (EndState::StillLoggedIn, _) => "  still logged in".to_string(),
+                    (EndState::StillRunning, _) => "  still running".to_string(),
+                    (EndState::Crash, _) => "- crash".to_string(),
+                    (EndState::Down, _) => "- down".to_string(),
+                    (EndState::Gone, Some(logout)) => self.end_time_string(&logout),
+                    (EndState::Gone, None) => "".to_string(),
+                };
+                self.print_line(
+                    &record.user,
+                    &record.line,
+                    &self.time_string(&record.login),
+                    &record.host,
+                    &end_date,
+                    &record.duration().map(duration_string).unwrap_or_default(),
+                );
+            }
+            OutputFormat::Raw(separator) => {
+                let fields = [
+                    record.user.clone(),
+                    record.line.clone(),
+                    record.host.clone(),
+                    Self::iso_time_string(&record.login),
+                    record
+                        .logout
+                        .map(|logout| Self::iso_time_string(&logout))
+                        .unwrap_or_default(),
+                    record
+                        .duration()
+                        .map(|duration| duration.whole_seconds().to_string())
+                        .unwrap_or_default(),
+                    record.state.to_string(),
+                ];
+                println!("{}", fields.join(separator));
+            }
+            OutputFormat::Json => self.records.push(record),
+        }
         true
     }
 
+    fn print_begins(&self, file_name: &str, file_time: &str) {
+        match &self.output {
+            OutputFormat::Text => {
+                println!("\n{} begins {}", file_name, file_time);
+            }
+            OutputFormat::Raw(separator) => {
+                // A record of its own with the same fields as the sessions, the file in place
+                // of the tty and its start as the login time, marked by the "begins" state.
+                let fields = ["", file_name, "", file_time, "", "", "begins"];
+                println!("{}", fields.join(separator));
+            }
+            OutputFormat::Json => {
+                let records: Vec<serde_json::Value> = self
+                    .records
+                    .iter()
+                    .map(|record| {
+                        serde_json::json!({
+                            "user": record.user,
+                            "tty": record.line,
+                            "host": record.host,
+                            "login": Self::iso_time_string(&record.login),
+                            "logout": record.logout.map(|logout| Self::iso_time_string(&logout)),
+                            "duration": record.duration().map(|duration| duration.whole_seconds()),
+                            "state": record.state.to_string(),
+                        })
+                    })
+                    .collect();
+                let json = serde_json::json!({
+                    "last": records,
+                    "begins": file_time,
+                });
+                uutable::print_json(&json);
+            }
+        }
+    }
+
     #[inline]
     #[allow(clippy::too_many_arguments)]
     fn print_line(
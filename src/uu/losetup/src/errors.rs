@@ -0,0 +1,34 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LosetupError {
+    #[error("{0}: {1}")]
+    Device(String, io::Error),
+
+    #[error("{0}: failed to set up loop device: {1}")]
+    SetUp(String, io::Error),
+
+    #[error("{0}: detach failed: {1}")]
+    Detach(String, io::Error),
+
+    #[error("cannot find an unused loop device: {0}")]
+    NoFreeDevice(io::Error),
+
+    #[error("failed to list loop devices: {0}")]
+    List(io::Error),
+}
+
+impl uucore::error::UError for LosetupError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,248 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::ffi::OsStr;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+use linux_raw_sys::ioctl::BLKSSZGET;
+use linux_raw_sys::loop_device::{
+    LO_FLAGS_AUTOCLEAR, LO_FLAGS_DIRECT_IO, LO_FLAGS_PARTSCAN, LO_FLAGS_READ_ONLY, LO_NAME_SIZE,
+    LOOP_CLR_FD, LOOP_CONFIGURE, LOOP_CTL_GET_FREE, LOOP_GET_STATUS64, loop_config, loop_info64,
+};
+use uu_blockdev::uu_ioctl;
+
+static _PATH_DEV_LOOPCTL: &str = "/dev/loop-control";
+static _PATH_SYS_BLOCK: &str = "/sys/block";
+static _PATH_SYS_DEVBLOCK: &str = "/sys/dev/block";
+
+// From linux/major.h
+const LOOP_MAJOR: u32 = 7;
+
+/// Parameters of a new loop device.
+#[derive(Debug, Default)]
+pub(crate) struct SetupOptions {
+    pub(crate) offset: u64,
+    pub(crate) sizelimit: u64,
+    pub(crate) partscan: bool,
+    pub(crate) read_only: bool,
+}
+
+/// State of a loop device attached to a backing file.
+#[derive(Debug)]
+pub(crate) struct LoopInfo {
+    pub(crate) back_file: PathBuf,
+    pub(crate) back_device: Option<u64>,
+    pub(crate) back_inode: Option<u64>,
+    pub(crate) offset: u64,
+    pub(crate) sizelimit: u64,
+    pub(crate) device_number: Option<(u32, u32)>,
+    pub(crate) sector_size: Option<u64>,
+    flags: u32,
+}
+
+impl LoopInfo {
+    /// Returns `None` if the device at `path` isn't attached to a backing file.
+    ///
+    /// sysfs can be read by everyone, while LOOP_GET_STATUS64 needs the device to be opened, which
+    /// usually only root can do. The ioctl is only used when sysfs doesn't have the attributes.
+    pub(crate) fn read(path: &Path) -> io::Result<Option<Self>> {
+        let rdev = fs::metadata(path)?.rdev();
+        let (major, minor) = (libc::major(rdev), libc::minor(rdev));
+        let sysfs_dir = PathBuf::from(format!("{_PATH_SYS_DEVBLOCK}/{major}:{minor}"));
+
+        if major == LOOP_MAJOR && sysfs_dir.exists() {
+            // The "loop" directory only exists while the device is attached.
+            if !sysfs_dir.join("loop").exists() {
+                return Ok(None);
+            }
+            if let Some(info) = Self::from_sysfs(&sysfs_dir, (major, minor)) {
+                return Ok(Some(info));
+            }
+        }
+
+        LoopDevice::open(path, false)?.status()
+    }
+
+    fn from_sysfs(dir: &Path, device_number: (u32, u32)) -> Option<Self> {
+        let attribute = |name: &str| {
+            let value = fs::read_to_string(dir.join(name)).ok()?;
+            Some(value.trim_end_matches('\n').to_string())
+        };
+        let number = |name: &str| attribute(name)?.parse::<u64>().ok();
+        let flag = |name: &str, flag: u32| Some(if number(name)? != 0 { flag } else { 0 });
+
+        let back_file = PathBuf::from(attribute("loop/backing_file")?);
+        let flags = flag("ro", LO_FLAGS_READ_ONLY as u32)?
+            | flag("loop/autoclear", LO_FLAGS_AUTOCLEAR as u32)?
+            | flag("loop/partscan", LO_FLAGS_PARTSCAN as u32)?
+            | flag("loop/dio", LO_FLAGS_DIRECT_IO as u32)?;
+
+        // sysfs only has the path of the backing file, so this is wrong if it has been replaced
+        // since, and unknown if it has been deleted.
+        let metadata = fs::metadata(&back_file).ok();
+
+        Some(Self {
+            back_device: metadata.as_ref().map(MetadataExt::dev),
+            back_inode: metadata.as_ref().map(MetadataExt::ino),
+            back_file,
+            offset: number("loop/offset")?,
+            sizelimit: number("loop/sizelimit")?,
+            device_number: Some(device_number),
+            sector_size: number("queue/logical_block_size"),
+            flags,
+        })
+    }
+
+    pub(crate) fn autoclear(&self) -> bool {
+        self.flags & LO_FLAGS_AUTOCLEAR as u32 != 0
+    }
+
+    pub(crate) fn partscan(&self) -> bool {
+        self.flags & LO_FLAGS_PARTSCAN as u32 != 0
+    }
+
+    pub(crate) fn read_only(&self) -> bool {
+        self.flags & LO_FLAGS_READ_ONLY as u32 != 0
+    }
+
+    pub(crate) fn direct_io(&self) -> bool {
+        self.flags & LO_FLAGS_DIRECT_IO as u32 != 0
+    }
+}
+
+pub(crate) struct LoopDevice {
+    pub(crate) path: PathBuf,
+    file: File,
+}
+
+impl LoopDevice {
+    pub(crate) fn open(path: &Path, write: bool) -> io::Result<Self> {
+        let file = OpenOptions::new().read(true).write(write).open(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+        })
+    }
+
+    /// Asks the kernel for an unused loop device, creating one if needed.
+    pub(crate) fn find_free() -> io::Result<PathBuf> {
+        let control = File::open(_PATH_DEV_LOOPCTL)?;
+        let index = unsafe { uu_ioctl(&control, LOOP_CTL_GET_FREE, 0)? };
+        Ok(PathBuf::from(format!("/dev/loop{index}")))
+    }
+
+    /// Loop devices which are attached to a backing file, in numerical order.
+    pub(crate) fn used_devices() -> io::Result<Vec<PathBuf>> {
+        let mut numbers: Vec<u32> = fs::read_dir(_PATH_SYS_BLOCK)?
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name();
+                name.to_str()?.strip_prefix("loop")?.parse().ok()
+            })
+            .collect();
+        numbers.sort_unstable();
+
+        // The "loop" directory only exists while the device is attached.
+        Ok(numbers
+            .into_iter()
+            .filter(|number| Path::new(&format!("{_PATH_SYS_BLOCK}/loop{number}/loop")).exists())
+            .map(|number| PathBuf::from(format!("/dev/loop{number}")))
+            .collect())
+    }
+
+    /// Returns `None` if the device isn't attached to a backing file.
+    fn status(&self) -> io::Result<Option<LoopInfo>> {
+        let mut info: loop_info64 = unsafe { std::mem::zeroed() };
+        match unsafe { uu_ioctl(&self.file, LOOP_GET_STATUS64, &mut info as *mut loop_info64) } {
+            Ok(_) => {}
+            Err(err) if err.raw_os_error() == Some(libc::ENXIO) => return Ok(None),
+            Err(err) => return Err(err),
+        }
+
+        // The name in the status is truncated, but sysfs has the whole path.
+        let back_file = self
+            .sysfs_attribute("loop/backing_file")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| {
+                let name = &info.lo_file_name;
+                let len = name.iter().position(|&c| c == 0).unwrap_or(name.len());
+                PathBuf::from(OsStr::from_bytes(&name[..len]))
+            });
+
+        Ok(Some(LoopInfo {
+            back_file,
+            back_device: Some(info.lo_device),
+            back_inode: Some(info.lo_inode),
+            offset: info.lo_offset,
+            sizelimit: info.lo_sizelimit,
+            device_number: self.device_number().ok(),
+            sector_size: self.sector_size().ok(),
+            flags: info.lo_flags,
+        }))
+    }
+
+    pub(crate) fn configure(&self, backing_file: &Path, options: &SetupOptions) -> io::Result<()> {
+        let mut read_only = options.read_only;
+        let file = match OpenOptions::new()
+            .read(true)
+            .write(!read_only)
+            .open(backing_file)
+        {
+            // Like the original, fall back to a read-only device for read-only files.
+            Err(err)
+                if !read_only && matches!(err.raw_os_error(), Some(libc::EROFS | libc::EACCES)) =>
+            {
+                read_only = true;
+                File::open(backing_file)?
+            }
+            result => result?,
+        };
+
+        let mut config: loop_config = unsafe { std::mem::zeroed() };
+        config.fd = std::os::fd::AsRawFd::as_raw_fd(&file) as u32;
+        config.info.lo_offset = options.offset;
+        config.info.lo_sizelimit = options.sizelimit;
+        if read_only {
+            config.info.lo_flags |= LO_FLAGS_READ_ONLY as u32;
+        }
+        if options.partscan {
+            config.info.lo_flags |= LO_FLAGS_PARTSCAN as u32;
+        }
+
+        let name = fs::canonicalize(backing_file).unwrap_or_else(|_| backing_file.into());
+        let name = name.as_os_str().as_bytes();
+        let len = name.len().min(LO_NAME_SIZE as usize - 1);
+        config.info.lo_file_name[..len].copy_from_slice(&name[..len]);
+
+        unsafe { uu_ioctl(&self.file, LOOP_CONFIGURE, &config as *const loop_config)? };
+        Ok(())
+    }
+
+    pub(crate) fn detach(&self) -> io::Result<()> {
+        unsafe { uu_ioctl(&self.file, LOOP_CLR_FD, 0)? };
+        Ok(())
+    }
+
+    fn sector_size(&self) -> io::Result<u64> {
+        let mut size: libc::c_int = 0;
+        unsafe { uu_ioctl(&self.file, BLKSSZGET, &mut size as *mut libc::c_int)? };
+        Ok(size as u64)
+    }
+
+    fn device_number(&self) -> io::Result<(u32, u32)> {
+        let rdev = self.file.metadata()?.rdev();
+        Ok((libc::major(rdev), libc::minor(rdev)))
+    }
+
+    fn sysfs_attribute(&self, attribute: &str) -> Option<String> {
+        let name = self.path.file_name()?.to_str()?;
+        let value = fs::read_to_string(format!("{_PATH_SYS_BLOCK}/{name}/{attribute}")).ok()?;
+        Some(value.trim_end_matches('\n').to_string())
+    }
+}
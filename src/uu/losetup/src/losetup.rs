@@ -0,0 +1,651 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+mod errors;
+#[cfg(target_os = "linux")]
+mod loopdev;
+
+use std::path::PathBuf;
+
+use clap::{Arg, ArgAction, ArgMatches, Command, crate_version, value_parser};
+use uucore::{error::UResult, format_usage, help_about, help_usage};
+use uutable::TableColumn;
+
+const ABOUT: &str = help_about!("losetup.md");
+const USAGE: &str = help_usage!("losetup.md");
+
+mod options {
+    pub const ALL: &str = "all";
+    pub const ARGUMENTS: &str = "arguments";
+    pub const ASSOCIATED: &str = "associated";
+    pub const DETACH: &str = "detach";
+    pub const DETACH_ALL: &str = "detach-all";
+    pub const FIND: &str = "find";
+    pub const JSON: &str = "json";
+    pub const LIST: &str = "list";
+    pub const NOHEADINGS: &str = "noheadings";
+    pub const OFFSET: &str = "offset";
+    pub const OUTPUT: &str = "output";
+    pub const OUTPUT_ALL: &str = "output-all";
+    pub const PARTSCAN: &str = "partscan";
+    pub const READ_ONLY: &str = "read-only";
+    pub const SHOW: &str = "show";
+    pub const SIZELIMIT: &str = "sizelimit";
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    AutoClear,
+    BackFile,
+    BackInode,
+    BackMajorMinor,
+    MajorMinor,
+    Name,
+    Offset,
+    PartScan,
+    ReadOnly,
+    SizeLimit,
+    DirectIo,
+    LogicalSectorSize,
+}
+
+const DEFAULT_COLS: &[Column] = &[
+    Column::Name,
+    Column::SizeLimit,
+    Column::Offset,
+    Column::AutoClear,
+    Column::ReadOnly,
+    Column::BackFile,
+    Column::DirectIo,
+    Column::LogicalSectorSize,
+];
+
+impl TableColumn for Column {
+    const ALL: &'static [Self] = &[
+        Self::AutoClear,
+        Self::BackFile,
+        Self::BackInode,
+        Self::BackMajorMinor,
+        Self::MajorMinor,
+        Self::Name,
+        Self::Offset,
+        Self::PartScan,
+        Self::ReadOnly,
+        Self::SizeLimit,
+        Self::DirectIo,
+        Self::LogicalSectorSize,
+    ];
+
+    fn header_text(&self) -> &'static str {
+        match self {
+            Self::AutoClear => "AUTOCLEAR",
+            Self::BackFile => "BACK-FILE",
+            Self::BackInode => "BACK-INO",
+            Self::BackMajorMinor => "BACK-MAJ:MIN",
+            Self::MajorMinor => "MAJ:MIN",
+            Self::Name => "NAME",
+            Self::Offset => "OFFSET",
+            Self::PartScan => "PARTSCAN",
+            Self::ReadOnly => "RO",
+            Self::SizeLimit => "SIZELIMIT",
+            Self::DirectIo => "DIO",
+            Self::LogicalSectorSize => "LOG-SEC",
+        }
+    }
+
+    fn get_help(&self) -> &'static str {
+        match self {
+            Self::AutoClear => "autoclear flag set",
+            Self::BackFile => "device backing file",
+            Self::BackInode => "backing file inode number",
+            Self::BackMajorMinor => "backing file major:minor device number",
+            Self::MajorMinor => "loop device major:minor number",
+            Self::Name => "loop device name",
+            Self::Offset => "offset from the beginning",
+            Self::PartScan => "partscan flag set",
+            Self::ReadOnly => "read-only device",
+            Self::SizeLimit => "size limit of the file in bytes",
+            Self::DirectIo => "access backing file with direct-io",
+            Self::LogicalSectorSize => "logical sector size in bytes",
+        }
+    }
+
+    fn get_float_right(&self) -> bool {
+        !matches!(self, Self::Name | Self::BackFile)
+    }
+}
+
+fn parse_size(value: &str) -> Result<u64, String> {
+    uucore::parse_size::parse_size_u64(value).map_err(|err| err.to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::os::unix::fs::MetadataExt;
+    use std::path::Path;
+    use std::thread;
+    use std::time::Duration;
+
+    use serde_json::Value;
+    use uucore::error::USimpleError;
+
+    use crate::errors::LosetupError;
+    use crate::loopdev::{LoopDevice, LoopInfo, SetupOptions};
+    use crate::*;
+
+    // How often util-linux tries to set up a free device before giving up
+    const SETUP_ATTEMPTS: u32 = 64;
+    const SETUP_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+    struct OutputOptions {
+        cols: Vec<Column>,
+        json: bool,
+        noheadings: bool,
+    }
+
+    struct Row {
+        path: String,
+        info: LoopInfo,
+    }
+
+    impl Row {
+        fn new(path: &Path, info: LoopInfo) -> Self {
+            Self {
+                path: path.to_string_lossy().into_owned(),
+                info,
+            }
+        }
+
+        fn get_value(&self, col: &Column) -> String {
+            let flag = |set: bool| if set { "1" } else { "0" }.to_string();
+            match col {
+                Column::AutoClear => flag(self.info.autoclear()),
+                Column::BackFile => self.info.back_file.to_string_lossy().into_owned(),
+                Column::BackInode => self
+                    .info
+                    .back_inode
+                    .map(|inode| inode.to_string())
+                    .unwrap_or_default(),
+                Column::BackMajorMinor => self
+                    .info
+                    .back_device
+                    .map(|dev| format!("{}:{}", libc::major(dev), libc::minor(dev)))
+                    .unwrap_or_default(),
+                Column::MajorMinor => self
+                    .info
+                    .device_number
+                    .map(|(major, minor)| format!("{}:{}", major, minor))
+                    .unwrap_or_default(),
+                Column::Name => self.path.clone(),
+                Column::Offset => self.info.offset.to_string(),
+                Column::PartScan => flag(self.info.partscan()),
+                Column::ReadOnly => flag(self.info.read_only()),
+                Column::SizeLimit => self.info.sizelimit.to_string(),
+                Column::DirectIo => flag(self.info.direct_io()),
+                Column::LogicalSectorSize => self
+                    .info
+                    .sector_size
+                    .map(|size| size.to_string())
+                    .unwrap_or_default(),
+            }
+        }
+
+        // Same as `get_value`, but typed the way libsmartcols does for each column
+        fn get_json_value(&self, col: &Column) -> Value {
+            match col {
+                Column::AutoClear => self.info.autoclear().into(),
+                Column::BackInode => self.info.back_inode.into(),
+                Column::Offset => self.info.offset.into(),
+                Column::PartScan => self.info.partscan().into(),
+                Column::ReadOnly => self.info.read_only().into(),
+                Column::SizeLimit => self.info.sizelimit.into(),
+                Column::DirectIo => self.info.direct_io().into(),
+                Column::LogicalSectorSize => self.info.sector_size.into(),
+                _ => {
+                    let value = self.get_value(col);
+                    if value.is_empty() {
+                        Value::Null
+                    } else {
+                        value.into()
+                    }
+                }
+            }
+        }
+    }
+
+    fn print_table(rows: &[Row], opts: &OutputOptions) {
+        let values: Vec<Vec<String>> = rows
+            .iter()
+            .map(|row| opts.cols.iter().map(|col| row.get_value(col)).collect())
+            .collect();
+
+        let mut column_widths: Vec<_> = opts
+            .cols
+            .iter()
+            .map(|col| {
+                if opts.noheadings {
+                    0
+                } else {
+                    col.header_text().len()
+                }
+            })
+            .collect();
+
+        for row in &values {
+            for (i, value) in row.iter().enumerate() {
+                column_widths[i] = column_widths[i].max(value.chars().count());
+            }
+        }
+
+        let format_row = |values: Vec<String>| {
+            let cells: Vec<_> = opts
+                .cols
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(i, (col, value))| {
+                    let width = column_widths[i];
+                    if col.get_float_right() {
+                        format!("{:>width$}", value)
+                    } else {
+                        format!("{:<width$}", value)
+                    }
+                })
+                .collect();
+            cells.join(" ").trim_end().to_string()
+        };
+
+        if !opts.noheadings {
+            let headers = opts.cols.iter().map(|col| col.header_text().to_string());
+            println!("{}", format_row(headers.collect()));
+        }
+
+        for row in values {
+            println!("{}", format_row(row));
+        }
+    }
+
+    fn print_json(rows: &[Row], opts: &OutputOptions) {
+        let records: Vec<_> = rows
+            .iter()
+            .map(|row| {
+                let record: serde_json::Map<_, _> = opts
+                    .cols
+                    .iter()
+                    .map(|col| (col.header_text().to_lowercase(), row.get_json_value(col)))
+                    .collect();
+                Value::Object(record)
+            })
+            .collect();
+
+        uutable::print_json(&serde_json::json!({ "loopdevices": records }));
+    }
+
+    // The traditional one-line description, e.g. "/dev/loop0: [2049]:1234 (/tmp/disk.img)"
+    fn print_info(path: &Path, info: &LoopInfo) {
+        let mut line = format!(
+            "{}: [{}]:{} ({})",
+            path.display(),
+            info.back_device
+                .map(|dev| format!("{dev:04}"))
+                .unwrap_or_default(),
+            info.back_inode
+                .map(|inode| inode.to_string())
+                .unwrap_or_default(),
+            info.back_file.display()
+        );
+        if info.offset != 0 {
+            line.push_str(&format!(", offset {}", info.offset));
+        }
+        if info.sizelimit != 0 {
+            line.push_str(&format!(", sizelimit {}", info.sizelimit));
+        }
+        println!("{line}");
+    }
+
+    fn device_info(path: &Path) -> Result<LoopInfo, LosetupError> {
+        let name = || path.to_string_lossy().into_owned();
+        match LoopInfo::read(path) {
+            Ok(Some(info)) => Ok(info),
+            Ok(None) => Err(LosetupError::Device(
+                name(),
+                std::io::Error::from_raw_os_error(libc::ENXIO),
+            )),
+            Err(err) => Err(LosetupError::Device(name(), err)),
+        }
+    }
+
+    fn detach(path: &Path) -> Result<(), LosetupError> {
+        let name = || path.to_string_lossy().into_owned();
+        LoopDevice::open(path, false)
+            .and_then(|device| device.detach())
+            .map_err(|err| LosetupError::Detach(name(), err))
+    }
+
+    fn set_up(
+        device: Option<&PathBuf>,
+        file: &Path,
+        options: &SetupOptions,
+    ) -> Result<PathBuf, LosetupError> {
+        let set_up_error = |err| LosetupError::SetUp(file.to_string_lossy().into_owned(), err);
+
+        if let Some(path) = device {
+            let device = LoopDevice::open(path, true).map_err(set_up_error)?;
+            device.configure(file, options).map_err(set_up_error)?;
+            return Ok(path.clone());
+        }
+
+        // Another process may take the free device before we configure it, so try again. A device
+        // held open exclusively keeps being reported as free though, so don't try forever.
+        let mut attempts = 1;
+        loop {
+            let path = LoopDevice::find_free().map_err(LosetupError::NoFreeDevice)?;
+            let device = LoopDevice::open(&path, true).map_err(set_up_error)?;
+            match device.configure(file, options) {
+                Ok(()) => return Ok(path),
+                Err(err)
+                    if err.raw_os_error() == Some(libc::EBUSY) && attempts < SETUP_ATTEMPTS =>
+                {
+                    attempts += 1;
+                    thread::sleep(SETUP_RETRY_DELAY);
+                }
+                Err(err) => return Err(set_up_error(err)),
+            }
+        }
+    }
+
+    pub fn losetup(matches: &ArgMatches) -> UResult<()> {
+        let arguments: Vec<&PathBuf> = matches
+            .get_many::<PathBuf>(options::ARGUMENTS)
+            .map(|arguments| arguments.collect())
+            .unwrap_or_default();
+        let offset = matches.get_one::<u64>(options::OFFSET).copied();
+
+        if matches.get_flag(options::DETACH_ALL) {
+            let devices = LoopDevice::used_devices().map_err(LosetupError::List)?;
+            for path in devices {
+                uucore::show_if_err!(detach(&path));
+            }
+            return Ok(());
+        }
+
+        if matches.get_flag(options::DETACH) {
+            for path in arguments {
+                uucore::show_if_err!(detach(path));
+            }
+            return Ok(());
+        }
+
+        let find = matches.get_flag(options::FIND);
+        if arguments.len() > if find { 1 } else { 2 } {
+            return Err(USimpleError::new(1, "unexpected arguments"));
+        }
+
+        if find || arguments.len() == 2 {
+            let (device, file) = if find {
+                (None, arguments.first())
+            } else {
+                (arguments.first().copied(), arguments.get(1))
+            };
+
+            // Without a file, --find only prints the name of the first unused device.
+            let Some(file) = file else {
+                let path = LoopDevice::find_free().map_err(LosetupError::NoFreeDevice)?;
+                println!("{}", path.display());
+                return Ok(());
+            };
+
+            let setup_options = SetupOptions {
+                offset: offset.unwrap_or(0),
+                sizelimit: matches
+                    .get_one::<u64>(options::SIZELIMIT)
+                    .copied()
+                    .unwrap_or(0),
+                partscan: matches.get_flag(options::PARTSCAN),
+                read_only: matches.get_flag(options::READ_ONLY),
+            };
+
+            let path = set_up(device, file, &setup_options)?;
+            if matches.get_flag(options::SHOW) {
+                println!("{}", path.display());
+            }
+            return Ok(());
+        }
+
+        let associated = matches.get_one::<PathBuf>(options::ASSOCIATED);
+        // JSON is a --list output format.
+        let list = matches.get_flag(options::LIST)
+            || matches.get_flag(options::JSON)
+            || (arguments.is_empty() && associated.is_none() && !matches.get_flag(options::ALL));
+
+        let devices: Vec<(PathBuf, LoopInfo)> = if let Some(&path) = arguments.first() {
+            vec![(path.clone(), device_info(path)?)]
+        } else {
+            // A device which can't be read is reported, but doesn't stop the listing.
+            LoopDevice::used_devices()
+                .map_err(LosetupError::List)?
+                .into_iter()
+                .filter_map(|path| match device_info(&path) {
+                    Ok(info) => Some((path, info)),
+                    Err(err) => {
+                        uucore::show!(err);
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        let devices = match associated {
+            Some(file) => {
+                let metadata = std::fs::metadata(file).map_err(|err| {
+                    LosetupError::Device(file.to_string_lossy().into_owned(), err)
+                })?;
+                devices
+                    .into_iter()
+                    .filter(|(_, info)| {
+                        info.back_device == Some(metadata.dev())
+                            && info.back_inode == Some(metadata.ino())
+                            && offset.is_none_or(|offset| info.offset == offset)
+                    })
+                    .collect()
+            }
+            None => devices,
+        };
+
+        if !list {
+            for (path, info) in &devices {
+                print_info(path, info);
+            }
+            return Ok(());
+        }
+
+        let output_opts = OutputOptions {
+            cols: if let Some(cols) = matches.get_one::<Vec<Column>>(options::OUTPUT) {
+                cols.clone()
+            } else if matches.get_flag(options::OUTPUT_ALL) {
+                Column::ALL.to_vec()
+            } else {
+                Vec::from(DEFAULT_COLS)
+            },
+            json: matches.get_flag(options::JSON),
+            noheadings: matches.get_flag(options::NOHEADINGS),
+        };
+
+        let rows: Vec<_> = devices
+            .into_iter()
+            .map(|(path, info)| Row::new(&path, info))
+            .collect();
+
+        if output_opts.json {
+            print_json(&rows, &output_opts);
+        } else {
+            print_table(&rows, &output_opts);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches: ArgMatches = uu_app().try_get_matches_from(args)?;
+    linux::losetup(&matches)
+}
+
+#[cfg(not(target_os = "linux"))]
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let _matches: ArgMatches = uu_app().try_get_matches_from(args)?;
+
+    Err(uucore::error::USimpleError::new(
+        1,
+        "`losetup` is available only on Linux.",
+    ))
+}
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::ALL)
+                .short('a')
+                .long(options::ALL)
+                .help("list all used devices")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::DETACH)
+                .short('d')
+                .long(options::DETACH)
+                .help("detach one or more devices")
+                .requires(options::ARGUMENTS)
+                .conflicts_with_all([
+                    options::ALL,
+                    options::ASSOCIATED,
+                    options::DETACH_ALL,
+                    options::FIND,
+                    options::JSON,
+                    options::LIST,
+                ])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::DETACH_ALL)
+                .short('D')
+                .long(options::DETACH_ALL)
+                .help("detach all used devices")
+                .conflicts_with_all([
+                    options::ALL,
+                    options::ARGUMENTS,
+                    options::ASSOCIATED,
+                    options::FIND,
+                    options::JSON,
+                    options::LIST,
+                ])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::FIND)
+                .short('f')
+                .long(options::FIND)
+                .help("find first unused device")
+                .conflicts_with_all([
+                    options::ALL,
+                    options::ASSOCIATED,
+                    options::JSON,
+                    options::LIST,
+                ])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::ASSOCIATED)
+                .short('j')
+                .long(options::ASSOCIATED)
+                .value_name("file")
+                .help("list all devices associated with <file>")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new(options::OFFSET)
+                .short('o')
+                .long(options::OFFSET)
+                .value_name("num")
+                .help("start at offset <num> into file")
+                .value_parser(parse_size),
+        )
+        .arg(
+            Arg::new(options::SIZELIMIT)
+                .long(options::SIZELIMIT)
+                .value_name("num")
+                .help("device is limited to <num> bytes of the file")
+                .value_parser(parse_size),
+        )
+        .arg(
+            Arg::new(options::PARTSCAN)
+                .short('P')
+                .long(options::PARTSCAN)
+                .help("create a partitioned loop device")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::READ_ONLY)
+                .short('r')
+                .long(options::READ_ONLY)
+                .help("set up a read-only loop device")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::SHOW)
+                .long(options::SHOW)
+                .help("print device name after setup (with -f)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::JSON)
+                .short('J')
+                .long(options::JSON)
+                .help("use JSON --list output format")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::LIST)
+                .short('l')
+                .long(options::LIST)
+                .help("list info about all or specified (default)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NOHEADINGS)
+                .short('n')
+                .long(options::NOHEADINGS)
+                .help("don't print headings for --list output")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::OUTPUT)
+                .short('O')
+                .long(options::OUTPUT)
+                .value_name("cols")
+                .help("specify columns to output for --list")
+                .value_parser(|list: &str| uutable::parse_columns(list, DEFAULT_COLS)),
+        )
+        .arg(
+            Arg::new(options::OUTPUT_ALL)
+                .long(options::OUTPUT_ALL)
+                .help("output all columns")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::ARGUMENTS)
+                .value_name("loopdev|file")
+                .num_args(0..)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .after_help(uutable::columns_help::<Column>())
+}
@@ -0,0 +1,28 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LsblkError {
+    #[error("{0}: not a block device")]
+    NotABlockDevice(String),
+
+    #[error("{0}: failed to get sysfs name")]
+    NoSysfsName(String),
+
+    #[error("failed to access sysfs directory: {0}: {1}")]
+    Sysfs(String, io::Error),
+}
+
+impl uucore::error::UError for LsblkError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        false
+    }
+}
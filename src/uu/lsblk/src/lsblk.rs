@@ -0,0 +1,648 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+mod errors;
+mod mounts;
+mod sysfs;
+
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+
+use clap::{Arg, ArgAction, ArgMatches, Command, crate_version, value_parser};
+use uucore::error::{UResult, set_exit_code};
+use uucore::{format_usage, help_about, help_usage};
+use uutable::{Row, Table, TableColumn, ValueKind};
+
+use crate::errors::LsblkError;
+use crate::mounts::MountPoints;
+use crate::sysfs::BlockDevice;
+
+const ABOUT: &str = help_about!("lsblk.md");
+const USAGE: &str = help_usage!("lsblk.md");
+
+mod options {
+    pub const ALL: &str = "all";
+    pub const ASCII: &str = "ascii";
+    pub const BYTES: &str = "bytes";
+    pub const DEVICES: &str = "devices";
+    pub const EXCLUDE: &str = "exclude";
+    pub const INCLUDE: &str = "include";
+    pub const JSON: &str = "json";
+    pub const LIST: &str = "list";
+    pub const NODEPS: &str = "nodeps";
+    pub const NOHEADINGS: &str = "noheadings";
+    pub const OUTPUT: &str = "output";
+    pub const OUTPUT_ALL: &str = "output-all";
+    pub const PAIRS: &str = "pairs";
+    pub const PATHS: &str = "paths";
+    pub const RAW: &str = "raw";
+    pub const SYSROOT: &str = "sysroot";
+}
+
+// Exit codes of the original when devices are given on the command line.
+const EXIT_NOT_FOUND: i32 = 32;
+const EXIT_SOME_NOT_FOUND: i32 = 64;
+
+// RAM disks are hidden unless asked for.
+const RAM_DISK_MAJOR: u32 = 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Column {
+    Name,
+    KName,
+    Path,
+    MajorMinor,
+    MountPoint,
+    MountPoints,
+    ReadAhead,
+    ReadOnly,
+    Removable,
+    Model,
+    Serial,
+    Size,
+    State,
+    Alignment,
+    MinIo,
+    OptIo,
+    PhySec,
+    LogSec,
+    Rotational,
+    Scheduler,
+    RequestQueueSize,
+    Type,
+    DiscardAlignment,
+    DiscardGranularity,
+    DiscardMax,
+    DiscardZeroes,
+    WriteSame,
+    ParentKName,
+    Revision,
+    Vendor,
+    Zoned,
+    Dax,
+    DiskSeq,
+}
+
+const DEFAULT_COLS: &[Column] = &[
+    Column::Name,
+    Column::MajorMinor,
+    Column::Removable,
+    Column::Size,
+    Column::ReadOnly,
+    Column::Type,
+    Column::MountPoints,
+];
+
+impl TableColumn for Column {
+    const ALL: &'static [Self] = &[
+        Self::Name,
+        Self::KName,
+        Self::Path,
+        Self::MajorMinor,
+        Self::MountPoint,
+        Self::MountPoints,
+        Self::ReadAhead,
+        Self::ReadOnly,
+        Self::Removable,
+        Self::Model,
+        Self::Serial,
+        Self::Size,
+        Self::State,
+        Self::Alignment,
+        Self::MinIo,
+        Self::OptIo,
+        Self::PhySec,
+        Self::LogSec,
+        Self::Rotational,
+        Self::Scheduler,
+        Self::RequestQueueSize,
+        Self::Type,
+        Self::DiscardAlignment,
+        Self::DiscardGranularity,
+        Self::DiscardMax,
+        Self::DiscardZeroes,
+        Self::WriteSame,
+        Self::ParentKName,
+        Self::Revision,
+        Self::Vendor,
+        Self::Zoned,
+        Self::Dax,
+        Self::DiskSeq,
+    ];
+
+    fn header_text(&self) -> &'static str {
+        match self {
+            Self::Name => "NAME",
+            Self::KName => "KNAME",
+            Self::Path => "PATH",
+            Self::MajorMinor => "MAJ:MIN",
+            Self::MountPoint => "MOUNTPOINT",
+            Self::MountPoints => "MOUNTPOINTS",
+            Self::ReadAhead => "RA",
+            Self::ReadOnly => "RO",
+            Self::Removable => "RM",
+            Self::Model => "MODEL",
+            Self::Serial => "SERIAL",
+            Self::Size => "SIZE",
+            Self::State => "STATE",
+            Self::Alignment => "ALIGNMENT",
+            Self::MinIo => "MIN-IO",
+            Self::OptIo => "OPT-IO",
+            Self::PhySec => "PHY-SEC",
+            Self::LogSec => "LOG-SEC",
+            Self::Rotational => "ROTA",
+            Self::Scheduler => "SCHED",
+            Self::RequestQueueSize => "RQ-SIZE",
+            Self::Type => "TYPE",
+            Self::DiscardAlignment => "DISC-ALN",
+            Self::DiscardGranularity => "DISC-GRAN",
+            Self::DiscardMax => "DISC-MAX",
+            Self::DiscardZeroes => "DISC-ZERO",
+            Self::WriteSame => "WSAME",
+            Self::ParentKName => "PKNAME",
+            Self::Revision => "REV",
+            Self::Vendor => "VENDOR",
+            Self::Zoned => "ZONED",
+            Self::Dax => "DAX",
+            Self::DiskSeq => "DISK-SEQ",
+        }
+    }
+
+    fn get_help(&self) -> &'static str {
+        match self {
+            Self::Name => "device name",
+            Self::KName => "internal kernel device name",
+            Self::Path => "path to the device node",
+            Self::MajorMinor => "major:minor device number",
+            Self::MountPoint => "where the device is mounted",
+            Self::MountPoints => "all locations where device is mounted",
+            Self::ReadAhead => "read-ahead of the device",
+            Self::ReadOnly => "read-only device",
+            Self::Removable => "removable device",
+            Self::Model => "device identifier",
+            Self::Serial => "disk serial number",
+            Self::Size => "size of the device",
+            Self::State => "state of the device",
+            Self::Alignment => "alignment offset",
+            Self::MinIo => "minimum I/O size",
+            Self::OptIo => "optimal I/O size",
+            Self::PhySec => "physical sector size",
+            Self::LogSec => "logical sector size",
+            Self::Rotational => "rotational device",
+            Self::Scheduler => "I/O scheduler name",
+            Self::RequestQueueSize => "request queue size",
+            Self::Type => "device type",
+            Self::DiscardAlignment => "discard alignment offset",
+            Self::DiscardGranularity => "discard granularity",
+            Self::DiscardMax => "discard max bytes",
+            Self::DiscardZeroes => "discard zeroes data",
+            Self::WriteSame => "write same max bytes",
+            Self::ParentKName => "internal parent kernel device name",
+            Self::Revision => "device revision",
+            Self::Vendor => "device vendor",
+            Self::Zoned => "zone model",
+            Self::Dax => "dax-capable device",
+            Self::DiskSeq => "disk sequence number",
+        }
+    }
+
+    fn get_float_right(&self) -> bool {
+        self.value_kind() != ValueKind::String && self.value_kind() != ValueKind::Array
+    }
+
+    fn value_kind(&self) -> ValueKind {
+        match self {
+            Self::ReadOnly
+            | Self::Removable
+            | Self::Rotational
+            | Self::DiscardZeroes
+            | Self::Dax => ValueKind::Boolean,
+            Self::ReadAhead
+            | Self::Alignment
+            | Self::MinIo
+            | Self::OptIo
+            | Self::PhySec
+            | Self::LogSec
+            | Self::RequestQueueSize
+            | Self::DiscardAlignment
+            | Self::DiskSeq => ValueKind::Number,
+            Self::Size | Self::DiscardGranularity | Self::DiscardMax | Self::WriteSame => {
+                ValueKind::Size
+            }
+            Self::MountPoints => ValueKind::Array,
+            _ => ValueKind::String,
+        }
+    }
+
+    fn get_width_hint(&self) -> usize {
+        if self == &Self::Size { 5 } else { 0 }
+    }
+}
+
+struct Filter {
+    all: bool,
+    include: Option<Vec<u32>>,
+    exclude: Vec<u32>,
+}
+
+impl Filter {
+    // Like the original, the filters only apply to top-level devices.
+    fn is_listed(&self, device: &BlockDevice) -> bool {
+        let major = device.device_number().map(|(major, _)| major);
+
+        if let Some(include) = &self.include {
+            return major.is_some_and(|major| include.contains(&major));
+        }
+        if major.is_some_and(|major| self.exclude.contains(&major)) {
+            return false;
+        }
+        self.all || device.size() != Some(0)
+    }
+}
+
+struct OutputOptions {
+    cols: Vec<Column>,
+    bytes: bool,
+    json: bool,
+    list: bool,
+    nodeps: bool,
+    noheadings: bool,
+    pairs: bool,
+    paths: bool,
+    raw: bool,
+    ascii: bool,
+}
+
+impl OutputOptions {
+    // Devices are only drawn as a tree when there is a NAME column to hang it on.
+    fn tree(&self) -> bool {
+        !self.list && !self.raw && !self.pairs && self.cols.contains(&Column::Name)
+    }
+
+    fn table(&self) -> Table<'_, Column> {
+        Table {
+            cols: &self.cols,
+            noheadings: self.noheadings,
+            tree: self.tree().then_some(Column::Name),
+            ascii: self.ascii,
+            bytes: self.bytes,
+        }
+    }
+}
+
+/// Builds the row of a device, with the devices which depend on it as its children.
+fn device_row(
+    device: &BlockDevice,
+    parent: Option<&BlockDevice>,
+    sys: &Path,
+    mounts: &MountPoints,
+    opts: &OutputOptions,
+) -> Row {
+    let values = opts
+        .cols
+        .iter()
+        .map(|col| get_value(device, parent, col, mounts, opts))
+        .collect();
+
+    let children = if opts.nodeps {
+        Vec::new()
+    } else {
+        device
+            .partitions()
+            .into_iter()
+            .chain(device.holders(sys))
+            .map(|child| device_row(&child, Some(device), sys, mounts, opts))
+            .collect()
+    };
+
+    Row { values, children }
+}
+
+fn get_value(
+    device: &BlockDevice,
+    parent: Option<&BlockDevice>,
+    col: &Column,
+    mounts: &MountPoints,
+    opts: &OutputOptions,
+) -> Option<String> {
+    let size = |value: Option<String>| {
+        let bytes: u64 = value?.parse().ok()?;
+        Some(if opts.bytes {
+            bytes.to_string()
+        } else {
+            uutable::size_to_human_string(bytes)
+        })
+    };
+    // Hardware details belong to the whole disk, and aren't repeated for its partitions.
+    let disk_only = |name: &str| {
+        if device.is_partition() {
+            None
+        } else {
+            device
+                .attribute(name)
+                .map(|value| value.trim_end().to_string())
+        }
+    };
+    let mountpoints = || device.device_number().map(|dev| mounts.get(dev));
+
+    match col {
+        Column::Name if opts.paths => Some(device.path()),
+        Column::Name => Some(device.name()),
+        Column::KName => Some(device.kname.clone()),
+        Column::Path => Some(device.path()),
+        Column::MajorMinor => {
+            let (major, minor) = device.device_number()?;
+            // Tables line up the colons.
+            if opts.json || opts.pairs || opts.raw {
+                Some(format!("{major}:{minor}"))
+            } else {
+                Some(format!("{major:>3}:{minor:<3}"))
+            }
+        }
+        Column::MountPoint => mountpoints()?.first().cloned(),
+        Column::MountPoints => Some(mountpoints()?.join("\n")).filter(|value| !value.is_empty()),
+        Column::ReadAhead => device.queue_attribute("read_ahead_kb"),
+        Column::ReadOnly => device.attribute("ro"),
+        Column::Removable => device.disk_attribute("removable"),
+        Column::Model => disk_only("device/model"),
+        Column::Serial => disk_only("device/serial").or_else(|| disk_only("serial")),
+        Column::Size => size(device.size().map(|size| size.to_string())),
+        Column::State if device.is_partition() => None,
+        Column::State => match device.attribute("dm/suspended") {
+            Some(suspended) if suspended == "1" => Some("suspended".to_string()),
+            Some(_) => Some("running".to_string()),
+            None => device.attribute("device/state"),
+        },
+        Column::Alignment => device.attribute("alignment_offset"),
+        Column::MinIo => device.queue_attribute("minimum_io_size"),
+        Column::OptIo => device.queue_attribute("optimal_io_size"),
+        Column::PhySec => device.queue_attribute("physical_block_size"),
+        Column::LogSec => device.queue_attribute("logical_block_size"),
+        Column::Rotational => device.queue_attribute("rotational"),
+        Column::Scheduler => {
+            // The active scheduler is in brackets, e.g. "mq-deadline kyber [bfq] none"
+            let schedulers = device.queue_attribute("scheduler")?;
+            match schedulers.split_once('[') {
+                Some((_, active)) => active.split_once(']').map(|(name, _)| name.to_string()),
+                None => Some(schedulers),
+            }
+        }
+        Column::RequestQueueSize => device.queue_attribute("nr_requests"),
+        Column::Type => Some(device.device_type()),
+        Column::DiscardAlignment => device.attribute("discard_alignment"),
+        Column::DiscardGranularity => size(device.queue_attribute("discard_granularity")),
+        Column::DiscardMax => size(device.queue_attribute("discard_max_bytes")),
+        Column::DiscardZeroes => device.queue_attribute("discard_zeroes_data"),
+        Column::WriteSame => size(device.queue_attribute("write_same_max_bytes")),
+        Column::ParentKName => parent
+            .map(|parent| parent.kname.clone())
+            .or_else(|| device.disk_kname().map(String::from)),
+        Column::Revision => disk_only("device/rev"),
+        Column::Vendor => disk_only("device/vendor"),
+        Column::Zoned => device.queue_attribute("zoned"),
+        Column::Dax => device.queue_attribute("dax"),
+        Column::DiskSeq => device.disk_attribute("diskseq"),
+    }
+}
+
+// Finds the sysfs entry of a device node given on the command line.
+fn find_device(sys: &Path, path: &Path) -> Result<BlockDevice, LsblkError> {
+    let name = || path.to_string_lossy().into_owned();
+    let metadata = fs::metadata(path).map_err(|_| LsblkError::NotABlockDevice(name()))?;
+    if !metadata.file_type().is_block_device() {
+        return Err(LsblkError::NotABlockDevice(name()));
+    }
+
+    let rdev = metadata.rdev();
+    BlockDevice::from_device_number(sys, libc::major(rdev), libc::minor(rdev))
+        .ok_or_else(|| LsblkError::NoSysfsName(name()))
+}
+
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches: ArgMatches = uu_app().try_get_matches_from(args)?;
+
+    let root = matches
+        .get_one::<PathBuf>(options::SYSROOT)
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("/"));
+    let sys = root.join("sys");
+
+    let all = matches.get_flag(options::ALL);
+    let filter = Filter {
+        all,
+        include: matches
+            .get_many::<u32>(options::INCLUDE)
+            .map(|majors| majors.copied().collect()),
+        exclude: match matches.get_many::<u32>(options::EXCLUDE) {
+            Some(majors) => majors.copied().collect(),
+            None if all => Vec::new(),
+            None => vec![RAM_DISK_MAJOR],
+        },
+    };
+
+    let raw = matches.get_flag(options::RAW);
+    let pairs = matches.get_flag(options::PAIRS);
+    let output_opts = OutputOptions {
+        cols: if let Some(cols) = matches.get_one::<Vec<Column>>(options::OUTPUT) {
+            cols.clone()
+        } else if matches.get_flag(options::OUTPUT_ALL) {
+            Column::ALL.to_vec()
+        } else {
+            Vec::from(DEFAULT_COLS)
+        },
+        bytes: matches.get_flag(options::BYTES),
+        json: matches.get_flag(options::JSON),
+        list: matches.get_flag(options::LIST) || raw || pairs,
+        nodeps: matches.get_flag(options::NODEPS),
+        noheadings: matches.get_flag(options::NOHEADINGS),
+        pairs,
+        paths: matches.get_flag(options::PATHS),
+        raw,
+        ascii: matches.get_flag(options::ASCII),
+    };
+
+    let devices = match matches.get_many::<PathBuf>(options::DEVICES) {
+        Some(paths) => {
+            let paths: Vec<_> = paths.collect();
+            let mut devices = Vec::new();
+            for path in &paths {
+                match find_device(&sys, path) {
+                    Ok(device) => devices.push(device),
+                    Err(err) => uucore::show!(err),
+                }
+            }
+
+            if devices.is_empty() {
+                set_exit_code(EXIT_NOT_FOUND);
+            } else if devices.len() < paths.len() {
+                set_exit_code(EXIT_SOME_NOT_FOUND);
+            }
+            devices
+        }
+        None => {
+            let dir = sys.join(sysfs::_PATH_SYS_BLOCK);
+            BlockDevice::whole_disks(&sys)
+                .map_err(|err| LsblkError::Sysfs(dir.to_string_lossy().into_owned(), err))?
+                .into_iter()
+                // Devices built on top of others are shown below them.
+                .filter(|device| !device.has_slaves() && filter.is_listed(device))
+                .collect()
+        }
+    };
+
+    if devices.is_empty() {
+        return Ok(());
+    }
+
+    let mounts = MountPoints::read(&root);
+    let rows: Vec<_> = devices
+        .iter()
+        .map(|device| device_row(device, None, &sys, &mounts, &output_opts))
+        .collect();
+
+    let table = output_opts.table();
+    if output_opts.json {
+        table.print_json("blockdevices", &rows);
+    } else if output_opts.pairs {
+        table.print_pairs(&rows);
+    } else if output_opts.raw {
+        table.print_raw(&rows);
+    } else {
+        table.print_table(&rows);
+    }
+
+    Ok(())
+}
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::ALL)
+                .short('a')
+                .long(options::ALL)
+                .help("print all devices")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::BYTES)
+                .short('b')
+                .long(options::BYTES)
+                .help("print SIZE in bytes rather than in human readable format")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NODEPS)
+                .short('d')
+                .long(options::NODEPS)
+                .help("don't print slaves or holders")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::EXCLUDE)
+                .short('e')
+                .long(options::EXCLUDE)
+                .value_name("list")
+                .help("exclude devices by major number (default: RAM disks)")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u32))
+                .conflicts_with(options::INCLUDE),
+        )
+        .arg(
+            Arg::new(options::INCLUDE)
+                .short('I')
+                .long(options::INCLUDE)
+                .value_name("list")
+                .help("show only devices with specified major numbers")
+                .value_delimiter(',')
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(
+            Arg::new(options::ASCII)
+                .short('i')
+                .long(options::ASCII)
+                .help("use ascii characters only")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::JSON)
+                .short('J')
+                .long(options::JSON)
+                .help("use JSON output format")
+                .conflicts_with_all([options::PAIRS, options::RAW])
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::LIST)
+                .short('l')
+                .long(options::LIST)
+                .help("use list format output")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NOHEADINGS)
+                .short('n')
+                .long(options::NOHEADINGS)
+                .help("don't print headings")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::OUTPUT)
+                .short('o')
+                .long(options::OUTPUT)
+                .value_name("list")
+                .help("output columns")
+                .value_parser(|list: &str| uutable::parse_columns(list, DEFAULT_COLS)),
+        )
+        .arg(
+            Arg::new(options::OUTPUT_ALL)
+                .short('O')
+                .long(options::OUTPUT_ALL)
+                .help("output all columns")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::PATHS)
+                .short('p')
+                .long(options::PATHS)
+                .help("print complete device path")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::PAIRS)
+                .short('P')
+                .long(options::PAIRS)
+                .help("use key=\"value\" output format")
+                .conflicts_with(options::RAW)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::RAW)
+                .short('r')
+                .long(options::RAW)
+                .help("use raw output format")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::SYSROOT)
+                .long(options::SYSROOT)
+                .value_name("dir")
+                .help("use specified directory as system root")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new(options::DEVICES)
+                .value_name("device")
+                .num_args(0..)
+                .action(ArgAction::Append)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .after_help(uutable::columns_help::<Column>())
+}
@@ -0,0 +1,57 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::Path;
+
+use uu_findmnt::{TableFormat, parse_table, unescape};
+
+const _PATH_PROC_MOUNTINFO: &str = "proc/self/mountinfo";
+const _PATH_PROC_SWAPS: &str = "proc/swaps";
+
+/// Where each block device is mounted, keyed by device number.
+#[derive(Debug, Default)]
+pub(crate) struct MountPoints(HashMap<(u32, u32), Vec<String>>);
+
+impl MountPoints {
+    /// Reads the mount table and the active swap areas below `root`.
+    pub(crate) fn read(root: &Path) -> Self {
+        let mut mounts = Self::default();
+
+        if let Ok(content) = fs::read_to_string(root.join(_PATH_PROC_MOUNTINFO)) {
+            let (table, _) = parse_table(&content, TableFormat::MountInfo);
+            for mount in table {
+                if let Some(device) = mount.device {
+                    mounts.0.entry(device).or_default().push(mount.target);
+                }
+            }
+        }
+
+        // Swap areas are only listed by path, so this only works for the running system.
+        if let Ok(content) = fs::read_to_string(root.join(_PATH_PROC_SWAPS)) {
+            for line in content.lines().skip(1) {
+                let Some(path) = line.split_whitespace().next() else {
+                    continue;
+                };
+                let Ok(metadata) = fs::metadata(unescape(path)) else {
+                    continue;
+                };
+                if metadata.file_type().is_block_device() {
+                    let rdev = metadata.rdev();
+                    let device = (libc::major(rdev), libc::minor(rdev));
+                    mounts.0.entry(device).or_default().push("[SWAP]".into());
+                }
+            }
+        }
+
+        mounts
+    }
+
+    pub(crate) fn get(&self, device: (u32, u32)) -> &[String] {
+        self.0.get(&device).map(Vec::as_slice).unwrap_or_default()
+    }
+}
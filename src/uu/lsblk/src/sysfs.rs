@@ -0,0 +1,222 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+pub(crate) const _PATH_SYS_BLOCK: &str = "block";
+const _PATH_SYS_CLASS_BLOCK: &str = "class/block";
+const _PATH_SYS_DEV_BLOCK: &str = "dev/block";
+
+/// A block device, as described by its directory in sysfs.
+#[derive(Debug)]
+pub(crate) struct BlockDevice {
+    pub(crate) kname: String,
+    dir: PathBuf,
+    // Partitions don't have a queue of their own, it belongs to the whole disk.
+    disk: Option<(String, PathBuf)>,
+}
+
+impl BlockDevice {
+    fn from_dir(dir: PathBuf) -> Option<Self> {
+        let kname = dir.file_name()?.to_str()?.to_string();
+        let disk = if dir.join("partition").exists() {
+            let disk_dir = dir.parent()?.to_path_buf();
+            let disk_name = disk_dir.file_name()?.to_str()?.to_string();
+            Some((disk_name, disk_dir))
+        } else {
+            None
+        };
+
+        Some(Self { kname, dir, disk })
+    }
+
+    /// Looks up any block device, whole disk or partition, by its kernel name.
+    pub(crate) fn from_kname(sys: &Path, kname: &str) -> Option<Self> {
+        let dir = fs::canonicalize(sys.join(_PATH_SYS_CLASS_BLOCK).join(kname)).ok()?;
+        Self::from_dir(dir)
+    }
+
+    pub(crate) fn from_device_number(sys: &Path, major: u32, minor: u32) -> Option<Self> {
+        let link = sys
+            .join(_PATH_SYS_DEV_BLOCK)
+            .join(format!("{major}:{minor}"));
+        Self::from_dir(fs::canonicalize(link).ok()?)
+    }
+
+    /// All whole disks known to the kernel, in order of their device numbers.
+    pub(crate) fn whole_disks(sys: &Path) -> io::Result<Vec<Self>> {
+        let mut disks: Vec<Self> = fs::read_dir(sys.join(_PATH_SYS_BLOCK))?
+            .flatten()
+            .filter_map(|entry| Self::from_dir(fs::canonicalize(entry.path()).ok()?))
+            .collect();
+        disks.sort_by_key(|disk| disk.device_number());
+        Ok(disks)
+    }
+
+    pub(crate) fn partitions(&self) -> Vec<Self> {
+        if self.is_partition() {
+            return Vec::new();
+        }
+
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+
+        let mut partitions: Vec<(u32, Self)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let partition = Self::from_dir(entry.path())?;
+                let number = partition.attribute("partition")?.parse().ok()?;
+                Some((number, partition))
+            })
+            .collect();
+        partitions.sort_by_key(|(number, _)| *number);
+        partitions
+            .into_iter()
+            .map(|(_, partition)| partition)
+            .collect()
+    }
+
+    /// Devices built on top of this one, e.g. device-mapper or RAID devices.
+    pub(crate) fn holders(&self, sys: &Path) -> Vec<Self> {
+        let mut holders: Vec<Self> = self
+            .links("holders")
+            .iter()
+            .filter_map(|name| Self::from_kname(sys, name))
+            .collect();
+        holders.sort_by_key(|holder| holder.device_number());
+        holders
+    }
+
+    pub(crate) fn has_slaves(&self) -> bool {
+        !self.links("slaves").is_empty()
+    }
+
+    fn links(&self, dir: &str) -> Vec<String> {
+        fs::read_dir(self.dir.join(dir))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| entry.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn is_partition(&self) -> bool {
+        self.disk.is_some()
+    }
+
+    pub(crate) fn disk_kname(&self) -> Option<&str> {
+        self.disk.as_ref().map(|(name, _)| name.as_str())
+    }
+
+    /// Reads an attribute of this device, e.g. "size" or "dm/name".
+    pub(crate) fn attribute(&self, name: &str) -> Option<String> {
+        read_attribute(&self.dir.join(name))
+    }
+
+    /// Reads an attribute of the whole disk, which is the device itself unless it is a partition.
+    pub(crate) fn disk_attribute(&self, name: &str) -> Option<String> {
+        match &self.disk {
+            Some((_, dir)) => read_attribute(&dir.join(name)),
+            None => self.attribute(name),
+        }
+    }
+
+    pub(crate) fn queue_attribute(&self, name: &str) -> Option<String> {
+        self.disk_attribute(&format!("queue/{name}"))
+    }
+
+    pub(crate) fn device_number(&self) -> Option<(u32, u32)> {
+        let dev = self.attribute("dev")?;
+        let (major, minor) = dev.split_once(':')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    }
+
+    /// The size in bytes; sysfs always counts in 512-byte sectors.
+    pub(crate) fn size(&self) -> Option<u64> {
+        let sectors: u64 = self.attribute("size")?.parse().ok()?;
+        Some(sectors * 512)
+    }
+
+    pub(crate) fn dm_name(&self) -> Option<String> {
+        self.attribute("dm/name").filter(|name| !name.is_empty())
+    }
+
+    /// The name shown to users: device-mapper devices are better known by their mapping name.
+    pub(crate) fn name(&self) -> String {
+        self.dm_name().unwrap_or_else(|| self.kname.clone())
+    }
+
+    pub(crate) fn path(&self) -> String {
+        match self.dm_name() {
+            Some(name) => format!("/dev/mapper/{name}"),
+            None => format!("/dev/{}", self.kname),
+        }
+    }
+
+    pub(crate) fn device_type(&self) -> String {
+        if self.is_partition() {
+            return "part".to_string();
+        }
+
+        // The UUID prefix names the subsystem which set up the mapping, e.g. "LVM-..." or
+        // "CRYPT-LUKS2-...", and "part1-..." for partitions of a multipath device.
+        if let Some(uuid) = self.attribute("dm/uuid") {
+            let prefix = uuid.split_once('-').map(|(prefix, _)| prefix);
+            return match prefix {
+                Some(prefix) if prefix.starts_with("part") => "part".to_string(),
+                Some(prefix) if !prefix.is_empty() => prefix.to_lowercase(),
+                _ => "dm".to_string(),
+            };
+        }
+
+        if self.kname.starts_with("loop") {
+            return "loop".to_string();
+        }
+
+        if self.kname.starts_with("md") {
+            return self
+                .attribute("md/level")
+                .filter(|level| !level.is_empty())
+                .unwrap_or_else(|| "md".to_string());
+        }
+
+        match self.attribute("device/type").and_then(|t| t.parse().ok()) {
+            Some(scsi_type) => scsi_type_name(scsi_type).to_string(),
+            None => "disk".to_string(),
+        }
+    }
+}
+
+fn read_attribute(path: &Path) -> Option<String> {
+    let value = fs::read_to_string(path).ok()?;
+    Some(value.trim_end_matches('\n').to_string())
+}
+
+// Peripheral device types from the SCSI standard, as named by util-linux.
+fn scsi_type_name(scsi_type: u32) -> &'static str {
+    match scsi_type {
+        0x00 => "disk",
+        0x01 => "tape",
+        0x02 => "printer",
+        0x03 => "processor",
+        0x04 => "worm",
+        0x05 => "rom",
+        0x06 => "scanner",
+        0x07 => "mo-disk",
+        0x08 => "changer",
+        0x09 => "comm",
+        0x0c => "raid",
+        0x0d => "enclosure",
+        0x0e => "rbc",
+        0x11 => "osd",
+        0x7f => "no-lun",
+        _ => "disk",
+    }
+}
@@ -29,7 +29,7 @@ impl ColumnInfo {
     }
 }
 
-pub(crate) static COLUMN_INFOS: [ColumnInfo; 34] = [
+pub(crate) static COLUMN_INFOS: [ColumnInfo; 38] = [
     // Generic
     ColumnInfo::new(c"KEY", "Key", 0),
     ColumnInfo::new(c"ID", "ID", 0),
@@ -44,6 +44,8 @@ pub(crate) static COLUMN_INFOS: [ColumnInfo; 34] = [
     ColumnInfo::new(c"GID", "GID", SCOLS_FL_RIGHT),
     ColumnInfo::new(c"GROUP", "Group name", 0),
     ColumnInfo::new(c"CTIME", "Last change", SCOLS_FL_RIGHT),
+    ColumnInfo::new(c"MTIME", "Last modification", SCOLS_FL_RIGHT),
+    ColumnInfo::new(c"NAME", "Name", 0),
     // Message queues
     ColumnInfo::new(c"USEDBYTES", "Bytes used", SCOLS_FL_RIGHT),
     ColumnInfo::new(c"MSGS", "Messages", 0),
@@ -65,9 +67,12 @@ pub(crate) static COLUMN_INFOS: [ColumnInfo; 34] = [
     },
     ColumnInfo::new(c"CPID", "Creator PID", SCOLS_FL_RIGHT),
     ColumnInfo::new(c"LPID", "Last user PID", SCOLS_FL_RIGHT),
+    ColumnInfo::new(c"APIDS", "Attached PIDs", 0),
     // Semaphores
     ColumnInfo::new(c"NSEMS", "Semaphores", SCOLS_FL_RIGHT),
     ColumnInfo::new(c"OTIME", "Last operation", SCOLS_FL_RIGHT),
+    // POSIX semaphores
+    ColumnInfo::new(c"VALUE", "Value", SCOLS_FL_RIGHT),
     // Summary
     ColumnInfo::new(c"RESOURCE", "Resource", 0),
     ColumnInfo::new(c"DESCRIPTION", "Description", 0),
@@ -84,12 +89,22 @@ mod all {
 
     pub(crate) static QUEUES: [&str; 6] = ["USEDBYTES", "MSGS", "SEND", "RECV", "LSPID", "LRPID"];
 
-    pub(crate) static SHARED_MEMORY: [&str; 8] = [
-        "SIZE", "NATTCH", "STATUS", "ATTACH", "DETACH", "COMMAND", "CPID", "LPID",
+    pub(crate) static SHARED_MEMORY: [&str; 9] = [
+        "SIZE", "NATTCH", "STATUS", "ATTACH", "DETACH", "COMMAND", "CPID", "LPID", "APIDS",
     ];
 
     pub(crate) static SEMAPHORES: [&str; 2] = ["NSEMS", "OTIME"];
 
+    pub(crate) static POSIX_GENERIC: [&str; 8] = [
+        "NAME", "OWNER", "PERMS", "UID", "USER", "GID", "GROUP", "MTIME",
+    ];
+
+    pub(crate) static POSIX_QUEUES: [&str; 2] = ["USEDBYTES", "MSGS"];
+
+    pub(crate) static POSIX_SHARED_MEMORY: [&str; 1] = ["SIZE"];
+
+    pub(crate) static POSIX_SEMAPHORES: [&str; 1] = ["VALUE"];
+
     pub(crate) static SUMMARY: [&str; 5] = ["RESOURCE", "DESCRIPTION", "USED", "USE%", "LIMIT"];
 }
 
@@ -112,9 +127,24 @@ mod default {
 
     pub(crate) static SEMAPHORES: [&str; 5] = ["KEY", "ID", "PERMS", "OWNER", "NSEMS"];
 
+    pub(crate) static POSIX_QUEUES: [&str; 6] =
+        ["NAME", "PERMS", "OWNER", "USEDBYTES", "MSGS", "MTIME"];
+
+    pub(crate) static POSIX_SHARED_MEMORY: [&str; 5] = ["NAME", "PERMS", "OWNER", "SIZE", "MTIME"];
+
+    pub(crate) static POSIX_SEMAPHORES: [&str; 5] = ["NAME", "PERMS", "OWNER", "VALUE", "MTIME"];
+
     pub(crate) static GLOBAL: [&str; 5] = ["RESOURCE", "DESCRIPTION", "LIMIT", "USED", "USE%"];
 
     pub(crate) static CREATOR: [&str; 4] = ["CUID", "CGID", "UID", "GID"];
+
+    pub(crate) static POSIX_CREATOR: [&str; 2] = ["UID", "GID"];
+}
+
+fn is_posix(args: &clap::ArgMatches) -> bool {
+    args.get_flag(crate::options::POSIX_QUEUES)
+        || args.get_flag(crate::options::POSIX_SHMEMS)
+        || args.get_flag(crate::options::POSIX_SEMAPHORES)
 }
 
 #[derive(Debug, Clone)]
@@ -164,14 +194,21 @@ impl From<&'_ clap::ArgMatches> for OutputColumns {
             return Self::default();
         };
 
-        let allowed_column_names = if args.get_flag(crate::options::GLOBAL) {
-            &all::SUMMARY[..]
+        let (generic_column_names, allowed_column_names) = if args.get_flag(crate::options::GLOBAL)
+        {
+            (&all::GENERIC[..], &all::SUMMARY[..])
         } else if args.get_flag(crate::options::QUEUES) {
-            &all::QUEUES[..]
+            (&all::GENERIC[..], &all::QUEUES[..])
         } else if args.get_flag(crate::options::SHMEMS) {
-            &all::SHARED_MEMORY[..]
+            (&all::GENERIC[..], &all::SHARED_MEMORY[..])
         } else if args.get_flag(crate::options::SEMAPHORES) {
-            &all::SEMAPHORES[..]
+            (&all::GENERIC[..], &all::SEMAPHORES[..])
+        } else if args.get_flag(crate::options::POSIX_QUEUES) {
+            (&all::POSIX_GENERIC[..], &all::POSIX_QUEUES[..])
+        } else if args.get_flag(crate::options::POSIX_SHMEMS) {
+            (&all::POSIX_GENERIC[..], &all::POSIX_SHARED_MEMORY[..])
+        } else if args.get_flag(crate::options::POSIX_SEMAPHORES) {
+            (&all::POSIX_GENERIC[..], &all::POSIX_SEMAPHORES[..])
         } else {
             unreachable!()
         };
@@ -179,7 +216,7 @@ impl From<&'_ clap::ArgMatches> for OutputColumns {
         let (list, not_applicable): (Vec<&ColumnInfo>, Vec<&ColumnInfo>) =
             columns.list.iter().partition(|&&column| {
                 let id = column.id.to_str().unwrap();
-                all::GENERIC.contains(&id) || allowed_column_names.contains(&id)
+                generic_column_names.contains(&id) || allowed_column_names.contains(&id)
             });
 
         if !not_applicable.is_empty() {
@@ -205,7 +242,23 @@ impl From<&'_ clap::ArgMatches> for OutputColumns {
 pub(crate) fn all_defaults(
     args: &clap::ArgMatches,
 ) -> Result<Vec<&'static ColumnInfo>, LsIpcError> {
-    let mut iter: Box<dyn Iterator<Item = &str>> = Box::new(all::GENERIC.into_iter());
+    let mut iter: Box<dyn Iterator<Item = &str>> = if is_posix(args) {
+        Box::new(all::POSIX_GENERIC.into_iter())
+    } else {
+        Box::new(all::GENERIC.into_iter())
+    };
+
+    if args.get_flag(crate::options::POSIX_QUEUES) {
+        iter = Box::new(iter.chain(all::POSIX_QUEUES));
+    }
+
+    if args.get_flag(crate::options::POSIX_SHMEMS) {
+        iter = Box::new(iter.chain(all::POSIX_SHARED_MEMORY));
+    }
+
+    if args.get_flag(crate::options::POSIX_SEMAPHORES) {
+        iter = Box::new(iter.chain(all::POSIX_SEMAPHORES));
+    }
 
     if args.get_flag(crate::options::QUEUES) {
         iter = Box::new(iter.chain(all::QUEUES));
@@ -245,15 +298,35 @@ pub(crate) fn filter_defaults(
         columns.extend(default::SEMAPHORES);
     }
 
+    if args.get_flag(crate::options::POSIX_QUEUES) {
+        columns.extend(default::POSIX_QUEUES);
+    }
+
+    if args.get_flag(crate::options::POSIX_SHMEMS) {
+        columns.extend(default::POSIX_SHARED_MEMORY);
+    }
+
+    if args.get_flag(crate::options::POSIX_SEMAPHORES) {
+        columns.extend(default::POSIX_SEMAPHORES);
+    }
+
     if args.get_flag(crate::options::GLOBAL) {
         columns.extend(default::GLOBAL);
     }
 
+    let posix = is_posix(args);
+
     if args.get_flag(crate::options::CREATOR) {
-        columns.extend(default::CREATOR);
+        if posix {
+            columns.extend(default::POSIX_CREATOR);
+        } else {
+            columns.extend(default::CREATOR);
+        }
     }
 
-    if args.get_flag(crate::options::TIME) {
+    // POSIX resources only record the time of their last modification, which is
+    // already part of their default columns.
+    if args.get_flag(crate::options::TIME) && !posix {
         if args.get_flag(crate::options::QUEUES)
             || (!args.get_flag(crate::options::SHMEMS)
                 && !args.get_flag(crate::options::SEMAPHORES))
@@ -332,8 +332,11 @@ pub(crate) fn new_table(
     Ok(table)
 }
 
-pub(crate) fn print_pretty_table(table: &Table) -> Result<(), LsIpcError> {
-    let line = table.line(0)?;
+pub(crate) fn print_pretty_table(table: &Table, columns: &[&ColumnInfo]) -> Result<(), LsIpcError> {
+    // Nothing was found for the requested resource.
+    let Ok(line) = table.line(0) else {
+        return Ok(());
+    };
 
     for (cell_index, _column) in table.column_iter(IterDirection::Forward)?.enumerate() {
         if let Some(dstr) = line
@@ -343,13 +346,17 @@ pub(crate) fn print_pretty_table(table: &Table) -> Result<(), LsIpcError> {
             .map(CStr::to_bytes)
             .and_then(|b| std::str::from_utf8(b).ok())
         {
-            let title = crate::column::COLUMN_INFOS[cell_index].title;
+            let title = columns[cell_index].title;
             println!("{title}:{}{dstr: <36}", " ".repeat(35 - title.len()));
         }
     }
 
     if let Some(sub_table) = NonNull::new(line.user_data().cast()).map(TableRef::from) {
-        println!("Elements:");
+        let heading = match sub_table.name().map(CStr::to_bytes) {
+            Some(b"attachments") => "Attachments",
+            _ => "Elements",
+        };
+        println!("{heading}:");
         println!();
         sub_table.print()?;
     }
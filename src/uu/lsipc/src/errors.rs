@@ -13,22 +13,26 @@ use uucore::error::UError;
 pub enum LsIpcError {
     InvalidColumnName(String),
     InvalidColumnSequence(String),
+    InvalidKey(String),
+    InvalidMode(String),
+    InvalidSize(String),
     InvalidTimeFormat(String),
+    LimitExceeded(&'static str, u64),
     IO0(String, std::io::Error),
     IO1(String, PathBuf, std::io::Error),
 }
 
 impl LsIpcError {
-    pub(crate) fn io0(message: impl Into<String>, error: impl Into<std::io::Error>) -> Self {
+    pub fn io0(message: impl Into<String>, error: impl Into<std::io::Error>) -> Self {
         Self::IO0(message.into(), error.into())
     }
 
-    pub(crate) fn last_io0(message: impl Into<String>) -> Self {
+    pub fn last_io0(message: impl Into<String>) -> Self {
         let err = std::io::Error::last_os_error();
         Self::IO0(message.into(), err)
     }
 
-    pub(crate) fn io1(
+    pub fn io1(
         message: impl Into<String>,
         path: impl Into<PathBuf>,
         error: impl Into<std::io::Error>,
@@ -56,7 +60,16 @@ impl fmt::Display for LsIpcError {
             Self::IO1(message, path, err) => write!(f, "{message} '{}': {err}", path.display()),
             Self::InvalidColumnName(name) => write!(f, "invalid column name: {name}"),
             Self::InvalidColumnSequence(seq) => write!(f, "invalid column sequence: {seq}"),
+            Self::InvalidKey(key) => write!(f, "invalid key: {key}"),
+            Self::InvalidMode(mode) => write!(f, "invalid mode: {mode}"),
+            Self::InvalidSize(size) => write!(f, "invalid size: {size}"),
             Self::InvalidTimeFormat(mode) => write!(f, "invalid time format: {mode}"),
+            Self::LimitExceeded(resource, limit) => {
+                write!(
+                    f,
+                    "the system-wide {resource} limit ({limit}) would be exceeded"
+                )
+            }
         }
     }
 }
@@ -12,11 +12,17 @@ mod column;
 mod display;
 mod errors;
 #[cfg(target_os = "linux")]
-mod message_queue;
+pub mod message_queue;
 #[cfg(target_os = "linux")]
-mod semaphore;
+mod posix_message_queue;
 #[cfg(target_os = "linux")]
-mod shared_memory;
+mod posix_semaphore;
+#[cfg(target_os = "linux")]
+mod posix_shared_memory;
+#[cfg(target_os = "linux")]
+pub mod semaphore;
+#[cfg(target_os = "linux")]
+pub mod shared_memory;
 #[cfg(target_os = "linux")]
 mod smartcols;
 #[cfg(target_os = "linux")]
@@ -31,10 +37,13 @@ use uucore::{error::UResult, format_usage, help_about, help_usage};
 
 #[cfg(target_os = "linux")]
 use crate::column::{ColumnInfo, OutputColumns};
-use crate::errors::LsIpcError;
 #[cfg(target_os = "linux")]
 use crate::smartcols::TableOperations;
 
+pub use crate::errors::LsIpcError;
+#[cfg(target_os = "linux")]
+pub use crate::utils::parse_key;
+
 mod options {
     pub static BYTES: &str = "bytes";
     pub static CREATOR: &str = "creator";
@@ -43,11 +52,15 @@ mod options {
     pub static ID: &str = "id";
     pub static JSON: &str = "json";
     pub static LIST: &str = "list";
+    pub static NAME: &str = "name";
     pub static NEW_LINE: &str = "newline";
     pub static NO_HEADINGS: &str = "noheadings";
     pub static NO_TRUNCATE: &str = "notruncate";
     pub static NUMERIC_PERMS: &str = "numeric-perms";
     pub static OUTPUT: &str = "output";
+    pub static POSIX_QUEUES: &str = "posix-mqueues";
+    pub static POSIX_SEMAPHORES: &str = "posix-semaphores";
+    pub static POSIX_SHMEMS: &str = "posix-shmems";
     pub static QUEUES: &str = "queues";
     pub static RAW: &str = "raw";
     pub static SEMAPHORES: &str = "semaphores";
@@ -107,7 +120,7 @@ pub fn uu_app() -> Command {
                 .value_name(options::ID)
                 .value_parser(value_parser!(c_uint))
                 .action(ArgAction::Set)
-                .conflicts_with(options::GLOBAL)
+                .conflicts_with_all([options::GLOBAL, "posix-ipc-kind"])
                 .requires("ipc-kind")
                 .help("print details on resource identified by id"),
         )
@@ -125,6 +138,16 @@ pub fn uu_app() -> Command {
                 .action(ArgAction::SetTrue)
                 .help("force list output format"),
         )
+        .arg(
+            Arg::new(options::NAME)
+                .short('N')
+                .long(options::NAME)
+                .value_name(options::NAME)
+                .action(ArgAction::Set)
+                .conflicts_with(options::GLOBAL)
+                .requires("posix-ipc-kind")
+                .help("print details on POSIX resource identified by name"),
+        )
         .arg(
             Arg::new(options::NEW_LINE)
                 .short('n')
@@ -160,6 +183,27 @@ pub fn uu_app() -> Command {
                 .action(ArgAction::Set)
                 .help("define the columns to output"),
         )
+        .arg(
+            Arg::new(options::POSIX_QUEUES)
+                .short('Q')
+                .long(options::POSIX_QUEUES)
+                .action(ArgAction::SetTrue)
+                .help("POSIX message queues"),
+        )
+        .arg(
+            Arg::new(options::POSIX_SEMAPHORES)
+                .short('S')
+                .long(options::POSIX_SEMAPHORES)
+                .action(ArgAction::SetTrue)
+                .help("POSIX semaphores"),
+        )
+        .arg(
+            Arg::new(options::POSIX_SHMEMS)
+                .short('M')
+                .long(options::POSIX_SHMEMS)
+                .action(ArgAction::SetTrue)
+                .help("POSIX shared memory segments"),
+        )
         .arg(
             Arg::new(options::QUEUES)
                 .short('q')
@@ -213,7 +257,23 @@ pub fn uu_app() -> Command {
         )
         .group(
             ArgGroup::new("ipc-kind")
-                .args([options::SHMEMS, options::QUEUES, options::SEMAPHORES])
+                .args([
+                    options::SHMEMS,
+                    options::QUEUES,
+                    options::SEMAPHORES,
+                    options::POSIX_SHMEMS,
+                    options::POSIX_QUEUES,
+                    options::POSIX_SEMAPHORES,
+                ])
+                .multiple(false),
+        )
+        .group(
+            ArgGroup::new("posix-ipc-kind")
+                .args([
+                    options::POSIX_SHMEMS,
+                    options::POSIX_QUEUES,
+                    options::POSIX_SEMAPHORES,
+                ])
                 .multiple(false),
         )
         .group(
@@ -316,22 +376,48 @@ fn lsipc(
         Option<c_uint>,
     ) -> Result<(), LsIpcError>;
 
-    let queues = args.get_flag(options::QUEUES);
-    let shmems = args.get_flag(options::SHMEMS);
-    let semaphores = args.get_flag(options::SEMAPHORES);
-
-    let config_list: [(bool, GlobalProc, DescribeProc); 3] = [
-        (queues, message_queue::print_global, message_queue::describe),
-        (shmems, shared_memory::print_global, shared_memory::describe),
-        (semaphores, semaphore::print_global, semaphore::describe),
+    let config_list: [(&str, Option<GlobalProc>, DescribeProc); 6] = [
+        (
+            options::QUEUES,
+            Some(message_queue::print_global),
+            message_queue::describe,
+        ),
+        (
+            options::SHMEMS,
+            Some(shared_memory::print_global),
+            shared_memory::describe,
+        ),
+        (
+            options::SEMAPHORES,
+            Some(semaphore::print_global),
+            semaphore::describe,
+        ),
+        (
+            options::POSIX_QUEUES,
+            Some(posix_message_queue::print_global),
+            posix_message_queue::describe,
+        ),
+        (
+            options::POSIX_SHMEMS,
+            Some(posix_shared_memory::print_global),
+            posix_shared_memory::describe,
+        ),
+        // POSIX semaphores have no system-wide limits.
+        (options::POSIX_SEMAPHORES, None, posix_semaphore::describe),
     ];
 
+    let any_kind = config_list
+        .iter()
+        .any(|&(option, _, _)| args.get_flag(option));
+
     let id = args.get_one::<c_uint>(options::ID).copied();
 
-    for (flag, global, describe) in config_list {
-        if flag || (!queues && !shmems && !semaphores) {
+    for (option, global, describe) in config_list {
+        if args.get_flag(option) || !any_kind {
             if print_global {
-                global(args, &columns, &mut table)?;
+                if let Some(global) = global {
+                    global(args, &columns, &mut table)?;
+                }
             } else {
                 describe(args, time_format, &columns, &mut table, id)?;
             }
@@ -339,7 +425,7 @@ fn lsipc(
     }
 
     if output_mode == OutputMode::Pretty {
-        display::print_pretty_table(&table)?;
+        display::print_pretty_table(&table, &columns)?;
     } else {
         table.print()?;
     }
@@ -416,7 +502,7 @@ impl From<&'_ ArgMatches> for OutputMode {
             Self::NewLine
         } else if args.get_flag(options::RAW) {
             Self::Raw
-        } else if args.contains_id(options::ID) {
+        } else if args.contains_id(options::ID) || args.contains_id(options::NAME) {
             Self::Pretty
         } else {
             Self::None
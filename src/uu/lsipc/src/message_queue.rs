@@ -38,14 +38,15 @@ fn msgctl(id: c_int, cmd: c_int) -> Result<(c_int, libc::msqid_ds), LsIpcError>
     }
 }
 
-struct Limits {
-    msg_mni: u64,
-    msg_mnb: u64,
-    msg_max: u64,
+/// System-wide limits, as reported by `/proc/sys/kernel` or `IPC_INFO`.
+pub struct Limits {
+    pub msg_mni: u64,
+    pub msg_mnb: u64,
+    pub msg_max: u64,
 }
 
 impl Limits {
-    fn new() -> Result<Self, LsIpcError> {
+    pub fn new() -> Result<Self, LsIpcError> {
         Self::from_proc().or_else(|_| Self::from_syscall())
     }
 
@@ -291,6 +292,32 @@ impl SysVIpc {
     }
 }
 
+/// Fails if creating one more message queue would exceed the `MSGMNI` limit.
+pub fn check_create_limits() -> Result<(), LsIpcError> {
+    let limits = Limits::new()?;
+    let count = SysVIpc::new(None)?.0.len();
+
+    if u64::try_from(count).unwrap_or(u64::MAX) >= limits.msg_mni {
+        return Err(LsIpcError::LimitExceeded("MSGMNI", limits.msg_mni));
+    }
+    Ok(())
+}
+
+/// Returns the key and identifier of every message queue in the system.
+pub fn list_keys_and_ids() -> Result<Vec<(libc::key_t, c_int)>, LsIpcError> {
+    let sys_v_ipc = SysVIpc::new(None)?;
+    Ok(sys_v_ipc
+        .0
+        .iter()
+        .map(|entry| (entry.key, entry.msqid))
+        .collect())
+}
+
+/// Removes the message queue identified by `id`.
+pub fn remove(id: c_int) -> Result<(), LsIpcError> {
+    msgctl(id, libc::IPC_RMID).map(|_| ())
+}
+
 pub(crate) fn print_global(
     args: &clap::ArgMatches,
     columns: &[&ColumnInfo],
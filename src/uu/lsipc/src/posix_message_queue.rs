@@ -0,0 +1,177 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::borrow::Cow;
+use std::ffi::{CString, c_long, c_uint};
+use std::path::Path;
+
+use crate::column::ColumnInfo;
+use crate::display::{
+    describe_integer, describe_owner, describe_permissions, describe_size, format_time,
+    new_global_line,
+};
+use crate::errors::LsIpcError;
+use crate::smartcols::{Table, TableOperations};
+use crate::utils::{
+    GroupDbRecordRef, PosixIpcEntry, UserDbRecordRef, posix_ipc_entries, read_value, time_of_day,
+};
+
+static _PATH_DEV_MQUEUE: &str = "/dev/mqueue";
+static _PATH_PROC_MQUEUE_QUEUES_MAX: &str = "/proc/sys/fs/mqueue/queues_max";
+static _PATH_PROC_MQUEUE_MSG_MAX: &str = "/proc/sys/fs/mqueue/msg_max";
+static _PATH_PROC_MQUEUE_MSGSIZE_MAX: &str = "/proc/sys/fs/mqueue/msgsize_max";
+
+struct Limits {
+    queues_max: u64,
+    msg_max: u64,
+    msgsize_max: u64,
+}
+
+impl Limits {
+    fn new() -> Result<Self, LsIpcError> {
+        Ok(Self {
+            queues_max: read_value::<u64>(_PATH_PROC_MQUEUE_QUEUES_MAX)?,
+            msg_max: read_value::<u64>(_PATH_PROC_MQUEUE_MSG_MAX)?,
+            msgsize_max: read_value::<u64>(_PATH_PROC_MQUEUE_MSGSIZE_MAX)?,
+        })
+    }
+}
+
+struct PosixIpcQueue {
+    entry: PosixIpcEntry,
+    qsize: Option<u64>,
+    msgs: Option<c_long>,
+}
+
+impl PosixIpcQueue {
+    fn new(entry: PosixIpcEntry) -> Self {
+        Self {
+            qsize: queue_size(&entry.path),
+            msgs: current_messages(&entry.name),
+            entry,
+        }
+    }
+}
+
+// The pseudo-file contains a single line such as:
+// "QSIZE:129        NOTIFY:0     SIGNO:0     NOTIFY_PID:0".
+fn queue_size(path: &Path) -> Option<u64> {
+    std::fs::read_to_string(path)
+        .ok()?
+        .split_ascii_whitespace()
+        .find_map(|field| field.strip_prefix("QSIZE:"))
+        .and_then(|s| s.parse().ok())
+}
+
+fn current_messages(name: &str) -> Option<c_long> {
+    let name = CString::new(name).ok()?;
+
+    let mqd = unsafe { libc::mq_open(name.as_ptr(), libc::O_RDONLY | libc::O_NONBLOCK) };
+    if mqd == -1 {
+        return None;
+    }
+
+    let mut attr: libc::mq_attr = unsafe { std::mem::zeroed() };
+    let r = unsafe { libc::mq_getattr(mqd, &mut attr) };
+    unsafe { libc::mq_close(mqd) };
+
+    (r == 0).then_some(attr.mq_curmsgs)
+}
+
+fn queues(name: Option<&str>) -> Result<Vec<PosixIpcQueue>, LsIpcError> {
+    let entries = posix_ipc_entries(_PATH_DEV_MQUEUE, |file_name| Some(file_name), name)?;
+    Ok(entries.into_iter().map(PosixIpcQueue::new).collect())
+}
+
+pub(crate) fn print_global(
+    args: &clap::ArgMatches,
+    columns: &[&ColumnInfo],
+    table: &mut Table,
+) -> Result<(), LsIpcError> {
+    let limits = Limits::new()?;
+    let count = queues(None)?.len();
+    let in_bytes = args.get_flag(crate::options::BYTES);
+
+    let lines_config = [
+        (
+            c"MQUMNI",
+            c"Number of POSIX message queues",
+            Some(u64::try_from(count).unwrap_or(u64::MAX)),
+            limits.queues_max,
+            true,
+        ),
+        (
+            c"MQUMAX",
+            c"Max size of POSIX message (bytes)",
+            None,
+            limits.msgsize_max,
+            in_bytes,
+        ),
+        (
+            c"MQUMSG",
+            c"Max number of messages in POSIX queue",
+            None,
+            limits.msg_max,
+            true,
+        ),
+    ];
+
+    lines_config
+        .into_iter()
+        .try_for_each(move |(resource, description, used, limit, in_bytes)| {
+            new_global_line(columns, table, resource, description, used, limit, in_bytes)
+        })
+}
+
+pub(crate) fn describe(
+    args: &clap::ArgMatches,
+    time_format: crate::TimeFormat,
+    columns: &[&ColumnInfo],
+    table: &mut Table,
+    _id: Option<c_uint>,
+) -> Result<(), LsIpcError> {
+    let now = time_of_day()?;
+    let name = args.get_one::<String>(crate::options::NAME);
+    let queues = queues(name.map(String::as_str))?;
+
+    if let Some(name) = name.filter(|_| queues.len() != 1) {
+        eprintln!("name {name} not found");
+        return Ok(());
+    }
+
+    table.set_name(c"posix-messages")?;
+
+    let mut users = UserDbRecordRef::default();
+    let mut groups = GroupDbRecordRef::default();
+
+    for queue in queues {
+        let entry = &queue.entry;
+        let mut line = table.new_line(None)?;
+
+        for (cell_index, &column) in columns.iter().enumerate() {
+            let data_str = match column.id.to_bytes() {
+                b"NAME" => Some(Cow::Owned(CString::new(entry.name.as_str()).unwrap())),
+                b"UID" => describe_integer(entry.uid),
+                b"GID" => describe_integer(entry.gid),
+                b"MTIME" => format_time(time_format, &now, entry.mtime)?.map(Cow::Owned),
+                b"MSGS" => queue.msgs.and_then(describe_integer),
+                b"USEDBYTES" => queue
+                    .qsize
+                    .and_then(|qsize| describe_size(qsize, args.get_flag(crate::options::BYTES))),
+                b"OWNER" => describe_owner(&mut users, entry.uid),
+                b"USER" => users.for_id(entry.uid).name().map(Cow::Borrowed),
+                b"GROUP" => groups.for_id(entry.gid).name().map(Cow::Borrowed),
+                b"PERMS" => describe_permissions(args, entry.perms & 0o777),
+
+                _ => continue,
+            };
+
+            if let Some(data_str) = data_str {
+                line.set_data(cell_index, &data_str)?;
+            }
+        }
+    }
+    Ok(())
+}
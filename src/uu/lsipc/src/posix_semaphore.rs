@@ -0,0 +1,87 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::borrow::Cow;
+use std::ffi::{CString, c_int, c_uint};
+
+use crate::column::ColumnInfo;
+use crate::display::{describe_integer, describe_owner, describe_permissions, format_time};
+use crate::errors::LsIpcError;
+use crate::posix_shared_memory::_PATH_DEV_SHM;
+use crate::smartcols::{Table, TableOperations};
+use crate::utils::{
+    GroupDbRecordRef, PosixIpcEntry, UserDbRecordRef, posix_ipc_entries, time_of_day,
+};
+
+// Named semaphores are stored in "/dev/shm/sem.NAME".
+fn semaphore_name(file_name: &str) -> Option<&str> {
+    file_name.strip_prefix("sem.")
+}
+
+fn semaphores(name: Option<&str>) -> Result<Vec<PosixIpcEntry>, LsIpcError> {
+    posix_ipc_entries(_PATH_DEV_SHM, semaphore_name, name)
+}
+
+fn semaphore_value(name: &str) -> Option<c_int> {
+    let name = CString::new(name).ok()?;
+
+    let sem = unsafe { libc::sem_open(name.as_ptr(), 0) };
+    if sem == libc::SEM_FAILED {
+        return None;
+    }
+
+    let mut value: c_int = 0;
+    let r = unsafe { libc::sem_getvalue(sem, &mut value) };
+    unsafe { libc::sem_close(sem) };
+
+    (r == 0).then_some(value)
+}
+
+pub(crate) fn describe(
+    args: &clap::ArgMatches,
+    time_format: crate::TimeFormat,
+    columns: &[&ColumnInfo],
+    table: &mut Table,
+    _id: Option<c_uint>,
+) -> Result<(), LsIpcError> {
+    let now = time_of_day()?;
+    let name = args.get_one::<String>(crate::options::NAME);
+    let semaphores = semaphores(name.map(String::as_str))?;
+
+    if let Some(name) = name.filter(|_| semaphores.len() != 1) {
+        eprintln!("name {name} not found");
+        return Ok(());
+    }
+
+    table.set_name(c"posix-semaphores")?;
+
+    let mut users = UserDbRecordRef::default();
+    let mut groups = GroupDbRecordRef::default();
+
+    for entry in semaphores {
+        let mut line = table.new_line(None)?;
+
+        for (cell_index, &column) in columns.iter().enumerate() {
+            let data_str = match column.id.to_bytes() {
+                b"NAME" => Some(Cow::Owned(CString::new(entry.name.as_str()).unwrap())),
+                b"UID" => describe_integer(entry.uid),
+                b"GID" => describe_integer(entry.gid),
+                b"MTIME" => format_time(time_format, &now, entry.mtime)?.map(Cow::Owned),
+                b"VALUE" => semaphore_value(&entry.name).and_then(describe_integer),
+                b"OWNER" => describe_owner(&mut users, entry.uid),
+                b"USER" => users.for_id(entry.uid).name().map(Cow::Borrowed),
+                b"GROUP" => groups.for_id(entry.gid).name().map(Cow::Borrowed),
+                b"PERMS" => describe_permissions(args, entry.perms & 0o777),
+
+                _ => continue,
+            };
+
+            if let Some(data_str) = data_str {
+                line.set_data(cell_index, &data_str)?;
+            }
+        }
+    }
+    Ok(())
+}
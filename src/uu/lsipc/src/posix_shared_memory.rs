@@ -0,0 +1,110 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::borrow::Cow;
+use std::ffi::{CString, c_uint};
+
+use crate::column::ColumnInfo;
+use crate::display::{
+    describe_integer, describe_owner, describe_permissions, describe_size, format_time,
+    new_global_line,
+};
+use crate::errors::LsIpcError;
+use crate::smartcols::{Table, TableOperations};
+use crate::utils::{
+    GroupDbRecordRef, PosixIpcEntry, UserDbRecordRef, posix_ipc_entries, time_of_day,
+};
+
+pub(crate) static _PATH_DEV_SHM: &str = "/dev/shm";
+
+// Named semaphores share the directory, under a "sem." prefix.
+fn shared_memory_name(file_name: &str) -> Option<&str> {
+    (!file_name.starts_with("sem.")).then_some(file_name)
+}
+
+fn shared_memory_objects(name: Option<&str>) -> Result<Vec<PosixIpcEntry>, LsIpcError> {
+    posix_ipc_entries(_PATH_DEV_SHM, shared_memory_name, name)
+}
+
+// POSIX shared memory lives in a tmpfs, so the size of that file system is the
+// effective limit on the total size of all objects.
+fn total_size_limit() -> Result<u64, LsIpcError> {
+    let path = CString::new(_PATH_DEV_SHM).unwrap();
+
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } == -1 {
+        let err = std::io::Error::last_os_error();
+        return Err(LsIpcError::io1("statvfs", _PATH_DEV_SHM, err));
+    }
+
+    Ok(stat.f_blocks.saturating_mul(stat.f_frsize))
+}
+
+pub(crate) fn print_global(
+    args: &clap::ArgMatches,
+    columns: &[&ColumnInfo],
+    table: &mut Table,
+) -> Result<(), LsIpcError> {
+    let objects = shared_memory_objects(None)?;
+    let total_size = objects.iter().map(|object| object.size).sum::<u64>();
+    let in_bytes = args.get_flag(crate::options::BYTES);
+
+    new_global_line(
+        columns,
+        table,
+        c"PSHMALL",
+        c"Total size of POSIX shared memory (bytes)",
+        Some(total_size),
+        total_size_limit()?,
+        in_bytes,
+    )
+}
+
+pub(crate) fn describe(
+    args: &clap::ArgMatches,
+    time_format: crate::TimeFormat,
+    columns: &[&ColumnInfo],
+    table: &mut Table,
+    _id: Option<c_uint>,
+) -> Result<(), LsIpcError> {
+    let now = time_of_day()?;
+    let name = args.get_one::<String>(crate::options::NAME);
+    let objects = shared_memory_objects(name.map(String::as_str))?;
+
+    if let Some(name) = name.filter(|_| objects.len() != 1) {
+        eprintln!("name {name} not found");
+        return Ok(());
+    }
+
+    table.set_name(c"posix-sharedmemory")?;
+
+    let mut users = UserDbRecordRef::default();
+    let mut groups = GroupDbRecordRef::default();
+
+    for entry in objects {
+        let mut line = table.new_line(None)?;
+
+        for (cell_index, &column) in columns.iter().enumerate() {
+            let data_str = match column.id.to_bytes() {
+                b"NAME" => Some(Cow::Owned(CString::new(entry.name.as_str()).unwrap())),
+                b"UID" => describe_integer(entry.uid),
+                b"GID" => describe_integer(entry.gid),
+                b"MTIME" => format_time(time_format, &now, entry.mtime)?.map(Cow::Owned),
+                b"SIZE" => describe_size(entry.size, args.get_flag(crate::options::BYTES)),
+                b"OWNER" => describe_owner(&mut users, entry.uid),
+                b"USER" => users.for_id(entry.uid).name().map(Cow::Borrowed),
+                b"GROUP" => groups.for_id(entry.gid).name().map(Cow::Borrowed),
+                b"PERMS" => describe_permissions(args, entry.perms & 0o777),
+
+                _ => continue,
+            };
+
+            if let Some(data_str) = data_str {
+                line.set_data(cell_index, &data_str)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -38,16 +38,17 @@ fn semctl(id: c_int, index: c_int, cmd: c_int) -> Result<(c_int, libc::semid_ds)
     }
 }
 
-struct Limits {
-    sem_vmx: u64,
-    sem_mni: u64,
-    sem_msl: u64,
-    sem_mns: u64,
-    sem_opm: u64,
+/// System-wide limits, as reported by `/proc/sys/kernel` or `IPC_INFO`.
+pub struct Limits {
+    pub sem_vmx: u64,
+    pub sem_mni: u64,
+    pub sem_msl: u64,
+    pub sem_mns: u64,
+    pub sem_opm: u64,
 }
 
 impl Limits {
-    fn new() -> Result<Self, LsIpcError> {
+    pub fn new() -> Result<Self, LsIpcError> {
         if let Ok(file) = File::open(_PATH_PROC_IPC_SEM).map(BufReader::new) {
             Self::from_proc(file)
         } else {
@@ -356,6 +357,47 @@ impl SysVIpc {
     }
 }
 
+/// Fails if creating a set of `nsems` semaphores would exceed the `SEMMSL`, `SEMMNI`
+/// or `SEMMNS` limits.
+pub fn check_create_limits(nsems: u64) -> Result<(), LsIpcError> {
+    let limits = Limits::new()?;
+
+    if nsems > limits.sem_msl {
+        return Err(LsIpcError::LimitExceeded("SEMMSL", limits.sem_msl));
+    }
+
+    let sys_v_ipc = SysVIpc::new(None)?;
+
+    if u64::try_from(sys_v_ipc.0.len()).unwrap_or(u64::MAX) >= limits.sem_mni {
+        return Err(LsIpcError::LimitExceeded("SEMMNI", limits.sem_mni));
+    }
+
+    let total_nsems: usize = sys_v_ipc.0.iter().map(|sem| sem.elements.len()).sum();
+    if u64::try_from(total_nsems)
+        .unwrap_or(u64::MAX)
+        .saturating_add(nsems)
+        > limits.sem_mns
+    {
+        return Err(LsIpcError::LimitExceeded("SEMMNS", limits.sem_mns));
+    }
+    Ok(())
+}
+
+/// Returns the key and identifier of every semaphore set in the system.
+pub fn list_keys_and_ids() -> Result<Vec<(libc::key_t, c_int)>, LsIpcError> {
+    let sys_v_ipc = SysVIpc::new(None)?;
+    Ok(sys_v_ipc
+        .0
+        .iter()
+        .map(|entry| (entry.key, entry.semid))
+        .collect())
+}
+
+/// Removes the semaphore set identified by `id`.
+pub fn remove(id: c_int) -> Result<(), LsIpcError> {
+    semctl(id, 0, libc::IPC_RMID).map(|_| ())
+}
+
 pub(crate) fn print_global(
     _args: &clap::ArgMatches,
     columns: &[&ColumnInfo],
@@ -4,7 +4,8 @@
 // file that was distributed with this source code.
 
 use std::borrow::Cow;
-use std::ffi::{CString, c_int, c_uint, c_ulong};
+use std::collections::BTreeMap;
+use std::ffi::{CStr, CString, c_int, c_uint, c_ulong};
 use std::fs::File;
 use std::io;
 use std::io::{BufRead, BufReader};
@@ -16,7 +17,7 @@ use crate::display::{
     format_time, new_global_line,
 };
 use crate::errors::LsIpcError;
-use crate::smartcols::{Table, TableOperations};
+use crate::smartcols::{LineRef, Table, TableOperations};
 use crate::utils::{
     GroupDbRecordRef, UserDbRecordRef, get_page_size, pid_command_line, read_value, time_of_day,
 };
@@ -26,6 +27,7 @@ const SHM_INFO: c_int = 14;
 const SHM_DEST: c_uint = 0o1000;
 const SHM_LOCKED: c_uint = 0o2000;
 
+static _PATH_PROC: &str = "/proc";
 static _PATH_PROC_SYSV_SHM: &str = "/proc/sysvipc/shm";
 static _PATH_PROC_IPC_SHMMAX: &str = "/proc/sys/kernel/shmmax";
 static _PATH_PROC_IPC_SHMMNI: &str = "/proc/sys/kernel/shmmni";
@@ -57,15 +59,16 @@ fn shmctl(id: c_int, cmd: c_int) -> Result<(c_int, libc::shmid_ds), LsIpcError>
     }
 }
 
-struct Limits {
-    shm_max: u64,
-    shm_min: u64,
-    shm_mni: u64,
-    shm_all: u64,
+/// System-wide limits, as reported by `/proc/sys/kernel` or `IPC_INFO`.
+pub struct Limits {
+    pub shm_max: u64,
+    pub shm_min: u64,
+    pub shm_mni: u64,
+    pub shm_all: u64,
 }
 
 impl Limits {
-    fn new() -> Result<Self, LsIpcError> {
+    pub fn new() -> Result<Self, LsIpcError> {
         Self::from_proc().or_else(|_| Self::from_syscall())
     }
 
@@ -333,6 +336,109 @@ impl SysVIpc {
     }
 }
 
+// Processes attached to each segment, indexed by segment id.
+struct Attachments(BTreeMap<c_int, Vec<libc::pid_t>>);
+
+impl Attachments {
+    // Attached segments show up in "/proc/PID/maps" as "/SYSV<key>" mappings,
+    // whose inode is the segment id. Matching on both tells apart segments
+    // sharing a key, such as those created with `IPC_PRIVATE`.
+    fn new(sys_v_ipc: &SysVIpc) -> Result<Self, LsIpcError> {
+        let mut result: BTreeMap<c_int, Vec<libc::pid_t>> = BTreeMap::new();
+
+        let keys: BTreeMap<c_int, String> = sys_v_ipc
+            .0
+            .iter()
+            .map(|entry| (entry.shmid, format!("/SYSV{:08x}", entry.key)))
+            .collect();
+
+        if keys.is_empty() {
+            return Ok(Self(result));
+        }
+
+        let dir = std::fs::read_dir(_PATH_PROC)
+            .map_err(|err| LsIpcError::io1("reading directory", _PATH_PROC, err))?;
+
+        for dir_entry in dir.flatten() {
+            let Some(pid) = dir_entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<libc::pid_t>().ok())
+            else {
+                continue;
+            };
+
+            // Processes may exit during the scan, or be inaccessible to us.
+            let Ok(file) = File::open(dir_entry.path().join("maps")) else {
+                continue;
+            };
+
+            for line in BufReader::new(file).lines().map_while(Result::ok) {
+                let mut fields = line.split_ascii_whitespace().skip(4);
+
+                let (Some(inode), Some(path)) = (fields.next(), fields.next()) else {
+                    continue;
+                };
+
+                let Ok(shmid) = inode.parse::<c_int>() else {
+                    continue;
+                };
+
+                if keys.get(&shmid).is_some_and(|key_path| key_path == path) {
+                    let pids = result.entry(shmid).or_default();
+                    if !pids.contains(&pid) {
+                        pids.push(pid);
+                    }
+                }
+            }
+        }
+
+        Ok(Self(result))
+    }
+
+    fn for_id(&self, shmid: c_int) -> &[libc::pid_t] {
+        self.0.get(&shmid).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Fails if creating a segment of `size` bytes would exceed the `SHMMAX`, `SHMMNI`
+/// or `SHMALL` limits.
+pub fn check_create_limits(size: u64) -> Result<(), LsIpcError> {
+    let limits = Limits::new()?;
+
+    if size > limits.shm_max {
+        return Err(LsIpcError::LimitExceeded("SHMMAX", limits.shm_max));
+    }
+
+    let sys_v_ipc = SysVIpc::new(None)?;
+
+    if u64::try_from(sys_v_ipc.0.len()).unwrap_or(u64::MAX) >= limits.shm_mni {
+        return Err(LsIpcError::LimitExceeded("SHMMNI", limits.shm_mni));
+    }
+
+    let page_size = get_page_size()?;
+    let used_pages = sys_v_ipc.0.iter().map(|shm| shm.segsz).sum::<u64>() / page_size;
+    if used_pages.saturating_add(size.div_ceil(page_size)) > limits.shm_all {
+        return Err(LsIpcError::LimitExceeded("SHMALL", limits.shm_all));
+    }
+    Ok(())
+}
+
+/// Returns the key and identifier of every shared memory segment in the system.
+pub fn list_keys_and_ids() -> Result<Vec<(libc::key_t, c_int)>, LsIpcError> {
+    let sys_v_ipc = SysVIpc::new(None)?;
+    Ok(sys_v_ipc
+        .0
+        .iter()
+        .map(|entry| (entry.key, entry.shmid))
+        .collect())
+}
+
+/// Removes the shared memory segment identified by `id`.
+pub fn remove(id: c_int) -> Result<(), LsIpcError> {
+    shmctl(id, libc::IPC_RMID).map(|_| ())
+}
+
 pub(crate) fn print_global(
     args: &clap::ArgMatches,
     columns: &[&ColumnInfo],
@@ -400,10 +506,18 @@ pub(crate) fn describe(
 
     table.set_name(c"sharedmemory")?;
 
+    // Scanning every process is costly, so only do it when asked for.
+    let attachments = if id.is_some() || columns.iter().any(|column| column.id == c"APIDS") {
+        Attachments::new(&sys_v_ipc)?
+    } else {
+        Attachments(BTreeMap::new())
+    };
+
     let mut users = UserDbRecordRef::default();
     let mut groups = GroupDbRecordRef::default();
 
     for entry in sys_v_ipc.0 {
+        let attached_pids = attachments.for_id(entry.shmid);
         let mut line = table.new_line(None)?;
 
         for (cell_index, &column) in columns.iter().enumerate() {
@@ -417,6 +531,7 @@ pub(crate) fn describe(
                 b"NATTCH" => describe_integer(entry.nattch),
                 b"CPID" => describe_integer(entry.cpid),
                 b"LPID" => describe_integer(entry.lpid),
+                b"APIDS" => describe_pids(attached_pids),
                 b"CTIME" => format_time(time_format, &now, entry.ctime)?.map(Cow::Owned),
                 b"ATTACH" => format_time(time_format, &now, entry.atime)?.map(Cow::Owned),
                 b"DETACH" => format_time(time_format, &now, entry.dtime)?.map(Cow::Owned),
@@ -437,6 +552,36 @@ pub(crate) fn describe(
                 line.set_data(cell_index, &data_str)?;
             }
         }
+
+        if id.is_some() && !attached_pids.is_empty() {
+            describe_attachments(&mut line, attached_pids)?;
+        }
     }
     Ok(())
 }
+
+fn describe_pids(pids: &[libc::pid_t]) -> Option<Cow<'static, CStr>> {
+    let pids = pids.iter().map(ToString::to_string).collect::<Vec<_>>();
+    (!pids.is_empty()).then(|| Cow::Owned(CString::new(pids.join(",")).unwrap()))
+}
+
+fn describe_attachments(line: &mut LineRef, pids: &[libc::pid_t]) -> Result<(), LsIpcError> {
+    let mut sub_table = Table::new()?;
+    sub_table.set_name(c"attachments")?;
+    sub_table.enable_headings(true)?;
+
+    sub_table.new_column(c"PID", 0.0, smartcols_sys::SCOLS_FL_RIGHT)?;
+    sub_table.new_column(c"COMMAND", 0.0, smartcols_sys::SCOLS_FL_TRUNC)?;
+
+    for &pid in pids {
+        let mut sub_line = sub_table.new_line(None)?;
+
+        sub_line.set_data(0, &CString::new(pid.to_string()).unwrap())?;
+
+        if let Ok(cmd_line) = pid_command_line(pid) {
+            sub_line.set_data(1, &cmd_line)?;
+        }
+    }
+
+    line.set_user_data(sub_table.into_inner().as_ptr().cast())
+}
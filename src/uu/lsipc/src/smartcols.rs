@@ -13,9 +13,9 @@ use smartcols_sys::{
     scols_line_get_cell, scols_line_get_userdata, scols_line_set_data, scols_line_set_userdata,
     scols_new_iter, scols_new_table, scols_print_table, scols_table_enable_export,
     scols_table_enable_json, scols_table_enable_noheadings, scols_table_enable_raw,
-    scols_table_enable_shellvar, scols_table_get_line, scols_table_new_column,
-    scols_table_new_line, scols_table_next_column, scols_table_set_column_separator,
-    scols_table_set_name, scols_unref_table,
+    scols_table_enable_shellvar, scols_table_get_line, scols_table_get_name,
+    scols_table_new_column, scols_table_new_line, scols_table_next_column,
+    scols_table_set_column_separator, scols_table_set_name, scols_unref_table,
 };
 
 use crate::errors::LsIpcError;
@@ -128,6 +128,11 @@ pub(crate) trait TableOperations: Sized {
         LsIpcError::io_from_neg_errno("scols_table_set_name", r).map(|_| ())
     }
 
+    fn name(&self) -> Option<&CStr> {
+        let name = unsafe { scols_table_get_name(self.as_ptr()) };
+        (!name.is_null()).then(|| unsafe { CStr::from_ptr(name) })
+    }
+
     fn line(&self, column_index: usize) -> Result<LineRef, LsIpcError> {
         NonNull::new(unsafe { scols_table_get_line(self.as_ptr(), column_index) })
             .ok_or_else(|| LsIpcError::io0("scols_table_get_line", io::ErrorKind::InvalidInput))
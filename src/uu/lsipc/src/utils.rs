@@ -3,10 +3,11 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-use std::ffi::{CStr, CString};
+use std::ffi::{CStr, CString, c_uint};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::Path;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::atomic::AtomicU64;
 use std::{io, ptr};
@@ -118,6 +119,72 @@ where
         .map_err(|_| LsIpcError::io1("invalid data", path, io::ErrorKind::InvalidData))
 }
 
+/// A POSIX IPC object, as represented by a file in `/dev/mqueue` or `/dev/shm`.
+pub(crate) struct PosixIpcEntry {
+    pub(crate) name: String,
+    pub(crate) path: PathBuf,
+    pub(crate) perms: c_uint,
+    pub(crate) uid: libc::uid_t,
+    pub(crate) gid: libc::gid_t,
+    pub(crate) size: u64,
+    pub(crate) mtime: libc::time_t,
+}
+
+/// Lists the POSIX IPC objects stored in `dir`, sorted by name.
+///
+/// `resource_name` maps a file name to the name of the object it represents, or
+/// rejects files holding other kinds of objects. If `name` is given, only the
+/// object with that name is listed. A missing directory yields no objects.
+pub(crate) fn posix_ipc_entries(
+    dir: &str,
+    resource_name: fn(&str) -> Option<&str>,
+    name: Option<&str>,
+) -> Result<Vec<PosixIpcEntry>, LsIpcError> {
+    let read_dir = match std::fs::read_dir(dir) {
+        Ok(read_dir) => read_dir,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::default()),
+        Err(err) => return Err(LsIpcError::io1("reading directory", dir, err)),
+    };
+
+    let name = name.map(|name| name.trim_start_matches('/'));
+    let mut list = Vec::default();
+
+    for dir_entry in read_dir {
+        let dir_entry = dir_entry.map_err(|err| LsIpcError::io1("reading directory", dir, err))?;
+
+        let file_name = dir_entry.file_name();
+        let Some(resource) = file_name.to_str().and_then(resource_name) else {
+            continue;
+        };
+
+        if name.is_some_and(|name| name != resource) {
+            continue;
+        }
+
+        let path = dir_entry.path();
+        let metadata = match dir_entry.metadata() {
+            Ok(metadata) if metadata.is_file() => metadata,
+            // The object might have been removed in the meantime.
+            Ok(_) => continue,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(LsIpcError::io1("reading metadata", path, err)),
+        };
+
+        list.push(PosixIpcEntry {
+            name: format!("/{resource}"),
+            path,
+            perms: metadata.mode(),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            size: metadata.size(),
+            mtime: metadata.mtime(),
+        });
+    }
+
+    list.sort_unstable_by(|a, b| a.name.cmp(&b.name));
+    Ok(list)
+}
+
 pub(crate) struct UserDbRecordRef(*const libc::passwd);
 
 impl Default for UserDbRecordRef {
@@ -163,3 +230,25 @@ impl GroupDbRecordRef {
             .map(|name| unsafe { CStr::from_ptr(name) })
     }
 }
+
+/// Parses an IPC key the way `strtoul(3)` does with base 0: a `0x` prefix selects
+/// hexadecimal, a leading `0` selects octal, anything else is decimal.
+pub fn parse_key(s: &str) -> Result<libc::key_t, LsIpcError> {
+    let trimmed = s.trim();
+
+    let parsed = if let Some(hex) = trimmed
+        .strip_prefix("0x")
+        .or_else(|| trimmed.strip_prefix("0X"))
+    {
+        u32::from_str_radix(hex, 16)
+    } else if trimmed.len() > 1 && trimmed.starts_with('0') {
+        u32::from_str_radix(&trimmed[1..], 8)
+    } else {
+        trimmed.parse()
+    };
+
+    // Keys are stored as a signed `key_t`, but are conventionally written as unsigned.
+    parsed
+        .map(|key| key as libc::key_t)
+        .map_err(|_| LsIpcError::InvalidKey(s.into()))
+}
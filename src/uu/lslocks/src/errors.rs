@@ -0,0 +1,48 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LsLocksError {
+    #[error("{0} '{path}': {2}", path = .1.display())]
+    IO1(String, PathBuf, std::io::Error),
+
+    #[error("failed to parse lock '{0}': {1}")]
+    Parse(String, ParseLockError),
+}
+
+impl LsLocksError {
+    pub(crate) fn io1(
+        message: impl Into<String>,
+        path: impl Into<PathBuf>,
+        error: std::io::Error,
+    ) -> Self {
+        Self::IO1(message.into(), path.into(), error)
+    }
+}
+
+impl uucore::error::UError for LsLocksError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        false
+    }
+}
+
+/// Reasons for a line of /proc/locks (or a `lock:` line of /proc/<pid>/fdinfo/<fd>) to be rejected.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseLockError {
+    #[error("missing {0}")]
+    MissingField(&'static str),
+
+    #[error("invalid {0} '{1}'")]
+    InvalidField(&'static str, String),
+
+    #[error("unexpected PID {0} for an OFD lock")]
+    UnexpectedOfdPid(usize),
+}
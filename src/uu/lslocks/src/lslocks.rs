@@ -3,126 +3,253 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
+mod errors;
+
+use std::io::IsTerminal;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
 use std::{fmt, fs, str::FromStr};
 
-use clap::{crate_version, Command};
-use uucore::{error::UResult, format_usage, help_about, help_usage};
+use clap::{crate_version, Arg, ArgAction, Command};
+use serde_json::Value;
+use uucore::{error::UResult, format_usage, help_about, help_usage, show_warning};
+use uutable::TableColumn;
+
+use crate::errors::{LsLocksError, ParseLockError};
+
+const PATH_PROC_LOCKS: &str = "/proc/locks";
 
 // See https://www.man7.org/linux/man-pages/man5/proc_locks.5.html for details on each field's meaning
 #[derive(Debug)]
 struct Lock {
-    _ord: usize,
+    ord: u64,
+    blocked: bool, // Waiters are listed after their blocker, with the same ordinal and a `->` marker
     lock_type: LockType,
-    mandatory: bool,
+    state: LockState,
     mode: LockMode,
-    pid: Option<usize>, // This value is -1 for OFD locks, hence the Option
-    major_minor: String,
-    inode: usize,
-    start_offset: usize,       // Byte offset to start of lock
-    end_offset: Option<usize>, // None = lock does not have an explicit end offset and applies until the end of the file
+    pid: Option<usize>,         // This value is -1 for OFD locks, hence the Option
+    device: Option<(u32, u32)>, // None = the lock is not on a file, e.g. a lease being broken
+    inode: u64,
+    start_offset: u64,       // Byte offset to start of lock
+    end_offset: Option<u64>, // None = lock does not have an explicit end offset and applies until the end of the file
+    waiters: Vec<Lock>,      // Locks blocked by this one
+
+    // The fields below are not part of /proc/locks, and are resolved by inspecting /proc/<pid>
+    path: Option<String>,
+    size: Option<u64>,
+    blocker: Option<usize>, // PID of the process holding the lock we're waiting on
+    holders: Vec<Holder>,
 }
 
 impl Lock {
-    fn get_value(&self, col: &Column) -> String {
+    fn get_value(&self, col: &Column, opts: &OutputOptions) -> String {
         match col {
             Column::Command => resolve_command(self).unwrap_or("<unknown>".to_string()),
             Column::Pid => self
-                .pid
+                .owner_pid()
                 .map(|pid| pid.to_string())
                 .unwrap_or("-".to_string()),
             Column::Type => self.lock_type.to_string(),
-            Column::Size => todo!(),
+            Column::Size => match self.size {
+                Some(size) if opts.bytes => size.to_string(),
+                Some(size) => uutable::size_to_human_string(size),
+                None => String::new(),
+            },
             Column::Inode => self.inode.to_string(),
-            Column::MajorMinor => self.major_minor.clone(),
+            Column::MajorMinor => self
+                .device
+                .map(|(major, minor)| format!("{}:{}", major, minor))
+                .unwrap_or_default(),
             Column::Mode => self.mode.to_string(),
             Column::Mandatory => {
-                if self.mandatory {
+                if self.state == LockState::Mandatory {
                     "1".to_string()
                 } else {
                     "0".to_string()
                 }
             }
             Column::Start => self.start_offset.to_string(),
-            // TODO: In the case of EOF end_offset, we should actually resolve the actual file size and display that as the end offset
-            Column::End => self
-                .end_offset
-                .map(|offset| offset.to_string())
-                .unwrap_or("EOF".to_string()),
-            Column::Path => todo!(), // TODO: Resolve filepath of the lock target
-            Column::Blocker => todo!(), // TODO: Check if lock is blocker (and by what)
-            Column::Holders => todo!(), // TODO: Resolve all holders of the lock (this would also let us display a process/PID for OFD locks)
+            // Locks reaching EOF end where the file currently ends
+            Column::End => match (self.end_offset, self.size) {
+                (Some(offset), _) => offset.to_string(),
+                (None, Some(size)) => size.to_string(),
+                (None, None) => "EOF".to_string(),
+            },
+            Column::Path => self.path.clone().unwrap_or_default(),
+            Column::Blocker => self.blocker.map(|pid| pid.to_string()).unwrap_or_default(),
+            Column::Holders => self
+                .holders
+                .iter()
+                .map(Holder::to_string)
+                .collect::<Vec<_>>()
+                .join(" "),
         }
     }
+
+    // Same as `get_value`, but typed the way libsmartcols does for each column
+    fn get_json_value(&self, col: &Column, opts: &OutputOptions) -> Value {
+        match col {
+            Column::Pid => self.owner_pid().into(),
+            Column::Size if opts.bytes => self.size.into(),
+            Column::Inode => self.inode.into(),
+            Column::Mandatory => (self.state == LockState::Mandatory).into(),
+            Column::Start => self.start_offset.into(),
+            Column::End => self.end_offset.or(self.size).into(),
+            Column::Blocker => self.blocker.into(),
+            Column::Holders => self.holders.iter().map(Holder::to_string).collect(),
+            _ => {
+                let value = self.get_value(col, opts);
+                if value.is_empty() {
+                    Value::Null
+                } else {
+                    value.into()
+                }
+            }
+        }
+    }
+
+    // OFD locks aren't owned by a process, so we fall back to the first process holding them
+    fn owner_pid(&self) -> Option<usize> {
+        self.pid
+            .or_else(|| self.holders.first().map(|holder| holder.pid))
+    }
+
+    fn is_on_file(&self, metadata: &fs::Metadata) -> bool {
+        let dev = metadata.dev();
+        self.device == Some((libc::major(dev), libc::minor(dev))) && metadata.ino() == self.inode
+    }
+
+    // Compares the lock as listed in /proc/<pid>/fdinfo/<fd> with the one listed in /proc/locks
+    fn is_same_lock(&self, other: &Lock) -> bool {
+        !self.blocked
+            && !other.blocked
+            && self.lock_type == other.lock_type
+            && self.mode == other.mode
+            && self.pid == other.pid
+            && self.device == other.device
+            && self.inode == other.inode
+            && self.start_offset == other.start_offset
+            && self.end_offset == other.end_offset
+    }
+}
+
+fn parse_field<T: FromStr>(value: &str, name: &'static str) -> Result<T, ParseLockError> {
+    value
+        .parse()
+        .map_err(|_| ParseLockError::InvalidField(name, value.to_string()))
 }
 
 impl FromStr for Lock {
-    type Err = ();
+    type Err = ParseLockError;
+
+    // Lines look like `1: POSIX  ADVISORY  WRITE 1234 08:02:131 0 EOF`, or
+    // `1: -> POSIX  ADVISORY  WRITE 1235 08:02:131 0 EOF` for a waiter.
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let mut parts = input.split_whitespace();
+        let mut parts = input.split_whitespace().peekable();
+        let mut next_part = |name| parts.next().ok_or(ParseLockError::MissingField(name));
 
         // Ordinal position comes in the form of `<value>:`, so we need to strip away the `:`
-        let ord = parts
-            .next()
-            .and_then(|s| s.strip_suffix(":"))
-            .unwrap()
-            .parse::<usize>()
-            .unwrap();
-        let lock_type = parts
-            .next()
-            .and_then(|part| LockType::from_str(part).ok())
-            .unwrap();
-        let mandatory = parts
-            .next()
-            .map(|part| match part {
-                "MANDATORY" => true,
-                "ADVISORY" => false,
-                _ => panic!("Unrecognized value in lock line: {}", part),
-            })
-            .unwrap();
-        let mode = parts
-            .next()
-            .and_then(|part| LockMode::from_str(part).ok())
-            .unwrap();
-        let pid: Option<usize> = parts.next().and_then(|pid_str| match pid_str {
-            "-1" => None,
-            other => other.parse::<usize>().ok(),
-        });
+        let ord = next_part("ordinal")?;
+        let ord = ord
+            .strip_suffix(':')
+            .ok_or_else(|| ParseLockError::InvalidField("ordinal", ord.to_string()))
+            .and_then(|ord| parse_field(ord, "ordinal"))?;
+
+        let mut lock_type = next_part("lock type")?;
+        let blocked = lock_type == "->";
+        if blocked {
+            lock_type = next_part("lock type")?;
+        }
+        let lock_type = parse_field(lock_type, "lock type")?;
+
+        let state = parse_field(next_part("lock state")?, "lock state")?;
+        let mode = parse_field(next_part("lock mode")?, "lock mode")?;
 
-        if lock_type == LockType::OFDLCK && pid.is_some() {
-            println!("Unexpected PID value on OFD lock: '{}'", input);
-            return Err(());
+        let pid = match next_part("PID")? {
+            "-1" => None,
+            pid => Some(parse_field(pid, "PID")?),
         };
 
-        // This field has a format of MAJOR:MINOR:INODE
-        let major_minor_inode: Vec<_> = parts.next().unwrap().split(":").collect();
-        assert_eq!(major_minor_inode.len(), 3);
-        let major_minor = [major_minor_inode[0], major_minor_inode[1]].join(":");
-        let inode = major_minor_inode[2].parse::<usize>().unwrap();
+        if let (LockType::OFDLCK, Some(pid)) = (&lock_type, pid) {
+            return Err(ParseLockError::UnexpectedOfdPid(pid));
+        }
 
-        let start_offset = parts.next().unwrap().parse::<usize>().unwrap();
-        let end_offset: Option<usize> = parts.next().and_then(|offset_str| match offset_str {
+        // This field has a format of MAJOR:MINOR:INODE, with the device numbers in hexadecimal,
+        // or `<none>:0` for locks which aren't on a file
+        let major_minor_inode = next_part("device and inode")?;
+        let (device, inode) = match major_minor_inode.rsplit_once(':') {
+            Some(("<none>", inode)) => (None, parse_field(inode, "inode")?),
+            Some((major_minor, inode)) => {
+                let invalid_device =
+                    || ParseLockError::InvalidField("device", major_minor.to_string());
+                let (major, minor) = major_minor.split_once(':').ok_or_else(invalid_device)?;
+                let major = u32::from_str_radix(major, 16).map_err(|_| invalid_device())?;
+                let minor = u32::from_str_radix(minor, 16).map_err(|_| invalid_device())?;
+                (Some((major, minor)), parse_field(inode, "inode")?)
+            }
+            None => {
+                return Err(ParseLockError::InvalidField(
+                    "device and inode",
+                    major_minor_inode.to_string(),
+                ))
+            }
+        };
+
+        let start_offset = parse_field(next_part("start offset")?, "start offset")?;
+        let end_offset = match next_part("end offset")? {
             "EOF" => None,
-            other => other.parse::<usize>().ok(),
-        });
+            offset => Some(parse_field(offset, "end offset")?),
+        };
 
         Ok(Self {
-            _ord: ord,
+            ord,
+            blocked,
             lock_type,
-            mandatory,
+            state,
             mode,
             pid,
-            major_minor,
+            device,
             inode,
             start_offset,
             end_offset,
+            waiters: Vec::new(),
+            path: None,
+            size: None,
+            blocker: None,
+            holders: Vec::new(),
         })
     }
 }
 
+// Waiters are attached to the lock they're waiting on, which is listed right before them
+fn parse_locks(content: &str) -> Vec<Lock> {
+    let mut locks: Vec<Lock> = Vec::new();
+
+    for line in content.lines() {
+        match Lock::from_str(line) {
+            Ok(lock) if lock.blocked => {
+                match locks.last_mut().filter(|holder| holder.ord == lock.ord) {
+                    Some(holder) => holder.waiters.push(lock),
+                    // The blocking lock may have gone away while the kernel was listing locks
+                    None => locks.push(lock),
+                }
+            }
+            Ok(lock) => locks.push(lock),
+            Err(err) => show_warning!("{}", LsLocksError::Parse(line.trim().to_string(), err)),
+        }
+    }
+
+    locks
+}
+
 #[derive(Debug, PartialEq, Eq)]
 #[allow(clippy::upper_case_acronyms)]
 enum LockType {
+    ACCESS, // POSIX lock taken by the kernel to enforce a mandatory lock
+    DELEG,  // NFS delegation
     FLOCK,  // BSD file lock
+    LEASE,  // File lease
     OFDLCK, // Open file descriptor
     POSIX,  // POSIX byte-range lock
 }
@@ -131,7 +258,10 @@ impl FromStr for LockType {
     type Err = ();
     fn from_str(input: &str) -> Result<Self, Self::Err> {
         match input {
+            "ACCESS" => Ok(Self::ACCESS),
+            "DELEG" => Ok(Self::DELEG),
             "FLOCK" => Ok(Self::FLOCK),
+            "LEASE" => Ok(Self::LEASE),
             "OFDLCK" => Ok(Self::OFDLCK),
             "POSIX" => Ok(Self::POSIX),
             _ => Err(()),
@@ -142,17 +272,46 @@ impl FromStr for LockType {
 impl fmt::Display for LockType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            LockType::ACCESS => write!(f, "ACCESS"),
+            LockType::DELEG => write!(f, "DELEG"),
             LockType::FLOCK => write!(f, "FLOCK"),
+            LockType::LEASE => write!(f, "LEASE"),
             LockType::OFDLCK => write!(f, "OFDLCK"),
             LockType::POSIX => write!(f, "POSIX"),
         }
     }
 }
 
-#[derive(Debug)]
-enum LockMode {
+// Locks are either advisory or mandatory, while leases report how far along they are in being broken
+#[derive(Debug, PartialEq, Eq)]
+enum LockState {
+    Advisory,
+    Mandatory,
+    Active,   // Lease which isn't being broken
+    Breaking, // Lease being broken, waiting for its holder to release it
+    Breaker,  // Placeholder lease of the process breaking another one
+}
+
+impl FromStr for LockState {
+    type Err = ();
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        match input {
+            "ADVISORY" => Ok(Self::Advisory),
+            "MANDATORY" => Ok(Self::Mandatory),
+            "ACTIVE" => Ok(Self::Active),
+            "BREAKING" => Ok(Self::Breaking),
+            "BREAKER" => Ok(Self::Breaker),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Access a lock grants, as named in /proc/locks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LockMode {
     Read,
     Write,
+    Unlock, // Lease being broken to no lease at all
 }
 
 impl FromStr for LockMode {
@@ -161,6 +320,7 @@ impl FromStr for LockMode {
         match input {
             "WRITE" => Ok(Self::Write),
             "READ" => Ok(Self::Read),
+            "UNLCK" => Ok(Self::Unlock),
             _ => Err(()),
         }
     }
@@ -171,13 +331,12 @@ impl fmt::Display for LockMode {
         match self {
             LockMode::Write => write!(f, "WRITE"),
             LockMode::Read => write!(f, "READ"),
+            LockMode::Unlock => write!(f, "UNLCK"),
         }
     }
 }
 
-// All of the columns that need to be supported in the final version
-#[derive(Clone)]
-#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Column {
     Command,
     Pid,
@@ -194,7 +353,23 @@ enum Column {
     Holders,
 }
 
-impl Column {
+impl TableColumn for Column {
+    const ALL: &'static [Self] = &[
+        Self::Command,
+        Self::Pid,
+        Self::Type,
+        Self::Size,
+        Self::Inode,
+        Self::MajorMinor,
+        Self::Mode,
+        Self::Mandatory,
+        Self::Start,
+        Self::End,
+        Self::Path,
+        Self::Blocker,
+        Self::Holders,
+    ];
+
     fn header_text(&self) -> &'static str {
         match self {
             Self::Command => "COMMAND",
@@ -212,85 +387,398 @@ impl Column {
             Self::Holders => "HOLDERS",
         }
     }
+
+    fn get_help(&self) -> &'static str {
+        match self {
+            Self::Command => "command of the process holding the lock",
+            Self::Pid => "PID of the process holding the lock",
+            Self::Type => "kind of lock",
+            Self::Size => "size of the lock, use <number> if --bytes is given",
+            Self::Inode => "inode number",
+            Self::MajorMinor => "major:minor device number",
+            Self::Mode => "lock access mode",
+            Self::Mandatory => "mandatory state of the lock: 0 (none), 1 (set)",
+            Self::Start => "relative byte offset of the lock",
+            Self::End => "ending offset of the lock",
+            Self::Path => "path of the locked file",
+            Self::Blocker => "PID of the process blocking the lock",
+            Self::Holders => "holders of the lock",
+        }
+    }
+
+    fn get_float_right(&self) -> bool {
+        matches!(
+            self,
+            Self::Pid | Self::Size | Self::Inode | Self::Start | Self::End | Self::Blocker
+        )
+    }
+}
+
+impl Column {
+    // Columns which may be shortened to make the table fit in the terminal
+    fn is_truncatable(&self) -> bool {
+        matches!(self, Self::Command | Self::Path | Self::Holders)
+    }
 }
 
 fn resolve_command(lock: &Lock) -> Option<String> {
-    if let Some(pid) = lock.pid {
-        return fs::read_to_string(format!("/proc/{}/comm", pid))
-            .map(|content| content.trim().to_string())
-            .ok();
+    lock.owner_pid().and_then(read_command)
+}
+
+fn read_command(pid: usize) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/comm", pid))
+        .map(|content| content.trim().to_string())
+        .ok()
+}
+
+// A process holding a lock through one of its file descriptors
+#[derive(Debug, Clone)]
+struct Holder {
+    pid: usize,
+    fd: usize,
+}
+
+impl Holder {
+    fn fd_path(&self) -> PathBuf {
+        PathBuf::from(format!("/proc/{}/fd/{}", self.pid, self.fd))
     }
+}
 
-    // File descriptor locks don't have a real notion of an "owner process", since it can be shared by multiple processes.
-    // The original `lslocks` goes through `/proc/<pid>/fdinfo/*` to find *any* of the processes that reference the same device/inode combo from the lock
-    // We don't implement this behaviour yet, and just show "unknown" for the locks which don't have a clear owner
-    None
+impl fmt::Display for Holder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let command = read_command(self.pid).unwrap_or_default();
+        write!(f, "{},{},{}", self.pid, command, self.fd)
+    }
+}
+
+// Every open file descriptor lists the locks held through it as `lock:` lines in /proc/<pid>/fdinfo/<fd>,
+// using the same format as /proc/locks. This is the only way to find out who holds OFD locks.
+fn read_fdinfo_locks() -> Vec<(Lock, Holder)> {
+    let mut result = Vec::new();
+
+    let Ok(proc_entries) = fs::read_dir("/proc") else {
+        return result;
+    };
+
+    for proc_entry in proc_entries.flatten() {
+        let Some(pid) = parse_file_name(&proc_entry.path()) else {
+            continue;
+        };
+
+        // Processes may exit while we're scanning, or we may lack the permissions to inspect them
+        let Ok(fdinfo_entries) = fs::read_dir(proc_entry.path().join("fdinfo")) else {
+            continue;
+        };
+
+        for fdinfo_entry in fdinfo_entries.flatten() {
+            let Some(fd) = parse_file_name(&fdinfo_entry.path()) else {
+                continue;
+            };
+            let Ok(content) = fs::read_to_string(fdinfo_entry.path()) else {
+                continue;
+            };
+
+            let locks = content
+                .lines()
+                .filter_map(|line| line.strip_prefix("lock:"))
+                .filter_map(|line| Lock::from_str(line).ok());
+
+            result.extend(locks.map(|lock| (lock, Holder { pid, fd })));
+        }
+    }
+
+    result
+}
+
+fn parse_file_name(path: &Path) -> Option<usize> {
+    path.file_name()?.to_str()?.parse().ok()
+}
+
+// Looks for the locked file among the open file descriptors of the lock owner
+fn find_fd_path(lock: &Lock) -> Option<PathBuf> {
+    let fd_entries = fs::read_dir(format!("/proc/{}/fd", lock.pid?)).ok()?;
+
+    fd_entries
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| fs::metadata(path).is_ok_and(|metadata| lock.is_on_file(&metadata)))
+}
+
+// When the locked file can't be found (e.g. we aren't allowed to look at the owner's file descriptors),
+// show the mountpoint of the file system that contains it instead.
+fn fallback_path(lock: &Lock) -> Option<String> {
+    let (major, minor) = lock.device?;
+    let device = format!("{}:{}", major, minor);
+
+    let mountinfo = fs::read_to_string("/proc/self/mountinfo").ok()?;
+
+    // Fields are: mount ID, parent ID, MAJOR:MINOR, root, mountpoint, ...
+    // The last matching entry is the one that's visible.
+    mountinfo
+        .lines()
+        .rev()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.get(2) == Some(&device.as_str()))
+        .and_then(|fields| fields.get(4).map(|mountpoint| format!("{}...", mountpoint)))
+}
+
+fn resolve_locks(locks: Vec<Lock>, opts: &OutputOptions) -> Vec<Lock> {
+    // Scanning all file descriptors of all processes is expensive, so only do it when needed
+    let needs_holders = opts.cols.contains(&Column::Holders)
+        || locks
+            .iter()
+            .flat_map(|lock| std::iter::once(lock).chain(&lock.waiters))
+            .any(|lock| lock.lock_type == LockType::OFDLCK);
+    let fdinfo_locks = if needs_holders {
+        read_fdinfo_locks()
+    } else {
+        Vec::new()
+    };
+
+    // Waiters are listed right after the lock blocking them
+    let mut resolved = Vec::new();
+    for mut lock in locks {
+        resolve_lock(&mut lock, &fdinfo_locks);
+
+        let mut waiters = std::mem::take(&mut lock.waiters);
+        for waiter in &mut waiters {
+            resolve_lock(waiter, &fdinfo_locks);
+            waiter.blocker = lock.owner_pid();
+        }
+
+        resolved.push(lock);
+        resolved.append(&mut waiters);
+    }
+
+    if let Some(pid) = opts.pid {
+        resolved.retain(|lock| lock.owner_pid() == Some(pid));
+    }
+
+    if opts.noinaccessible {
+        resolved.retain(|lock| lock.path.is_some());
+    }
+
+    for lock in resolved.iter_mut().filter(|lock| lock.path.is_none()) {
+        lock.path = fallback_path(lock);
+    }
+
+    resolved
+}
+
+fn resolve_lock(lock: &mut Lock, fdinfo_locks: &[(Lock, Holder)]) {
+    lock.holders = fdinfo_locks
+        .iter()
+        .filter(|(fdinfo_lock, _)| fdinfo_lock.is_same_lock(lock))
+        .map(|(_, holder)| holder.clone())
+        .collect();
+
+    if let Some(fd_path) = find_fd_path(lock).or_else(|| lock.holders.first().map(Holder::fd_path))
+    {
+        lock.path = fs::read_link(&fd_path)
+            .ok()
+            .map(|path| path.to_string_lossy().into_owned());
+        lock.size = fs::metadata(&fd_path).ok().map(|metadata| metadata.len());
+    }
 }
 
 const DEFAULT_COLS: &[Column] = &[
     Column::Command,
     Column::Pid,
     Column::Type,
-    //TODO: Implement Column::Size here
+    Column::Size,
     Column::Mode,
     Column::Mandatory,
     Column::Start,
     Column::End,
-    //TODO: Implements Column::Path here
+    Column::Path,
 ];
 
+mod options {
+    pub const BYTES: &str = "bytes";
+    pub const JSON: &str = "json";
+    pub const NOHEADINGS: &str = "noheadings";
+    pub const NOINACCESSIBLE: &str = "noinaccessible";
+    pub const NOTRUNCATE: &str = "notruncate";
+    pub const OUTPUT: &str = "output";
+    pub const OUTPUT_ALL: &str = "output-all";
+    pub const PID: &str = "pid";
+    pub const RAW: &str = "raw";
+}
+
 struct OutputOptions {
     cols: Vec<Column>,
+    bytes: bool,
+    json: bool,
+    raw: bool,
+    noheadings: bool,
+    notruncate: bool,
+    noinaccessible: bool,
+    pid: Option<usize>,
 }
 
-fn print_output(locks: Vec<Lock>, output_opts: OutputOptions) {
-    let mut column_widths: Vec<_> = output_opts
+fn terminal_width() -> Option<usize> {
+    let stdout = std::io::stdout();
+    if !stdout.is_terminal() {
+        return None;
+    }
+
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let r = unsafe { libc::ioctl(stdout.as_raw_fd(), libc::TIOCGWINSZ, &mut size) };
+    (r == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+fn print_table(locks: &[Lock], opts: &OutputOptions) {
+    let rows: Vec<Vec<String>> = locks
+        .iter()
+        .map(|lock| {
+            opts.cols
+                .iter()
+                .map(|col| lock.get_value(col, opts))
+                .collect()
+        })
+        .collect();
+
+    let mut column_widths: Vec<_> = opts
         .cols
         .iter()
         .map(|col| col.header_text().len())
         .collect();
 
-    for lock in &locks {
-        for (i, col) in output_opts.cols.iter().enumerate() {
-            column_widths[i] = column_widths[i].max(lock.get_value(col).len());
+    for row in &rows {
+        for (i, value) in row.iter().enumerate() {
+            column_widths[i] = column_widths[i].max(value.chars().count());
         }
     }
 
-    let headers: Vec<_> = output_opts
-        .cols
-        .iter()
-        .enumerate()
-        .map(|(i, col)| format!("{:<width$}", col.header_text(), width = column_widths[i]))
-        .collect();
+    // Shrink the truncatable columns, starting from the last one, until the table fits in the terminal
+    if let Some(term_width) = terminal_width().filter(|_| !opts.notruncate) {
+        let table_width = column_widths.iter().sum::<usize>() + column_widths.len() - 1;
+        let mut excess = table_width.saturating_sub(term_width);
 
-    println!("{}", headers.join(" "));
+        for (i, col) in opts.cols.iter().enumerate().rev() {
+            if excess == 0 {
+                break;
+            }
+            if col.is_truncatable() {
+                let shrink = excess.min(column_widths[i] - col.header_text().len());
+                column_widths[i] -= shrink;
+                excess -= shrink;
+            }
+        }
+    }
 
-    for lock in &locks {
-        let values: Vec<_> = output_opts
+    let format_row = |values: Vec<String>| {
+        let cells: Vec<_> = opts
             .cols
             .iter()
+            .zip(values)
             .enumerate()
-            .map(|(i, col)| format!("{:<width$}", lock.get_value(col), width = column_widths[i]))
+            .map(|(i, (col, value))| {
+                let width = column_widths[i];
+                let value: String = value.chars().take(width).collect();
+                if col.get_float_right() {
+                    format!("{:>width$}", value)
+                } else {
+                    format!("{:<width$}", value)
+                }
+            })
+            .collect();
+        cells.join(" ")
+    };
+
+    if !opts.noheadings {
+        let headers = opts.cols.iter().map(|col| col.header_text().to_string());
+        println!("{}", format_row(headers.collect()));
+    }
+
+    for row in rows {
+        println!("{}", format_row(row));
+    }
+}
+
+// Like libsmartcols, escape the characters which would make raw output ambiguous
+fn escape_raw(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == ' ' || c == '\\' || c.is_control() {
+            result.push_str(&format!("\\x{:02x}", c as u32));
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn print_raw(locks: &[Lock], opts: &OutputOptions) {
+    if !opts.noheadings {
+        let headers: Vec<_> = opts.cols.iter().map(|col| col.header_text()).collect();
+        println!("{}", headers.join(" "));
+    }
+
+    for lock in locks {
+        let values: Vec<_> = opts
+            .cols
+            .iter()
+            .map(|col| escape_raw(&lock.get_value(col, opts)))
             .collect();
         println!("{}", values.join(" "));
     }
 }
 
+fn print_json(locks: &[Lock], opts: &OutputOptions) {
+    let records: Vec<_> = locks
+        .iter()
+        .map(|lock| {
+            let record: serde_json::Map<_, _> = opts
+                .cols
+                .iter()
+                .map(|col| {
+                    let key = col.header_text().to_lowercase();
+                    (key, lock.get_json_value(col, opts))
+                })
+                .collect();
+            Value::Object(record)
+        })
+        .collect();
+
+    uutable::print_json(&serde_json::json!({ "locks": records }));
+}
+
 #[uucore::main]
-pub fn uumain(_args: impl uucore::Args) -> UResult<()> {
-    let output_opts = OutputOptions {
-        cols: Vec::from(DEFAULT_COLS),
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches = uu_app().try_get_matches_from(args)?;
+
+    // --output takes priority over --output-all
+    let cols = if let Some(cols) = matches.get_one::<Vec<Column>>(options::OUTPUT) {
+        cols.clone()
+    } else if matches.get_flag(options::OUTPUT_ALL) {
+        Column::ALL.to_vec()
+    } else {
+        Vec::from(DEFAULT_COLS)
     };
 
-    let locks: Vec<_> = match fs::read_to_string("/proc/locks") {
-        Ok(content) => content
-            .lines()
-            .map(|line| Lock::from_str(line).unwrap())
-            .collect(),
-        Err(e) => panic!("Could not read /proc/locks: {}", e),
+    let output_opts = OutputOptions {
+        cols,
+        bytes: matches.get_flag(options::BYTES),
+        json: matches.get_flag(options::JSON),
+        raw: matches.get_flag(options::RAW),
+        noheadings: matches.get_flag(options::NOHEADINGS),
+        notruncate: matches.get_flag(options::NOTRUNCATE),
+        noinaccessible: matches.get_flag(options::NOINACCESSIBLE),
+        pid: matches.get_one::<usize>(options::PID).copied(),
     };
 
-    print_output(locks, output_opts);
+    let content = fs::read_to_string(PATH_PROC_LOCKS)
+        .map_err(|err| LsLocksError::io1("failed to read", PATH_PROC_LOCKS, err))?;
+    let locks = resolve_locks(parse_locks(&content), &output_opts);
+
+    if output_opts.json {
+        print_json(&locks, &output_opts);
+    } else if output_opts.raw {
+        print_raw(&locks, &output_opts);
+    } else {
+        print_table(&locks, &output_opts);
+    }
     Ok(())
 }
 
@@ -303,4 +791,142 @@ pub fn uu_app() -> Command {
         .about(ABOUT)
         .override_usage(format_usage(USAGE))
         .infer_long_args(true)
+        .arg(
+            Arg::new(options::BYTES)
+                .short('b')
+                .long(options::BYTES)
+                .help("print SIZE in bytes rather than in human readable format")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NOINACCESSIBLE)
+                .short('i')
+                .long(options::NOINACCESSIBLE)
+                .help("ignore locks without read permissions")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::JSON)
+                .short('J')
+                .long(options::JSON)
+                .help("use JSON output format")
+                .action(ArgAction::SetTrue)
+                .conflicts_with(options::RAW),
+        )
+        .arg(
+            Arg::new(options::NOHEADINGS)
+                .short('n')
+                .long(options::NOHEADINGS)
+                .help("don't print headings")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::OUTPUT)
+                .short('o')
+                .long(options::OUTPUT)
+                .help("output columns")
+                .action(ArgAction::Set)
+                .value_name("list")
+                .value_parser(|list: &str| uutable::parse_columns(list, DEFAULT_COLS)),
+        )
+        .arg(
+            Arg::new(options::OUTPUT_ALL)
+                .long(options::OUTPUT_ALL)
+                .help("output all columns")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::PID)
+                .short('p')
+                .long(options::PID)
+                .help("display only locks held by this process")
+                .action(ArgAction::Set)
+                .value_name("pid")
+                .value_parser(clap::value_parser!(usize)),
+        )
+        .arg(
+            Arg::new(options::RAW)
+                .short('r')
+                .long(options::RAW)
+                .help("use the raw output format")
+                .action(ArgAction::SetTrue)
+                .conflicts_with(options::JSON),
+        )
+        .arg(
+            Arg::new(options::NOTRUNCATE)
+                .short('u')
+                .long(options::NOTRUNCATE)
+                .help("don't truncate text in columns")
+                .action(ArgAction::SetTrue),
+        )
+        .after_help(uutable::columns_help::<Column>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lock() {
+        let lock = Lock::from_str("1: POSIX  ADVISORY  WRITE 1234 08:02:131 0 EOF").unwrap();
+        assert_eq!(lock.ord, 1);
+        assert!(!lock.blocked);
+        assert_eq!(lock.lock_type, LockType::POSIX);
+        assert_eq!(lock.pid, Some(1234));
+        assert_eq!(lock.device, Some((8, 2)));
+        assert_eq!(lock.inode, 131);
+        assert_eq!(lock.end_offset, None);
+    }
+
+    #[test]
+    fn test_parse_large_offsets() {
+        let lock =
+            Lock::from_str("2: OFDLCK ADVISORY  READ  -1 fd:00:42 4294967296 9223372036854775807")
+                .unwrap();
+        assert_eq!(lock.device, Some((253, 0)));
+        assert_eq!(lock.start_offset, 1 << 32);
+        assert_eq!(lock.end_offset, Some(i64::MAX as u64));
+    }
+
+    #[test]
+    fn test_parse_leases() {
+        let lock = Lock::from_str("3: LEASE  BREAKING  UNLCK 42 00:2b:7 0 EOF").unwrap();
+        assert_eq!(lock.lock_type, LockType::LEASE);
+        assert_eq!(lock.state, LockState::Breaking);
+        assert_eq!(lock.mode, LockMode::Unlock);
+
+        let lock = Lock::from_str("4: DELEG  ACTIVE    READ  42 <none>:0 0 EOF").unwrap();
+        assert_eq!(lock.lock_type, LockType::DELEG);
+        assert_eq!(lock.device, None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Lock::from_str("1: POSIX ADVISORY").unwrap_err(),
+            ParseLockError::MissingField("lock mode")
+        );
+        assert_eq!(
+            Lock::from_str("1: POSIX ADVISORY WRITE 12 08:02:131 0 -1").unwrap_err(),
+            ParseLockError::InvalidField("end offset", "-1".to_string())
+        );
+        assert_eq!(
+            Lock::from_str("1: OFDLCK ADVISORY WRITE 12 08:02:131 0 EOF").unwrap_err(),
+            ParseLockError::UnexpectedOfdPid(12)
+        );
+    }
+
+    #[test]
+    fn test_parse_waiters() {
+        let locks = parse_locks(
+            "1: POSIX  ADVISORY  WRITE 10 08:02:131 0 EOF\n\
+             1: -> POSIX  ADVISORY  WRITE 11 08:02:131 0 EOF\n\
+             garbage\n\
+             2: -> FLOCK  ADVISORY  WRITE 12 08:02:132 0 EOF\n",
+        );
+        assert_eq!(locks.len(), 2);
+        assert_eq!(locks[0].waiters.len(), 1);
+        assert_eq!(locks[0].waiters[0].pid, Some(11));
+        assert!(locks[1].blocked);
+    }
 }
@@ -3,60 +3,150 @@
 // For the full copyright and license information, please view the LICENSE
 // file that was distributed with this source code.
 
-use clap::Arg;
-use clap::{crate_version, Command};
-use std::env;
-#[cfg(not(windows))]
+use clap::{crate_version, Arg, ArgAction, Command};
 use std::fs;
-#[cfg(not(windows))]
-use std::os::unix::fs::MetadataExt;
-use std::process;
-use uucore::{error::UResult, format_usage, help_about, help_usage};
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::Path;
+use uu_findmnt::{parse_table, TableFormat, _PATH_PROC_MOUNTINFO};
+use uucore::error::{set_exit_code, FromIo, UResult, USimpleError};
+use uucore::{format_usage, help_about, help_usage};
 
 const ABOUT: &str = help_about!("mountpoint.md");
 const USAGE: &str = help_usage!("mountpoint.md");
 
-#[uucore::main]
-pub fn uumain(args: impl uucore::Args) -> UResult<()> {
-    let matches: clap::ArgMatches = uu_app().try_get_matches_from(args)?;
-    let path = matches.get_one::<String>("path");
+// Scripts rely on telling "not a mountpoint" apart from other failures.
+const EXIT_NOT_MOUNTPOINT: i32 = 32;
 
-    if let Some(path) = path {
-        if is_mountpoint(path) {
-            println!("{} is a mountpoint", path);
-        } else {
-            println!("{} is not a mountpoint", path);
-        }
+mod options {
+    pub const DEVNO: &str = "devno";
+    pub const FS_DEVNO: &str = "fs-devno";
+    pub const NOFOLLOW: &str = "nofollow";
+    pub const PATH: &str = "path";
+    pub const QUIET: &str = "quiet";
+}
+
+/// Asks the kernel whether `path` is the root of a mount, along with the device number of its
+/// filesystem. Returns `None` on kernels which don't report STATX_ATTR_MOUNT_ROOT.
+#[cfg(target_os = "linux")]
+fn statx_mount_root(path: &str, nofollow: bool) -> Option<(bool, (u32, u32))> {
+    use std::ffi::CString;
+
+    let path = CString::new(path).ok()?;
+    let flags = if nofollow {
+        libc::AT_SYMLINK_NOFOLLOW
     } else {
-        // Handle the case where path is not provided
-        eprintln!("Error: Path argument is required");
-        process::exit(1);
+        0
+    };
+
+    let mut stx: libc::statx = unsafe { std::mem::zeroed() };
+    let ret = unsafe {
+        libc::statx(
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            flags,
+            libc::STATX_BASIC_STATS,
+            &mut stx,
+        )
+    };
+
+    let mount_root = libc::STATX_ATTR_MOUNT_ROOT as u64;
+    if ret != 0 || stx.stx_attributes_mask & mount_root == 0 {
+        return None;
+    }
+
+    let device = (stx.stx_dev_major, stx.stx_dev_minor);
+    Some((stx.stx_attributes & mount_root != 0, device))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn statx_mount_root(_path: &str, _nofollow: bool) -> Option<(bool, (u32, u32))> {
+    None
+}
+
+/// Looks `path` up in the mount table, for kernels without STATX_ATTR_MOUNT_ROOT.
+fn mountinfo_device(path: &str) -> Option<(u32, u32)> {
+    let path = fs::canonicalize(path).ok()?;
+    let content = fs::read_to_string(_PATH_PROC_MOUNTINFO).ok()?;
+    let (mounts, _) = parse_table(&content, TableFormat::MountInfo);
+
+    // The last of several filesystems mounted on the same path is the visible one.
+    mounts
+        .iter()
+        .rev()
+        .find(|mount| Path::new(&mount.target) == path)
+        .and_then(|mount| mount.device)
+}
+
+/// The device number of the filesystem mounted on `path`, or `None` if it isn't a mountpoint.
+fn mountpoint_device(path: &str, nofollow: bool) -> Option<(u32, u32)> {
+    match statx_mount_root(path, nofollow) {
+        Some((true, device)) => Some(device),
+        Some((false, _)) => None,
+        None => mountinfo_device(path),
     }
-    Ok(())
 }
 
-#[cfg(not(windows))]
-fn is_mountpoint(path: &str) -> bool {
-    let metadata = match fs::metadata(path) {
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches: clap::ArgMatches = uu_app().try_get_matches_from(args)?;
+    let path = matches.get_one::<String>(options::PATH).unwrap();
+    let quiet = matches.get_flag(options::QUIET);
+    let nofollow = matches.get_flag(options::NOFOLLOW);
+
+    let metadata = if nofollow {
+        fs::symlink_metadata(path)
+    } else {
+        fs::metadata(path)
+    };
+    let metadata = match metadata {
         Ok(metadata) => metadata,
-        Err(_) => return false,
+        Err(_) if quiet => {
+            set_exit_code(1);
+            return Ok(());
+        }
+        Err(err) => return Err(err.map_err_context(|| path.to_string())),
     };
 
-    let dev = metadata.dev();
-    let inode = metadata.ino();
-
-    // Root inode (typically 2 in most Unix filesystems) indicates a mount point
-    inode == 2
-        || match fs::metadata("..") {
-            Ok(parent_metadata) => parent_metadata.dev() != dev,
-            Err(_) => false,
+    if matches.get_flag(options::DEVNO) {
+        if !metadata.file_type().is_block_device() {
+            if quiet {
+                set_exit_code(EXIT_NOT_MOUNTPOINT);
+                return Ok(());
+            }
+            return Err(USimpleError::new(
+                EXIT_NOT_MOUNTPOINT,
+                format!("{path}: not a block device"),
+            ));
         }
-}
+        let rdev = metadata.rdev();
+        println!("{}:{}", libc::major(rdev), libc::minor(rdev));
+        return Ok(());
+    }
+
+    // A symbolic link is never a mountpoint itself.
+    let device = if metadata.is_symlink() {
+        None
+    } else {
+        mountpoint_device(path, nofollow)
+    };
 
-// TODO: implement for windows
-#[cfg(windows)]
-fn is_mountpoint(_path: &str) -> bool {
-    false
+    match device {
+        Some((major, minor)) if matches.get_flag(options::FS_DEVNO) => {
+            println!("{major}:{minor}");
+        }
+        Some(_) => {
+            if !quiet {
+                println!("{path} is a mountpoint");
+            }
+        }
+        None => {
+            if !quiet {
+                println!("{path} is not a mountpoint");
+            }
+            set_exit_code(EXIT_NOT_MOUNTPOINT);
+        }
+    }
+    Ok(())
 }
 
 pub fn uu_app() -> Command {
@@ -66,9 +156,38 @@ pub fn uu_app() -> Command {
         .override_usage(format_usage(USAGE))
         .infer_long_args(true)
         .arg(
-            Arg::new("path")
-                .value_name("PATH")
-                .help("Path to check for mountpoint")
+            Arg::new(options::FS_DEVNO)
+                .short('d')
+                .long(options::FS_DEVNO)
+                .help("print maj:min device number of the filesystem")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::QUIET)
+                .short('q')
+                .long(options::QUIET)
+                .help("quiet mode - don't print anything")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::NOFOLLOW)
+                .long(options::NOFOLLOW)
+                .help("do not follow symlink")
+                .conflicts_with(options::DEVNO)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::DEVNO)
+                .short('x')
+                .long(options::DEVNO)
+                .help("print maj:min device number of the block device")
+                .conflicts_with(options::FS_DEVNO)
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::PATH)
+                .value_name("directory|file|device")
+                .help("path to check for mountpoint, or device with --devno")
                 .required(true)
                 .index(1),
         )
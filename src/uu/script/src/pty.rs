@@ -0,0 +1,228 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Running a shell or command on a new pseudoterminal, shared by script and scriptlive.
+
+use nix::pty::{Winsize, openpty};
+use nix::sys::termios;
+use nix::unistd::{ForkResult, Pid, dup2, execvp, fork};
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{AsFd, OwnedFd};
+use std::os::unix::io::AsRawFd;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EchoMode {
+    Always,
+    Never,
+    Auto,
+}
+
+/// The parent's end of a session: the master side of the pseudoterminal, and the child
+/// running on its slave side.
+pub struct Session {
+    pub master: OwnedFd,
+    pub child: Pid,
+}
+
+/// The shell to run, from $SHELL.
+pub fn shell() -> String {
+    std::env::var("SHELL").unwrap_or_else(|_| String::from("/bin/sh"))
+}
+
+/// Puts our terminal in raw mode, so that everything typed reaches the session untouched.
+/// The original settings are restored when it is dropped.
+pub struct RawMode {
+    original: termios::Termios,
+}
+
+impl RawMode {
+    /// Returns `None` if stdin isn't a terminal.
+    pub fn enable() -> Result<Option<Self>, String> {
+        let stdin = io::stdin();
+        if unsafe { libc::isatty(libc::STDIN_FILENO) } == 0 {
+            return Ok(None);
+        }
+
+        let original = termios::tcgetattr(stdin.as_fd())
+            .map_err(|e| format!("Failed to get terminal attributes: {}", e))?;
+        let mut raw = original.clone();
+        termios::cfmakeraw(&mut raw);
+        termios::tcsetattr(stdin.as_fd(), termios::SetArg::TCSANOW, &raw)
+            .map_err(|e| format!("Failed to set terminal attributes: {}", e))?;
+        Ok(Some(Self { original }))
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let stdin = io::stdin();
+        let _ = termios::tcsetattr(stdin.as_fd(), termios::SetArg::TCSADRAIN, &self.original);
+    }
+}
+
+/// The size of our terminal, if stdin is one.
+pub fn window_size() -> Option<Winsize> {
+    let mut ws: Winsize = unsafe { std::mem::zeroed() };
+    (unsafe { libc::ioctl(libc::STDIN_FILENO, libc::TIOCGWINSZ, &mut ws) } == 0).then_some(ws)
+}
+
+/// Opens a pseudoterminal with the size and settings of our terminal, and runs the command
+/// (or an interactive shell) on it.
+pub fn spawn(command: Option<&str>, echo_mode: EchoMode) -> Result<Session, String> {
+    // Get current terminal settings
+    let isatty = unsafe { libc::isatty(libc::STDIN_FILENO) } != 0;
+    let termios = if isatty {
+        let stdin_termios_result = {
+            let stdin = std::io::stdin();
+            termios::tcgetattr(stdin.as_fd())
+        };
+        match stdin_termios_result {
+            Ok(t) => Some(t),
+            Err(e) => {
+                return Err(format!("Failed to get terminal attributes: {}", e));
+            }
+        }
+    } else {
+        None
+    };
+
+    // Create a pseudoterminal
+    let pty = match openpty(None, None) {
+        Ok(pty) => pty,
+        Err(e) => {
+            return Err(format!("Failed to open pseudoterminal: {}", e));
+        }
+    };
+
+    // Set terminal size
+    if let Some(ws) = window_size() {
+        unsafe { libc::ioctl(pty.master.as_raw_fd(), libc::TIOCSWINSZ, &ws) };
+    }
+
+    // Configure echo mode for the slave PTY, which keeps its default settings when our
+    // input isn't a terminal
+    let mut new_termios = match termios {
+        Some(termios_settings) => termios_settings,
+        None => termios::tcgetattr(&pty.slave)
+            .map_err(|e| format!("Failed to get terminal attributes: {}", e))?,
+    };
+    match echo_mode {
+        EchoMode::Always => {
+            new_termios.local_flags |= termios::LocalFlags::ECHO;
+        }
+        EchoMode::Never => {
+            new_termios.local_flags &= !termios::LocalFlags::ECHO;
+        }
+        EchoMode::Auto => {
+            // Default behavior - echo enabled for PTY. Our own terminal is put in raw mode
+            // by RawMode so that nothing is echoed twice, and piped input shows up in the
+            // output as if it had been typed
+            new_termios.local_flags |= termios::LocalFlags::ECHO;
+        }
+    }
+    if let Err(e) = termios::tcsetattr(&pty.slave, termios::SetArg::TCSANOW, &new_termios) {
+        return Err(format!("Failed to set terminal attributes: {}", e));
+    }
+
+    // Fork a child process
+    match unsafe { fork() } {
+        Ok(ForkResult::Parent { child }) => {
+            // Parent process
+            // Close the slave end of the pty in the parent
+            // Use Rust's drop mechanism for safe resource cleanup
+            drop(pty.slave);
+            Ok(Session {
+                master: pty.master,
+                child,
+            })
+        }
+        Ok(ForkResult::Child) => {
+            // Child process
+            // Close the master end of the pty in the child
+            // Use Rust's drop for safety
+            let slave_pty = pty.slave;
+            drop(pty.master);
+
+            // Make the slave PTY the controlling terminal
+            unsafe {
+                libc::setsid();
+                libc::ioctl(slave_pty.as_raw_fd(), libc::TIOCSCTTY, 0);
+            }
+
+            // Redirect stdin, stdout, and stderr to the slave PTY
+            if let Err(e) = dup2(slave_pty.as_raw_fd(), 0) {
+                eprintln!("Failed to redirect stdin: {}", e);
+                unsafe { libc::_exit(1) };
+            }
+            if let Err(e) = dup2(slave_pty.as_raw_fd(), 1) {
+                eprintln!("Failed to redirect stdout: {}", e);
+                unsafe { libc::_exit(1) };
+            }
+            if let Err(e) = dup2(slave_pty.as_raw_fd(), 2) {
+                eprintln!("Failed to redirect stderr: {}", e);
+                unsafe { libc::_exit(1) };
+            }
+
+            // The file descriptors have been duplicated to stdin/stdout/stderr
+            // We can now safely drop the original without closing the duplicated ones
+            drop(slave_pty);
+
+            // Execute the shell or command
+            let shell = shell();
+            if let Some(cmd) = command {
+                let args = vec!["-c".to_string(), cmd.to_string()];
+                let c_shell = CString::new(shell.clone()).unwrap();
+                let c_args: Vec<CString> = std::iter::once(CString::new(shell).unwrap())
+                    .chain(args.into_iter().map(|s| CString::new(s).unwrap()))
+                    .collect();
+                let _ = execvp(&c_shell, &c_args);
+                eprintln!("Failed to execute command: {}", io::Error::last_os_error());
+            } else {
+                let c_shell = CString::new(shell.clone()).unwrap();
+                let c_args = vec![CString::new(shell).unwrap()];
+                let _ = execvp(&c_shell, &c_args);
+                eprintln!("Failed to execute shell: {}", io::Error::last_os_error());
+            }
+
+            // If we get here, exec failed
+            unsafe { libc::_exit(1) };
+        }
+        Err(e) => Err(format!("Fork failed: {}", e)),
+    }
+}
+
+/// Reaps the child, returning its exit status, or 128 plus the signal which killed it.
+pub fn wait(child: Pid) -> io::Result<i32> {
+    let mut status: libc::c_int = 0;
+    loop {
+        if unsafe { libc::waitpid(child.as_raw(), &mut status, 0) } >= 0 {
+            break;
+        }
+        let err = io::Error::last_os_error();
+        if err.kind() != io::ErrorKind::Interrupted {
+            return Err(err);
+        }
+    }
+    Ok(exit_code(status))
+}
+
+/// Like [`wait`], but returns `None` if the child is still running.
+pub fn try_wait(child: Pid) -> io::Result<Option<i32>> {
+    let mut status: libc::c_int = 0;
+    match unsafe { libc::waitpid(child.as_raw(), &mut status, libc::WNOHANG) } {
+        0 => Ok(None),
+        ret if ret < 0 => Err(io::Error::last_os_error()),
+        _ => Ok(Some(exit_code(status))),
+    }
+}
+
+fn exit_code(status: libc::c_int) -> i32 {
+    if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        libc::WEXITSTATUS(status)
+    }
+}
@@ -2,43 +2,88 @@ use clap::builder::ValueParser;
 use clap::{Arg, ArgAction, Command, crate_version};
 use uucore::{error::UResult, format_usage, help_about, help_usage};
 
+#[cfg(target_os = "linux")]
+pub mod pty;
+pub mod timing;
+
 const ABOUT: &str = help_about!("script.md");
 const USAGE: &str = help_usage!("script.md");
 
 #[cfg(target_os = "linux")]
 mod platform {
-    use nix::pty::{Winsize, openpty};
-    use nix::sys::termios;
-    use nix::unistd::{ForkResult, Pid, dup2, execvp, fork};
+    use nix::unistd::Pid;
     use std::collections::HashMap;
-    use std::ffi::CString;
+    use std::ffi::CStr;
     use std::fs::{File, OpenOptions};
     use std::io::{self, Write};
-    use std::os::fd::{AsFd, OwnedFd};
+    use std::os::fd::{FromRawFd, OwnedFd};
     use std::os::unix::fs::MetadataExt;
     use std::os::unix::io::{AsRawFd, RawFd};
     use std::path::{Path, PathBuf};
-    use std::sync::atomic::{AtomicBool, Ordering};
-    use std::time::Instant;
+    use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+    use std::time::{Duration, Instant};
     use uucore::error::{UResult, USimpleError};
 
+    pub use crate::pty::EchoMode;
+    pub use crate::timing::LogFormat;
+    use crate::timing::{Entry, Stream};
+
+    const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+    const CTRL_D: u8 = 0x04;
+    // How long to wait for the output of background jobs at a time once the child has exited
+    const DRAIN_POLL_TIMEOUT_MS: libc::c_int = 100;
+
     static FLUSH_LOGS: AtomicBool = AtomicBool::new(false);
+    static WINDOW_RESIZED: AtomicBool = AtomicBool::new(false);
+    static TERMINATE_SIGNAL: AtomicI32 = AtomicI32::new(0);
+    // Write end of the self-pipe which wakes up the I/O loop when a signal arrives
+    static WAKEUP_FD: AtomicI32 = AtomicI32::new(-1);
+
+    fn wake_up() {
+        let fd = WAKEUP_FD.load(Ordering::SeqCst);
+        if fd >= 0 {
+            // The pipe is non-blocking, if it is full the loop is woken up anyway
+            unsafe { libc::write(fd, [0u8].as_ptr() as *const libc::c_void, 1) };
+        }
+    }
 
     extern "C" fn handle_sigusr1(_: libc::c_int) {
         FLUSH_LOGS.store(true, Ordering::SeqCst);
+        wake_up();
+    }
+
+    extern "C" fn handle_sigwinch(_: libc::c_int) {
+        WINDOW_RESIZED.store(true, Ordering::SeqCst);
+        wake_up();
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
-    pub enum LogFormat {
-        Classic,
-        Advanced,
+    // SIGTERM and SIGHUP are forwarded to the child, which ends the session
+    extern "C" fn handle_terminate(signal: libc::c_int) {
+        TERMINATE_SIGNAL.store(signal, Ordering::SeqCst);
+        wake_up();
     }
 
-    #[derive(Debug, Clone, Copy, PartialEq)]
-    pub enum EchoMode {
-        Always,
-        Never,
-        Auto,
+    extern "C" fn handle_sigchld(_: libc::c_int) {
+        wake_up();
+    }
+
+    // Creates the self-pipe, returning its read end
+    fn wakeup_pipe() -> io::Result<OwnedFd> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK | libc::O_CLOEXEC) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // The write end stays open for as long as the handlers may use it
+        WAKEUP_FD.store(fds[1], Ordering::SeqCst);
+        Ok(unsafe { OwnedFd::from_raw_fd(fds[0]) })
+    }
+
+    fn signal_name(signal: libc::c_int) -> &'static str {
+        match signal {
+            libc::SIGHUP => "SIGHUP",
+            libc::SIGTERM => "SIGTERM",
+            _ => "SIGNAL",
+        }
     }
 
     #[derive(Debug)]
@@ -57,6 +102,10 @@ mod platform {
         pub logging_format: LogFormat,
         pub output_limit: Option<u64>,
         pub quiet: bool,
+        pub buffer_size: usize,
+        pub rotate_size: Option<u64>,
+        pub rotate_interval: Option<Duration>,
+        pub rotate_keep: Option<usize>,
     }
 
     #[derive(Debug)]
@@ -65,6 +114,7 @@ mod platform {
         flush: bool,
         output_limit: Option<u64>,
         start_time: Instant,
+        buffer_size: usize,
     }
 
     struct LogFiles {
@@ -73,6 +123,10 @@ mod platform {
         log_out_file: Option<File>,
         log_io_file: Option<File>,
         timing_file: Option<File>,
+        // What has been written to the typescript since the files were opened, to know
+        // when to rotate them
+        chunk_bytes: u64,
+        chunk_start: Instant,
     }
 
     impl LogFiles {
@@ -93,6 +147,155 @@ mod platform {
             }
             Ok(())
         }
+
+        // Helper method to add an entry to the timing file, if there is one
+        fn write_timing(&mut self, entry: &Entry, config: &IoHandlerConfig) {
+            if let Some(ref mut file) = self.timing_file {
+                if let Err(e) = writeln!(file, "{}", entry.format(config.log_format)) {
+                    eprintln!("Failed to write to timing file: {}", e);
+                }
+                if config.flush {
+                    let _ = file.flush();
+                }
+            }
+        }
+
+        // Helper method to copy what was typed to the input logs
+        fn log_input(&mut self, data: &[u8], config: &IoHandlerConfig) {
+            for (file, name) in [
+                (&mut self.log_in_file, "input log file"),
+                (&mut self.log_io_file, "I/O log file"),
+            ] {
+                if let Some(file) = file {
+                    if let Err(e) = file.write_all(data) {
+                        eprintln!("Failed to write to {}: {}", name, e);
+                    }
+                    if config.flush {
+                        let _ = file.flush();
+                    }
+                }
+            }
+        }
+
+        // Helper method to copy the session's output to the typescript and output logs
+        fn log_output(&mut self, data: &[u8], config: &IoHandlerConfig) {
+            self.chunk_bytes += data.len() as u64;
+            for (file, name) in [
+                (Some(&mut self.out_file), "typescript file"),
+                (self.log_out_file.as_mut(), "output log file"),
+                (self.log_io_file.as_mut(), "I/O log file"),
+            ] {
+                if let Some(file) = file {
+                    if let Err(e) = file.write_all(data) {
+                        eprintln!("Failed to write to {}: {}", name, e);
+                    }
+                    if config.flush {
+                        let _ = file.flush();
+                    }
+                }
+            }
+        }
+
+        // Helper method to describe the session at the start of the logs. When they are
+        // rotated, the typescript also starts with a line which scriptreplay skips, so that
+        // each chunk can be replayed on its own.
+        fn write_header(&mut self, options: &ScriptOptions, config: &IoHandlerConfig) {
+            let headers = start_headers(options);
+            if options.rotate_size.is_some() || options.rotate_interval.is_some() {
+                let fields: Vec<String> = headers[1..]
+                    .iter()
+                    .map(|(name, value)| format!("{}=\"{}\"", name, value))
+                    .collect();
+                if let Err(e) = writeln!(
+                    self.out_file,
+                    "Script started on {} [{}]",
+                    headers[0].1,
+                    fields.join(" ")
+                ) {
+                    eprintln!("Failed to write to typescript file: {}", e);
+                }
+            }
+            if config.log_format == LogFormat::Advanced {
+                for (name, value) in headers {
+                    let entry = Entry::field(Stream::Info, Duration::ZERO, name, value);
+                    self.write_timing(&entry, config);
+                }
+            }
+        }
+
+        fn rotation_due(&self, options: &ScriptOptions) -> bool {
+            options
+                .rotate_size
+                .is_some_and(|size| self.chunk_bytes >= size)
+                || options
+                    .rotate_interval
+                    .is_some_and(|interval| self.chunk_start.elapsed() >= interval)
+        }
+
+        // Helper method to move the logs aside and start new ones
+        fn rotate(&mut self, options: &ScriptOptions, config: &IoHandlerConfig) {
+            // Whatever happens, the current files are used for another full chunk
+            self.chunk_bytes = 0;
+            self.chunk_start = Instant::now();
+
+            if let Err(e) = self.flush_all() {
+                eprintln!("Failed to flush log files: {}", e);
+            }
+            for path in log_paths(options) {
+                if let Err(e) = shift_old_logs(path, options.rotate_keep) {
+                    eprintln!("Failed to rotate {}: {}", path.display(), e);
+                    return;
+                }
+            }
+            match open_log_files(options) {
+                Ok(files) => *self = files,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return;
+                }
+            }
+            self.write_header(options, config);
+        }
+    }
+
+    fn log_paths(options: &ScriptOptions) -> impl Iterator<Item = &PathBuf> {
+        std::iter::once(&options.typescript_file).chain(
+            [
+                &options.log_in_file,
+                &options.log_out_file,
+                &options.log_io_file,
+                &options.log_timing_file,
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+
+    // Renames the log to path.1, path.1 to path.2 and so on, removing the old logs past
+    // the number to keep
+    fn shift_old_logs(path: &Path, keep: Option<usize>) -> io::Result<()> {
+        let numbered = |i: usize| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".{}", i));
+            PathBuf::from(name)
+        };
+        let mut count = 0;
+        while numbered(count + 1).exists() {
+            count += 1;
+        }
+
+        let keep = keep.unwrap_or(usize::MAX);
+        for i in keep.max(1)..=count {
+            std::fs::remove_file(numbered(i))?;
+        }
+        for i in (1..=count.min(keep.saturating_sub(1))).rev() {
+            std::fs::rename(numbered(i), numbered(i + 1))?;
+        }
+        if keep == 0 {
+            std::fs::remove_file(path)
+        } else {
+            std::fs::rename(path, numbered(1))
+        }
     }
 
     pub fn parse_size(size_str: &str) -> Result<u64, String> {
@@ -155,19 +358,73 @@ mod platform {
     }
 
     // Helper function to set up a signal handler safely
-    fn setup_signal_handler() -> io::Result<()> {
+    fn setup_signal_handler(
+        signal: libc::c_int,
+        handler: extern "C" fn(libc::c_int),
+    ) -> io::Result<()> {
         unsafe {
             let mut sa: libc::sigaction = std::mem::zeroed();
-            sa.sa_sigaction = handle_sigusr1 as usize;
+            sa.sa_sigaction = handler as usize;
             libc::sigemptyset(&mut sa.sa_mask);
             sa.sa_flags = 0;
-            if libc::sigaction(libc::SIGUSR1, &sa, std::ptr::null_mut()) < 0 {
+            if libc::sigaction(signal, &sa, std::ptr::null_mut()) < 0 {
                 return Err(io::Error::last_os_error());
             }
         }
         Ok(())
     }
 
+    // Writes the start and done messages, to stderr when our output goes to a pipe or a
+    // file, where they would end up mixed with the session's
+    fn announce(message: std::fmt::Arguments) {
+        if unsafe { libc::isatty(libc::STDOUT_FILENO) } != 0 {
+            println!("{}", message);
+        } else {
+            eprintln!("{}", message);
+        }
+    }
+
+    // Tells the session that its input has ended by typing the EOF character of its
+    // terminal. After a partial line, the first one only sends that line.
+    fn write_eof(master_fd: RawFd, mid_line: bool) -> io::Result<()> {
+        let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+        let eof = if unsafe { libc::tcgetattr(master_fd, &mut termios) } == 0 {
+            termios.c_cc[libc::VEOF]
+        } else {
+            CTRL_D
+        };
+        write_all(master_fd, &[eof; 2][..if mid_line { 2 } else { 1 }])
+    }
+
+    // The fields which describe the session, as written in the header entries of the
+    // advanced timing format
+    fn start_headers(options: &ScriptOptions) -> Vec<(&'static str, String)> {
+        let start_time = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%:z");
+        let mut headers = vec![("START_TIME", start_time.to_string())];
+
+        if let Ok(term) = std::env::var("TERM") {
+            headers.push(("TERM", term));
+        }
+        let tty = unsafe { libc::ttyname(libc::STDIN_FILENO) };
+        if !tty.is_null() {
+            let tty = unsafe { CStr::from_ptr(tty) };
+            headers.push(("TTY", tty.to_string_lossy().into_owned()));
+        }
+        if let Some(ws) = crate::pty::window_size() {
+            headers.push(("COLUMNS", ws.ws_col.to_string()));
+            headers.push(("LINES", ws.ws_row.to_string()));
+        }
+        headers.push(("SHELL", crate::pty::shell()));
+        if let Some(command) = &options.command {
+            headers.push(("COMMAND", command.clone()));
+        }
+        if let Some(path) = &options.log_timing_file {
+            headers.push(("TIMING_LOG", path.display().to_string()));
+        }
+
+        headers
+    }
+
     // Helper function to open and validate all log files
     fn open_log_files(options: &ScriptOptions) -> Result<LogFiles, String> {
         // Open typescript file
@@ -223,359 +480,289 @@ mod platform {
             log_out_file,
             log_io_file,
             timing_file,
+            chunk_bytes: 0,
+            chunk_start: Instant::now(),
         })
     }
 
     pub fn run_script(options: ScriptOptions) -> UResult<()> {
-        // Set up signal handler for SIGUSR1
-        if let Err(e) = setup_signal_handler() {
-            return Err(USimpleError::new(
-                1,
-                format!("Failed to set up signal handler for SIGUSR1: {}", e),
-            ));
+        let wakeup = wakeup_pipe().map_err(|e| {
+            USimpleError::new(1, format!("Failed to create a pipe for signals: {}", e))
+        })?;
+
+        // Set up signal handlers for SIGUSR1, SIGWINCH, SIGTERM, SIGHUP and SIGCHLD
+        for (signal, name, handler) in [
+            (
+                libc::SIGUSR1,
+                "SIGUSR1",
+                handle_sigusr1 as extern "C" fn(libc::c_int),
+            ),
+            (libc::SIGWINCH, "SIGWINCH", handle_sigwinch),
+            (libc::SIGTERM, "SIGTERM", handle_terminate),
+            (libc::SIGHUP, "SIGHUP", handle_terminate),
+            (libc::SIGCHLD, "SIGCHLD", handle_sigchld),
+        ] {
+            if let Err(e) = setup_signal_handler(signal, handler) {
+                return Err(USimpleError::new(
+                    1,
+                    format!("Failed to set up signal handler for {}: {}", name, e),
+                ));
+            }
         }
 
         // Open all log files
-        let log_files = match open_log_files(&options) {
+        let mut log_files = match open_log_files(&options) {
             Ok(files) => files,
             Err(e) => {
                 return Err(USimpleError::new(1, e));
             }
         };
 
-        // Get current terminal settings
-        let isatty = unsafe { libc::isatty(libc::STDIN_FILENO) } != 0;
-        let termios = if isatty {
-            let stdin_termios_result = {
-                let stdin = std::io::stdin();
-                termios::tcgetattr(stdin.as_fd())
-            };
-            match stdin_termios_result {
-                Ok(t) => Some(t),
-                Err(e) => {
-                    return Err(USimpleError::new(
-                        1,
-                        format!("Failed to get terminal attributes: {}", e),
-                    ));
-                }
-            }
-        } else {
-            None
+        let session = match crate::pty::spawn(options.command.as_deref(), options.echo_mode) {
+            Ok(session) => session,
+            Err(e) => return Err(USimpleError::new(1, e)),
         };
 
-        // Create a pseudoterminal
-        let pty = match openpty(None, None) {
-            Ok(pty) => pty,
-            Err(e) => {
-                return Err(USimpleError::new(
-                    1,
-                    format!("Failed to open pseudoterminal: {}", e),
-                ));
-            }
-        };
-
-        // Set terminal size
-        if isatty {
-            let mut ws: Winsize = unsafe { std::mem::zeroed() };
-            if unsafe { libc::ioctl(libc::STDIN_FILENO, libc::TIOCGWINSZ, &mut ws) } == 0 {
-                unsafe { libc::ioctl(pty.master.as_raw_fd(), libc::TIOCSWINSZ, &ws) };
-            }
-        }
-
-        // Configure echo mode for the slave PTY
-        if let Some(termios_settings) = termios.as_ref() {
-            let mut new_termios = termios_settings.clone();
-            match options.echo_mode {
-                EchoMode::Always => {
-                    new_termios.local_flags |= termios::LocalFlags::ECHO;
-                }
-                EchoMode::Never => {
-                    new_termios.local_flags &= !termios::LocalFlags::ECHO;
-                }
-                EchoMode::Auto => {
-                    // Default behavior - echo enabled for PTY
-                    if isatty {
-                        // If stdin is a terminal, disable echo to prevent double echo
-                        new_termios.local_flags &= !termios::LocalFlags::ECHO;
-                    } else {
-                        // If stdin is not a terminal, keep echo enabled
-                        new_termios.local_flags |= termios::LocalFlags::ECHO;
-                    }
-                }
-            }
-            if let Err(e) = termios::tcsetattr(&pty.slave, termios::SetArg::TCSANOW, &new_termios) {
-                return Err(USimpleError::new(
-                    1,
-                    format!("Failed to set terminal attributes: {}", e),
-                ));
-            }
-        }
-
         // Write start message
         if !options.quiet {
-            println!(
+            announce(format_args!(
                 "Script started, file is {}",
                 options.typescript_file.display()
-            );
+            ));
         }
 
         // Record start time
         let start_time = Instant::now();
 
-        // Fork a child process
-        match unsafe { fork() } {
-            Ok(ForkResult::Parent { child }) => {
-                // Parent process
-                // Close the slave end of the pty in the parent
-                // Use Rust's drop mechanism for safe resource cleanup
-                let master_pty = pty.master;
-                drop(pty.slave);
-
-                let io_handler_config = IoHandlerConfig {
-                    log_format: options.logging_format,
-                    flush: options.flush,
-                    output_limit: options.output_limit,
-                    start_time,
-                };
-
-                // Set up I/O handling
-                let result = handle_io(master_pty, child, log_files, io_handler_config);
-
-                // Write end message
-                if !options.quiet {
-                    println!("Script done, file is {}", options.typescript_file.display());
-                }
+        let io_handler_config = IoHandlerConfig {
+            log_format: options.logging_format,
+            flush: options.flush,
+            output_limit: options.output_limit,
+            start_time,
+            buffer_size: options.buffer_size,
+        };
 
-                // Return exit status if requested
-                if options.return_exit_status {
-                    match result {
-                        Ok(status) => {
-                            uucore::error::set_exit_code(status);
-                        }
-                        Err(e) => {
-                            return Err(USimpleError::new(1, format!("Error: {}", e)));
-                        }
-                    }
-                }
+        log_files.write_header(&options, &io_handler_config);
+
+        // Our terminal is restored when raw_mode is dropped, whichever way we leave
+        let raw_mode = match crate::pty::RawMode::enable() {
+            Ok(raw_mode) => raw_mode,
+            Err(e) => {
+                unsafe { libc::kill(session.child.as_raw(), libc::SIGTERM) };
+                let _ = crate::pty::wait(session.child);
+                return Err(USimpleError::new(1, e));
             }
-            Ok(ForkResult::Child) => {
-                // Child process
-                // Close the master end of the pty in the child
-                // Use Rust's drop for safety
-                let slave_pty = pty.slave;
-                drop(pty.master);
-
-                // Make the slave PTY the controlling terminal
-                unsafe {
-                    libc::setsid();
-                    libc::ioctl(slave_pty.as_raw_fd(), libc::TIOCSCTTY, 0);
-                }
+        };
 
-                // Redirect stdin, stdout, and stderr to the slave PTY
-                if let Err(e) = dup2(slave_pty.as_raw_fd(), 0) {
-                    eprintln!("Failed to redirect stdin: {}", e);
-                    unsafe { libc::_exit(1) };
-                }
-                if let Err(e) = dup2(slave_pty.as_raw_fd(), 1) {
-                    eprintln!("Failed to redirect stdout: {}", e);
-                    unsafe { libc::_exit(1) };
-                }
-                if let Err(e) = dup2(slave_pty.as_raw_fd(), 2) {
-                    eprintln!("Failed to redirect stderr: {}", e);
-                    unsafe { libc::_exit(1) };
-                }
+        // Set up I/O handling
+        let result = handle_io(
+            session.master,
+            session.child,
+            wakeup,
+            log_files,
+            io_handler_config,
+            &options,
+        );
+        drop(raw_mode);
+
+        // Write end message
+        if !options.quiet {
+            announce(format_args!(
+                "Script done, file is {}",
+                options.typescript_file.display()
+            ));
+        }
 
-                // The file descriptors have been duplicated to stdin/stdout/stderr
-                // We can now safely drop the original without closing the duplicated ones
-                drop(slave_pty);
-
-                // Execute the shell or command
-                let shell = std::env::var("SHELL").unwrap_or_else(|_| String::from("/bin/sh"));
-                if let Some(cmd) = options.command {
-                    let args = vec!["-c".to_string(), cmd];
-                    let c_shell = CString::new(shell.clone()).unwrap();
-                    let c_args: Vec<CString> = std::iter::once(CString::new(shell).unwrap())
-                        .chain(args.into_iter().map(|s| CString::new(s).unwrap()))
-                        .collect();
-                    let _ = execvp(&c_shell, &c_args);
-                    eprintln!("Failed to execute command: {}", io::Error::last_os_error());
-                } else {
-                    let c_shell = CString::new(shell.clone()).unwrap();
-                    let c_args = vec![CString::new(shell).unwrap()];
-                    let _ = execvp(&c_shell, &c_args);
-                    eprintln!("Failed to execute shell: {}", io::Error::last_os_error());
+        // Return exit status if requested
+        if options.return_exit_status {
+            match result {
+                Ok(status) => {
+                    uucore::error::set_exit_code(status);
+                }
+                Err(e) => {
+                    return Err(USimpleError::new(1, format!("Error: {}", e)));
                 }
-
-                // If we get here, exec failed
-                unsafe { libc::_exit(1) };
-            }
-            Err(e) => {
-                return Err(USimpleError::new(1, format!("Fork failed: {}", e)));
             }
         }
 
         Ok(())
     }
 
+    // Copies data between our terminal and the session until the child has exited and
+    // everything it wrote has been read. Signals are delivered through the self-pipe, so
+    // the loop only wakes up when there is something to do.
     fn handle_io(
         master_pty: OwnedFd,
         child_pid: Pid,
+        wakeup: OwnedFd,
         mut log_files: LogFiles,
         config: IoHandlerConfig,
+        options: &ScriptOptions,
     ) -> Result<i32, String> {
         let master_fd = master_pty.as_raw_fd();
+        let wakeup_fd = wakeup.as_raw_fd();
+        let stdin_fd = io::stdin().as_raw_fd();
+        let mut stdout = io::stdout();
         let mut total_bytes = 0u64;
         let mut last_time = config.start_time;
-        let mut buffer = [0u8; 1024];
-        let mut stdin_buffer = [0u8; 1024];
-
-        // Set up file descriptor guards to manage non-blocking mode
-        let stdin_fd = io::stdin().as_raw_fd();
+        let mut buffer = vec![0u8; config.buffer_size];
+
+        let mut stdin_open = true;
+        let mut input_mid_line = false;
+        let mut exit_status = None;
+
+        loop {
+            // Forward terminating signals to the child
+            let signal = TERMINATE_SIGNAL.swap(0, Ordering::SeqCst);
+            if signal != 0 && exit_status.is_some() {
+                // Nobody is left to forward it to, so stop waiting for background jobs
+                break;
+            }
+            if signal != 0 {
+                unsafe { libc::kill(child_pid.as_raw(), signal) };
+                if config.log_format == LogFormat::Advanced {
+                    let now = Instant::now();
+                    let entry =
+                        Entry::field(Stream::Signal, now - last_time, signal_name(signal), "");
+                    last_time = now;
+                    log_files.write_timing(&entry, &config);
+                }
+            }
 
-        // Used to ensure master_pty is dropped at the end of this function
-        // (even though we're only using its raw fd in this function)
-        let _master_pty_owner = master_pty;
+            // Apply terminal resizes to the session, the kernel sends SIGWINCH to its
+            // foreground process group
+            if WINDOW_RESIZED.swap(false, Ordering::SeqCst)
+                && let Some(ws) = crate::pty::window_size()
+            {
+                unsafe { libc::ioctl(master_fd, libc::TIOCSWINSZ, &ws) };
+                if config.log_format == LogFormat::Advanced {
+                    let now = Instant::now();
+                    let size = format!("ROWS={} COLS={}", ws.ws_row, ws.ws_col);
+                    let entry = Entry::field(Stream::Signal, now - last_time, "SIGWINCH", size);
+                    last_time = now;
+                    log_files.write_timing(&entry, &config);
+                }
+            }
 
-        // Prepare for select()
-        let mut exit_status = 0;
-        let mut child_exited = false;
+            // Check if we need to flush logs due to SIGUSR1
+            if FLUSH_LOGS.swap(false, Ordering::SeqCst) {
+                if let Err(e) = log_files.flush_all() {
+                    eprintln!("Failed to flush log files: {}", e);
+                }
+            }
 
-        while !child_exited {
-            let mut read_fds: libc::fd_set = unsafe { std::mem::zeroed() };
-            unsafe {
-                libc::FD_ZERO(&mut read_fds);
-                libc::FD_SET(stdin_fd, &mut read_fds);
-                libc::FD_SET(master_fd, &mut read_fds);
+            if exit_status.is_none() {
+                exit_status = crate::pty::try_wait(child_pid)
+                    .map_err(|e| format!("Failed to wait for the child: {}", e))?;
             }
 
-            // Wait for data or signals
-            let mut tv: libc::timeval = libc::timeval {
-                tv_sec: 1,
-                tv_usec: 0,
-            };
-            let select_result = unsafe {
-                libc::select(
-                    std::cmp::max(stdin_fd, master_fd) + 1,
-                    &mut read_fds,
-                    std::ptr::null_mut(),
-                    std::ptr::null_mut(),
-                    &mut tv,
-                )
+            // Once the child has exited, only the pseudoterminal is read, until reading it fails
+            // because background jobs of the session have closed it too
+            let mut fds = [
+                libc::pollfd {
+                    fd: master_fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: wakeup_fd,
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+                libc::pollfd {
+                    fd: if stdin_open && exit_status.is_none() {
+                        stdin_fd
+                    } else {
+                        -1
+                    },
+                    events: libc::POLLIN,
+                    revents: 0,
+                },
+            ];
+            let timeout = if exit_status.is_some() {
+                DRAIN_POLL_TIMEOUT_MS
+            } else {
+                -1
             };
-
-            if select_result < 0 {
+            let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout) };
+            if ready < 0 {
                 let err = io::Error::last_os_error();
                 if err.kind() == io::ErrorKind::Interrupted {
-                    // Check if child has exited
-                    let mut status: libc::c_int = 0;
-                    let wait_result =
-                        unsafe { libc::waitpid(child_pid.as_raw(), &mut status, libc::WNOHANG) };
-                    if wait_result > 0 {
-                        child_exited = true;
-                        if libc::WIFEXITED(status) {
-                            exit_status = libc::WEXITSTATUS(status);
-                        } else if libc::WIFSIGNALED(status) {
-                            exit_status = 128 + { libc::WTERMSIG(status) };
-                        }
-                    }
                     continue;
-                } else {
-                    return Err(format!("select() failed: {}", err));
                 }
+                return Err(format!("poll() failed: {}", err));
             }
-
-            // Check if child has exited
-            let mut status: libc::c_int = 0;
-            let wait_result =
-                unsafe { libc::waitpid(child_pid.as_raw(), &mut status, libc::WNOHANG) };
-            if wait_result > 0 {
-                child_exited = true;
-                if libc::WIFEXITED(status) {
-                    exit_status = libc::WEXITSTATUS(status);
-                } else if libc::WIFSIGNALED(status) {
-                    exit_status = 128 + libc::WTERMSIG(status);
-                }
+            if ready == 0 {
+                continue;
             }
+            let [master, wakeup, stdin] = fds.map(|fd| fd.revents);
 
-            // Check if we need to flush logs due to SIGUSR1
-            if FLUSH_LOGS.swap(false, Ordering::SeqCst) {
-                if let Err(e) = log_files.flush_all() {
-                    eprintln!("Failed to flush log files: {}", e);
-                }
+            // The signals themselves are handled at the top of the loop
+            if wakeup & libc::POLLIN != 0 {
+                while unsafe {
+                    libc::read(
+                        wakeup_fd,
+                        buffer.as_mut_ptr() as *mut libc::c_void,
+                        buffer.len(),
+                    )
+                } > 0
+                {}
             }
 
-            // Check if stdin has data
-            if unsafe { libc::FD_ISSET(stdin_fd, &read_fds) } {
+            if stdin & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0 {
                 match unsafe {
                     libc::read(
                         stdin_fd,
-                        stdin_buffer.as_mut_ptr() as *mut libc::c_void,
-                        stdin_buffer.len(),
+                        buffer.as_mut_ptr() as *mut libc::c_void,
+                        buffer.len(),
                     )
                 } {
                     n if n > 0 => {
-                        let now = Instant::now();
-                        let elapsed = now.duration_since(last_time);
-                        last_time = now;
-                        let n_usize = n as usize;
+                        let data = &buffer[..n as usize];
+                        input_mid_line = !data.ends_with(b"\n");
 
                         // Write to master PTY
-                        if let Err(e) = write_all(master_fd, &stdin_buffer[0..n_usize]) {
+                        if let Err(e) = write_all(master_fd, data) {
                             eprintln!("Failed to write to master PTY: {}", e);
                         }
-
-                        // Log input if requested
-                        if let Some(ref mut file) = log_files.log_in_file {
-                            if let Err(e) = file.write_all(&stdin_buffer[0..n_usize]) {
-                                eprintln!("Failed to write to input log file: {}", e);
-                            }
-                            if config.flush {
-                                let _ = file.flush();
-                            }
+                        // Rotating only when there is something to write never leaves an
+                        // empty chunk behind
+                        if log_files.rotation_due(options) {
+                            log_files.rotate(options, &config);
                         }
-
-                        // Log I/O if requested
-                        if let Some(ref mut file) = log_files.log_io_file {
-                            if let Err(e) = file.write_all(&stdin_buffer[0..n_usize]) {
-                                eprintln!("Failed to write to I/O log file: {}", e);
-                            }
-                            if config.flush {
-                                let _ = file.flush();
-                            }
-                        }
-
-                        // Write timing information if requested
-                        if let Some(ref mut file) = log_files.timing_file {
-                            let result = match config.log_format {
-                                LogFormat::Classic => {
-                                    writeln!(file, "{:.6} {}", elapsed.as_secs_f64(), n)
-                                }
-                                LogFormat::Advanced => {
-                                    writeln!(file, "I {:.6} {}", elapsed.as_secs_f64(), n)
-                                }
-                            };
-                            if let Err(e) = result {
-                                eprintln!("Failed to write to timing file: {}", e);
-                            }
-                            if config.flush {
-                                let _ = file.flush();
-                            }
+                        log_files.log_input(data, &config);
+
+                        // Write timing information if requested. The classic format has
+                        // no way to tell input from output, so it only records the output.
+                        if config.log_format == LogFormat::Advanced {
+                            let now = Instant::now();
+                            let entry = Entry::data(Stream::Input, now - last_time, data.len());
+                            last_time = now;
+                            log_files.write_timing(&entry, &config);
                         }
                     }
                     n if n < 0 => {
                         let err = io::Error::last_os_error();
-                        if err.kind() != io::ErrorKind::WouldBlock {
+                        if !matches!(
+                            err.kind(),
+                            io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+                        ) {
                             eprintln!("Failed to read from stdin: {}", err);
+                            stdin_open = false;
                         }
                     }
                     _ => {
-                        // EOF on stdin, but we continue as the child might still produce output
+                        // EOF on stdin, which is passed on to the session. We continue as
+                        // the child might still produce output.
+                        stdin_open = false;
+                        if let Err(e) = write_eof(master_fd, input_mid_line) {
+                            eprintln!("Failed to write to master PTY: {}", e);
+                        }
                     }
                 }
             }
 
-            // Check if master PTY has data
-            if unsafe { libc::FD_ISSET(master_fd, &read_fds) } {
+            if master & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0 {
                 match unsafe {
                     libc::read(
                         master_fd,
@@ -585,63 +772,24 @@ mod platform {
                 } {
                     n if n > 0 => {
                         let now = Instant::now();
-                        let elapsed = now.duration_since(last_time);
-                        last_time = now;
-                        let n_usize = n as usize;
+                        let data = &buffer[..n as usize];
 
                         // Write to stdout
-                        if let Err(e) = io::stdout().write_all(&buffer[0..n_usize]) {
+                        if let Err(e) = stdout.write_all(data).and_then(|()| stdout.flush()) {
                             eprintln!("Failed to write to stdout: {}", e);
                         }
-
-                        // Write to typescript file
-                        if let Err(e) = log_files.out_file.write_all(&buffer[0..n_usize]) {
-                            eprintln!("Failed to write to typescript file: {}", e);
-                        }
-                        if config.flush {
-                            let _ = log_files.out_file.flush();
-                        }
-
-                        // Log output if requested
-                        if let Some(ref mut file) = log_files.log_out_file {
-                            if let Err(e) = file.write_all(&buffer[0..n_usize]) {
-                                eprintln!("Failed to write to output log file: {}", e);
-                            }
-                            if config.flush {
-                                let _ = file.flush();
-                            }
-                        }
-
-                        // Log I/O if requested
-                        if let Some(ref mut file) = log_files.log_io_file {
-                            if let Err(e) = file.write_all(&buffer[0..n_usize]) {
-                                eprintln!("Failed to write to I/O log file: {}", e);
-                            }
-                            if config.flush {
-                                let _ = file.flush();
-                            }
+                        if log_files.rotation_due(options) {
+                            log_files.rotate(options, &config);
                         }
+                        log_files.log_output(data, &config);
 
                         // Write timing information if requested
-                        if let Some(ref mut file) = log_files.timing_file {
-                            let result = match config.log_format {
-                                LogFormat::Classic => {
-                                    writeln!(file, "{:.6} {}", elapsed.as_secs_f64(), n)
-                                }
-                                LogFormat::Advanced => {
-                                    writeln!(file, "O {:.6} {}", elapsed.as_secs_f64(), n)
-                                }
-                            };
-                            if let Err(e) = result {
-                                eprintln!("Failed to write to timing file: {}", e);
-                            }
-                            if config.flush {
-                                let _ = file.flush();
-                            }
-                        }
+                        let entry = Entry::data(Stream::Output, now - last_time, data.len());
+                        last_time = now;
+                        log_files.write_timing(&entry, &config);
 
                         // Update total bytes and check output limit
-                        total_bytes += n as u64;
+                        total_bytes += data.len() as u64;
                         if let Some(limit) = config.output_limit {
                             if total_bytes >= limit {
                                 // Kill the child process
@@ -653,9 +801,14 @@ mod platform {
                     }
                     n if n < 0 => {
                         let err = io::Error::last_os_error();
-                        if err.kind() != io::ErrorKind::WouldBlock {
-                            eprintln!("Failed to read from master PTY: {}", err);
-                            break;
+                        match err.raw_os_error() {
+                            Some(libc::EAGAIN | libc::EINTR) => (),
+                            // Every process on the slave side has closed it
+                            Some(libc::EIO) => break,
+                            _ => {
+                                eprintln!("Failed to read from master PTY: {}", err);
+                                break;
+                            }
                         }
                     }
                     _ => {
@@ -665,8 +818,37 @@ mod platform {
                 }
             }
         }
+        drop(master_pty);
+
+        // The loop may end before the child has been reaped
+        let exit_status = match exit_status {
+            Some(status) => status,
+            None => crate::pty::wait(child_pid)
+                .map_err(|e| format!("Failed to wait for the child: {}", e))?,
+        };
+
+        if config.log_format == LogFormat::Advanced {
+            let now = Instant::now();
+            let duration = now - config.start_time;
+            let entries = [
+                Entry::field(
+                    Stream::Info,
+                    now - last_time,
+                    "DURATION",
+                    format!("{:.6}", duration.as_secs_f64()),
+                ),
+                Entry::field(
+                    Stream::Info,
+                    Duration::ZERO,
+                    "EXIT_CODE",
+                    exit_status.to_string(),
+                ),
+            ];
+            for entry in &entries {
+                log_files.write_timing(entry, &config);
+            }
+        }
 
-        // File descriptor guards will restore original flags when dropped
         Ok(exit_status)
     }
 
@@ -730,6 +912,54 @@ mod platform {
             })
         });
 
+        let buffer_size = match matches
+            .get_one::<String>("buffer-size")
+            .map(|s| parse_size(s))
+        {
+            Some(Ok(size)) if size > 0 => size as usize,
+            Some(Ok(_)) => return Err(USimpleError::new(1, "buffer size must be positive")),
+            Some(Err(e)) => return Err(USimpleError::new(1, e)),
+            None => DEFAULT_BUFFER_SIZE,
+        };
+
+        let rotate_size = match matches
+            .get_one::<String>("rotate-size")
+            .map(|s| parse_size(s))
+        {
+            Some(Ok(0)) => return Err(USimpleError::new(1, "rotation size must be positive")),
+            Some(Ok(size)) => Some(size),
+            Some(Err(e)) => return Err(USimpleError::new(1, e)),
+            None => None,
+        };
+        let rotate_interval = match matches.get_one::<String>("rotate-time") {
+            Some(minutes) => match minutes
+                .parse::<f64>()
+                .ok()
+                .and_then(|minutes| Duration::try_from_secs_f64(minutes * 60.0).ok())
+            {
+                Some(interval) if !interval.is_zero() => Some(interval),
+                _ => {
+                    return Err(USimpleError::new(
+                        1,
+                        format!("Invalid number of minutes: {}", minutes),
+                    ));
+                }
+            },
+            None => None,
+        };
+        let rotate_keep = match matches.get_one::<String>("rotate-keep") {
+            Some(count) => match count.parse::<usize>() {
+                Ok(count) => Some(count),
+                Err(_) => {
+                    return Err(USimpleError::new(
+                        1,
+                        format!("Invalid number of files: {}", count),
+                    ));
+                }
+            },
+            None => None,
+        };
+
         let options = ScriptOptions {
             typescript_file,
             append: matches.get_flag("append"),
@@ -745,6 +975,10 @@ mod platform {
             logging_format,
             output_limit,
             quiet: matches.get_flag("quiet"),
+            buffer_size,
+            rotate_size,
+            rotate_interval,
+            rotate_keep,
         };
 
         // Handle deprecated -t option
@@ -881,6 +1115,36 @@ pub fn uu_app() -> Command {
                 .help("Limit the size of the typescript and timing files")
                 .value_parser(ValueParser::string()),
         )
+        .arg(
+            Arg::new("buffer-size")
+                .long("buffer-size")
+                .help(
+                    "Size of the buffer used to copy data to and from the session (default: 64KiB)",
+                )
+                .value_name("SIZE")
+                .value_parser(ValueParser::string()),
+        )
+        .arg(
+            Arg::new("rotate-size")
+                .long("rotate-size")
+                .help("Start new log files once the typescript has reached SIZE")
+                .value_name("SIZE")
+                .value_parser(ValueParser::string()),
+        )
+        .arg(
+            Arg::new("rotate-time")
+                .long("rotate-time")
+                .help("Start new log files every MINUTES")
+                .value_name("MINUTES")
+                .value_parser(ValueParser::string()),
+        )
+        .arg(
+            Arg::new("rotate-keep")
+                .long("rotate-keep")
+                .help("Keep at most COUNT rotated log files (default: all)")
+                .value_name("COUNT")
+                .value_parser(ValueParser::string()),
+        )
         .arg(
             Arg::new("quiet")
                 .short('q')
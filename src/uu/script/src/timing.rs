@@ -0,0 +1,309 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! The timing logs written by script and read back by scriptreplay.
+//!
+//! The classic format only records the output, one `<delay> <length>` line per chunk. The
+//! advanced format prefixes every line with the stream it belongs to:
+//!
+//! ```text
+//! I 0.000013 3
+//! O 0.000021 9
+//! S 1.500000 SIGWINCH ROWS=24 COLS=80
+//! H 0.000000 TERM xterm
+//! ```
+//!
+//! Header (`H`) entries describe the session: START_TIME, TERM, TTY, COLUMNS, LINES, SHELL,
+//! COMMAND and TIMING_LOG when it starts, DURATION and EXIT_CODE when it ends.
+
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Classic,
+    Advanced,
+}
+
+/// What a timing entry records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Input,
+    Output,
+    Signal,
+    Info,
+}
+
+impl Stream {
+    fn id(self) -> char {
+        match self {
+            Self::Input => 'I',
+            Self::Output => 'O',
+            Self::Signal => 'S',
+            Self::Info => 'H',
+        }
+    }
+
+    fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "I" => Some(Self::Input),
+            "O" => Some(Self::Output),
+            "S" => Some(Self::Signal),
+            "H" => Some(Self::Info),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Payload {
+    /// The number of bytes in the input or output log, for data streams.
+    Length(usize),
+    /// A name with a possibly empty value, e.g. "SIGWINCH" and "ROWS=24 COLS=80".
+    Field(String, String),
+}
+
+/// One line of a timing log. The delay is the time elapsed since the previous entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub stream: Stream,
+    pub delay: Duration,
+    pub payload: Payload,
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum TimingError {
+    #[error("missing {0}")]
+    MissingField(&'static str),
+
+    #[error("invalid delay '{0}'")]
+    InvalidDelay(String),
+
+    #[error("invalid length '{0}'")]
+    InvalidLength(String),
+
+    #[error("unknown entry type '{0}'")]
+    UnknownStream(String),
+}
+
+impl Entry {
+    pub fn data(stream: Stream, delay: Duration, length: usize) -> Self {
+        Self {
+            stream,
+            delay,
+            payload: Payload::Length(length),
+        }
+    }
+
+    pub fn field(stream: Stream, delay: Duration, name: &str, value: impl Into<String>) -> Self {
+        Self {
+            stream,
+            delay,
+            payload: Payload::Field(name.to_string(), value.into()),
+        }
+    }
+
+    /// Parses a line in either format, telling them apart by the stream identifier.
+    pub fn parse(line: &str) -> Result<Self, TimingError> {
+        let line = line.trim();
+        let (first, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        if first.is_empty() {
+            return Err(TimingError::MissingField("delay"));
+        }
+
+        if first.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            let (length, _) = split_field(rest, "length")?;
+            return Ok(Self::data(
+                Stream::Output,
+                parse_delay(first)?,
+                parse_length(length)?,
+            ));
+        }
+
+        let stream =
+            Stream::from_id(first).ok_or_else(|| TimingError::UnknownStream(first.into()))?;
+        let (delay, rest) = split_field(rest, "delay")?;
+        let delay = parse_delay(delay)?;
+        let payload = match stream {
+            Stream::Input | Stream::Output => {
+                let (length, _) = split_field(rest, "length")?;
+                Payload::Length(parse_length(length)?)
+            }
+            Stream::Signal | Stream::Info => {
+                let (name, value) = split_field(rest, "name")?;
+                Payload::Field(name.to_string(), value.trim().to_string())
+            }
+        };
+
+        Ok(Self {
+            stream,
+            delay,
+            payload,
+        })
+    }
+
+    /// Formats the entry as a line of a timing log, without the line terminator. Only data
+    /// entries can be written in the classic format, which has no stream identifier.
+    pub fn format(&self, format: LogFormat) -> String {
+        let delay = self.delay.as_secs_f64();
+        match (format, &self.payload) {
+            (LogFormat::Classic, Payload::Length(length)) => format!("{delay:.6} {length}"),
+            (_, Payload::Length(length)) => {
+                format!("{} {delay:.6} {length}", self.stream.id())
+            }
+            (_, Payload::Field(name, value)) if value.is_empty() => {
+                format!("{} {delay:.6} {name}", self.stream.id())
+            }
+            (_, Payload::Field(name, value)) => {
+                format!("{} {delay:.6} {name} {value}", self.stream.id())
+            }
+        }
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format(LogFormat::Advanced))
+    }
+}
+
+/// How long to wait for a recorded delay when playing a session back, sped up by `divisor`
+/// and capped at `maxdelay`.
+pub fn replay_delay(delay: Duration, divisor: f64, maxdelay: Option<Duration>) -> Duration {
+    // A tiny divisor can stretch the delay beyond what a Duration holds.
+    let delay = Duration::try_from_secs_f64(delay.as_secs_f64() / divisor).unwrap_or(Duration::MAX);
+    maxdelay.map_or(delay, |maxdelay| delay.min(maxdelay))
+}
+
+/// Parses a `--maxdelay` or `--target` value, a non-negative number of seconds.
+pub fn parse_seconds(s: &str) -> Result<Duration, String> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("invalid number of seconds: '{s}'"))
+}
+
+/// Parses a playback speed divisor, which must be a positive number.
+pub fn parse_divisor(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(divisor) if divisor.is_finite() && divisor > 0.0 => Ok(divisor),
+        _ => Err(format!("divisor must be a positive number: '{s}'")),
+    }
+}
+
+fn split_field<'a>(s: &'a str, name: &'static str) -> Result<(&'a str, &'a str), TimingError> {
+    let s = s.trim_start();
+    if s.is_empty() {
+        return Err(TimingError::MissingField(name));
+    }
+    Ok(s.split_once(char::is_whitespace).unwrap_or((s, "")))
+}
+
+fn parse_delay(s: &str) -> Result<Duration, TimingError> {
+    s.parse::<f64>()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| TimingError::InvalidDelay(s.into()))
+}
+
+fn parse_length(s: &str) -> Result<usize, TimingError> {
+    s.parse().map_err(|_| TimingError::InvalidLength(s.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_classic() {
+        assert_eq!(
+            Entry::parse("0.250000 12"),
+            Ok(Entry::data(Stream::Output, Duration::from_millis(250), 12))
+        );
+        assert_eq!(
+            Entry::parse("0.25"),
+            Err(TimingError::MissingField("length"))
+        );
+        assert_eq!(
+            Entry::parse("1.2.3 12"),
+            Err(TimingError::InvalidDelay("1.2.3".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_advanced() {
+        assert_eq!(
+            Entry::parse("I 1.5 3"),
+            Ok(Entry::data(Stream::Input, Duration::from_millis(1500), 3))
+        );
+        assert_eq!(
+            Entry::parse("S 0.1 SIGWINCH ROWS=24 COLS=80"),
+            Ok(Entry {
+                stream: Stream::Signal,
+                delay: Duration::from_millis(100),
+                payload: Payload::Field("SIGWINCH".into(), "ROWS=24 COLS=80".into()),
+            })
+        );
+        assert_eq!(
+            Entry::parse("H 0 TERM"),
+            Ok(Entry {
+                stream: Stream::Info,
+                delay: Duration::ZERO,
+                payload: Payload::Field("TERM".into(), String::new()),
+            })
+        );
+        assert_eq!(
+            Entry::parse("X 0.1 3"),
+            Err(TimingError::UnknownStream("X".into()))
+        );
+        assert_eq!(
+            Entry::parse("O 0.1 abc"),
+            Err(TimingError::InvalidLength("abc".into()))
+        );
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let entries = [
+            Entry::data(Stream::Output, Duration::from_micros(1234), 42),
+            Entry {
+                stream: Stream::Info,
+                delay: Duration::ZERO,
+                payload: Payload::Field("SHELL".into(), "/bin/sh".into()),
+            },
+        ];
+        for entry in entries {
+            assert_eq!(Entry::parse(&entry.to_string()), Ok(entry));
+        }
+
+        let entry = Entry::data(Stream::Output, Duration::from_millis(500), 7);
+        assert_eq!(entry.format(LogFormat::Classic), "0.500000 7");
+        assert_eq!(entry.format(LogFormat::Advanced), "O 0.500000 7");
+    }
+
+    #[test]
+    fn test_parse_replay_options() {
+        assert_eq!(parse_seconds("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_seconds("1.5"), Ok(Duration::from_millis(1500)));
+        assert!(parse_seconds("-1").is_err());
+        assert!(parse_seconds("inf").is_err());
+        assert!(parse_seconds("1e300").is_err());
+        assert_eq!(parse_divisor("2"), Ok(2.0));
+        assert!(parse_divisor("0").is_err());
+        assert!(parse_divisor("abc").is_err());
+    }
+
+    #[test]
+    fn test_replay_delay() {
+        let second = Duration::from_secs(1);
+        assert_eq!(replay_delay(second, 2.0, None), Duration::from_millis(500));
+        assert_eq!(
+            replay_delay(second, 1.0, Some(Duration::ZERO)),
+            Duration::ZERO
+        );
+        assert_eq!(replay_delay(second, 1e-300, None), Duration::MAX);
+        assert_eq!(replay_delay(second, 1e-300, Some(second)), second);
+    }
+}
@@ -0,0 +1,49 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+
+use uu_script::timing::TimingError;
+use uucore::error::strip_errno;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptliveError {
+    #[error("timing file not specified")]
+    MissingTiming,
+
+    #[error("input log file not specified")]
+    MissingLog,
+
+    #[error("cannot open {0}: {msg}", msg = strip_errno(.1))]
+    Open(String, io::Error),
+
+    #[error("cannot read {0}: {msg}", msg = strip_errno(.1))]
+    Read(String, io::Error),
+
+    #[error("timing file {0}: line {1}: {2}")]
+    Timing(String, usize, TimingError),
+
+    #[error("timing file {0}: no input recorded, the advanced format is required")]
+    NoInput(String),
+
+    #[error("{0}: unexpected end of file")]
+    UnexpectedEof(String),
+
+    #[error("{0}")]
+    Session(String),
+
+    #[error("failed to wait for the shell: {msg}", msg = strip_errno(.0))]
+    Wait(io::Error),
+}
+
+impl uucore::error::UError for ScriptliveError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        matches!(self, Self::MissingTiming | Self::MissingLog)
+    }
+}
@@ -0,0 +1,280 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+#[cfg(target_os = "linux")]
+mod errors;
+
+use clap::{Arg, ArgAction, Command, crate_version};
+#[cfg(target_os = "linux")]
+use std::{
+    fs::{self, File},
+    io::{self, BufReader, Read, Write},
+    thread,
+    time::Duration,
+};
+use uu_script::timing::{parse_divisor, parse_seconds};
+#[cfg(target_os = "linux")]
+use uu_script::{
+    pty::{self, EchoMode},
+    timing::{Entry, Payload, Stream, replay_delay},
+};
+use uucore::{error::UResult, format_usage, help_about, help_usage};
+
+#[cfg(target_os = "linux")]
+pub use crate::errors::ScriptliveError;
+
+const ABOUT: &str = help_about!("scriptlive.md");
+const USAGE: &str = help_usage!("scriptlive.md");
+
+mod options {
+    pub const COMMAND: &str = "command";
+    pub const DIVISOR: &str = "divisor";
+    pub const ECHO: &str = "echo";
+    pub const LOG_IN: &str = "log-in";
+    pub const LOG_IO: &str = "log-io";
+    pub const LOG_TIMING: &str = "log-timing";
+    pub const MAXDELAY: &str = "maxdelay";
+    // The historical command line: [timingfile] [logfile].
+    pub const FILES: &str = "files";
+}
+
+/// A chunk of recorded input, to be typed after waiting for `delay`.
+#[cfg(target_os = "linux")]
+struct Input {
+    delay: Duration,
+    /// Bytes of output to skip first, in a log of both input and output.
+    skip: usize,
+    length: usize,
+}
+
+/// Reads the input entries of a timing log, keeping track of the time spent on the others.
+#[cfg(target_os = "linux")]
+fn read_timing(path: &str, shared_log: bool) -> Result<Vec<Input>, ScriptliveError> {
+    let content =
+        fs::read_to_string(path).map_err(|err| ScriptliveError::Open(path.into(), err))?;
+
+    let mut inputs = Vec::new();
+    let mut delay = Duration::ZERO;
+    let mut skip = 0;
+
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry =
+            Entry::parse(line).map_err(|err| ScriptliveError::Timing(path.into(), i + 1, err))?;
+        delay += entry.delay;
+
+        match (entry.stream, entry.payload) {
+            (Stream::Input, Payload::Length(length)) => {
+                inputs.push(Input {
+                    delay,
+                    skip,
+                    length,
+                });
+                delay = Duration::ZERO;
+                skip = 0;
+            }
+            (Stream::Output, Payload::Length(length)) if shared_log => skip += length,
+            _ => (),
+        }
+    }
+
+    if inputs.is_empty() {
+        return Err(ScriptliveError::NoInput(path.into()));
+    }
+    Ok(inputs)
+}
+
+/// Reads the recorded input from the log, so that a truncated log is noticed before the
+/// shell is started.
+#[cfg(target_os = "linux")]
+fn read_input(path: &str, inputs: Vec<Input>) -> Result<Vec<(Duration, Vec<u8>)>, ScriptliveError> {
+    let log = File::open(path).map_err(|err| ScriptliveError::Open(path.into(), err))?;
+    let mut log = BufReader::new(log);
+
+    let mut chunks = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        let mut chunk = Vec::with_capacity(input.skip + input.length);
+        let wanted = (input.skip + input.length) as u64;
+        let read = (&mut log)
+            .take(wanted)
+            .read_to_end(&mut chunk)
+            .map_err(|err| ScriptliveError::Read(path.into(), err))?;
+        if (read as u64) < wanted {
+            return Err(ScriptliveError::UnexpectedEof(path.into()));
+        }
+        chunk.drain(..input.skip);
+        chunks.push((input.delay, chunk));
+    }
+    Ok(chunks)
+}
+
+/// Copies everything the session writes to stdout, until the pseudoterminal is closed.
+#[cfg(target_os = "linux")]
+fn forward_output(mut master: File) {
+    let mut stdout = io::stdout();
+    let mut buffer = [0u8; 4096];
+    // Reading fails with EIO once the slave side is closed.
+    while let Ok(n @ 1..) = master.read(&mut buffer) {
+        if stdout.write_all(&buffer[..n]).is_err() || stdout.flush().is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches: clap::ArgMatches = uu_app().try_get_matches_from(args)?;
+
+    // The timing file may be given with -t, which shifts the log file.
+    let mut files = matches
+        .get_many::<String>(options::FILES)
+        .into_iter()
+        .flatten()
+        .map(String::as_str);
+    let timing_path = matches
+        .get_one::<String>(options::LOG_TIMING)
+        .map(String::as_str)
+        .or_else(|| files.next())
+        .ok_or(ScriptliveError::MissingTiming)?;
+
+    let shared_log = matches.contains_id(options::LOG_IO);
+    let log_path = matches
+        .get_one::<String>(options::LOG_IO)
+        .or_else(|| matches.get_one::<String>(options::LOG_IN))
+        .map(String::as_str)
+        .or_else(|| files.next())
+        .ok_or(ScriptliveError::MissingLog)?;
+
+    let divisor = matches
+        .get_one::<f64>(options::DIVISOR)
+        .copied()
+        .unwrap_or(1.0);
+    let maxdelay = matches.get_one::<Duration>(options::MAXDELAY).copied();
+    let echo_mode = match matches.get_one::<String>(options::ECHO).map(String::as_str) {
+        Some("never") => EchoMode::Never,
+        Some("auto") => EchoMode::Auto,
+        _ => EchoMode::Always,
+    };
+
+    let inputs = read_timing(timing_path, shared_log)?;
+    let chunks = read_input(log_path, inputs)?;
+
+    println!(
+        ">>> scriptlive: Starting your typescript execution by {}.",
+        pty::shell()
+    );
+
+    let command = matches.get_one::<String>(options::COMMAND);
+    let session =
+        pty::spawn(command.map(String::as_str), echo_mode).map_err(ScriptliveError::Session)?;
+    let mut master = File::from(session.master);
+    let output = master
+        .try_clone()
+        .map_err(|err| ScriptliveError::Session(format!("Failed to open pseudoterminal: {err}")))?;
+    let output = thread::spawn(move || forward_output(output));
+
+    for (delay, chunk) in chunks {
+        thread::sleep(replay_delay(delay, divisor, maxdelay));
+        // The shell may have exited already, e.g. because the input contains "exit".
+        if master.write_all(&chunk).is_err() {
+            break;
+        }
+    }
+
+    // End the shell if the recorded input didn't.
+    let _ = master.write_all(b"\x04");
+
+    let status = pty::wait(session.child).map_err(ScriptliveError::Wait)?;
+    drop(master);
+    let _ = output.join();
+
+    println!("\n>>> scriptlive: Done.");
+    uucore::error::set_exit_code(status);
+    Ok(())
+}
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::LOG_TIMING)
+                .short('t')
+                .long(options::LOG_TIMING)
+                .visible_alias("timing")
+                .value_name("file")
+                .help("script timing log file"),
+        )
+        .arg(
+            Arg::new(options::LOG_IN)
+                .short('I')
+                .long(options::LOG_IN)
+                .value_name("file")
+                .help("script stdin log file"),
+        )
+        .arg(
+            Arg::new(options::LOG_IO)
+                .short('B')
+                .long(options::LOG_IO)
+                .value_name("file")
+                .help("script stdin and stdout log file")
+                .conflicts_with(options::LOG_IN),
+        )
+        .arg(
+            Arg::new(options::COMMAND)
+                .short('c')
+                .long(options::COMMAND)
+                .value_name("command")
+                .help("run command rather than interactive shell"),
+        )
+        .arg(
+            Arg::new(options::ECHO)
+                .short('E')
+                .long(options::ECHO)
+                .value_name("when")
+                .help("echo input in session (always, never, auto)")
+                .value_parser(["always", "never", "auto"])
+                .default_value("always"),
+        )
+        .arg(
+            Arg::new(options::DIVISOR)
+                .short('d')
+                .long(options::DIVISOR)
+                .value_name("num")
+                .help("speed up or slow down execution with time divisor")
+                .value_parser(parse_divisor),
+        )
+        .arg(
+            Arg::new(options::MAXDELAY)
+                .short('m')
+                .long(options::MAXDELAY)
+                .value_name("num")
+                .help("wait at maximum <num> seconds between updates")
+                .value_parser(parse_seconds),
+        )
+        .arg(
+            Arg::new(options::FILES)
+                .value_name("file")
+                .help("timing file and input log, as in older versions")
+                .num_args(0..=2)
+                .action(ArgAction::Append),
+        )
+}
+
+#[cfg(not(target_os = "linux"))]
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let _matches: clap::ArgMatches = uu_app().try_get_matches_from(args)?;
+
+    Err(uucore::error::USimpleError::new(
+        1,
+        "`scriptlive` is available only on Linux.",
+    ))
+}
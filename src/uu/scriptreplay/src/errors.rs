@@ -0,0 +1,43 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::io;
+
+use uu_script::timing::TimingError;
+use uucore::error::strip_errno;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ScriptreplayError {
+    #[error("timing file not specified")]
+    MissingTiming,
+
+    #[error("log file for the {0} stream not specified")]
+    MissingLog(&'static str),
+
+    #[error("cannot open {0}: {msg}", msg = strip_errno(.1))]
+    Open(String, io::Error),
+
+    #[error("cannot read {0}: {msg}", msg = strip_errno(.1))]
+    Read(String, io::Error),
+
+    #[error("timing file {0}: line {1}: {2}")]
+    Timing(String, usize, TimingError),
+
+    #[error("{0}: unexpected end of file")]
+    UnexpectedEof(String),
+
+    #[error("write error: {msg}", msg = strip_errno(.0))]
+    Write(io::Error),
+}
+
+impl uucore::error::UError for ScriptreplayError {
+    fn code(&self) -> i32 {
+        1
+    }
+
+    fn usage(&self) -> bool {
+        matches!(self, Self::MissingTiming | Self::MissingLog(_))
+    }
+}
@@ -0,0 +1 @@
+uucore::bin!(uu_scriptreplay);
@@ -0,0 +1,364 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+mod errors;
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::thread;
+use std::time::Duration;
+
+use clap::{Arg, ArgAction, ArgMatches, Command, crate_version};
+use uu_script::timing::{Entry, Payload, Stream, parse_divisor, parse_seconds, replay_delay};
+use uucore::error::{UResult, USimpleError};
+use uucore::{format_usage, help_about, help_usage};
+
+pub use crate::errors::ScriptreplayError;
+
+const ABOUT: &str = help_about!("scriptreplay.md");
+const USAGE: &str = help_usage!("scriptreplay.md");
+
+mod options {
+    pub const CM_IN: &str = "cm-in";
+    pub const DIVISOR: &str = "divisor";
+    pub const LOG_IN: &str = "log-in";
+    pub const LOG_IO: &str = "log-io";
+    pub const LOG_OUT: &str = "log-out";
+    pub const LOG_TIMING: &str = "log-timing";
+    pub const MAXDELAY: &str = "maxdelay";
+    pub const STREAM: &str = "stream";
+    pub const TARGET: &str = "target";
+    pub const TYPESCRIPT: &str = "typescript";
+    // The historical command line: [timingfile] [typescript] [divisor].
+    pub const FILES: &str = "files";
+}
+
+// Written as the first line of the typescript by older versions of script, and of each
+// chunk of rotated logs.
+const CLASSIC_HEADER: &[u8] = b"Script started on ";
+
+/// A log of the terminal data, read in step with the timing file.
+struct Log {
+    path: String,
+    reader: BufReader<File>,
+}
+
+impl Log {
+    fn open(path: &str) -> Result<Self, ScriptreplayError> {
+        let file = File::open(path).map_err(|err| ScriptreplayError::Open(path.into(), err))?;
+        let mut log = Self {
+            path: path.to_string(),
+            reader: BufReader::new(file),
+        };
+
+        let read_error = |err| ScriptreplayError::Read(path.into(), err);
+        if log
+            .reader
+            .fill_buf()
+            .map_err(read_error)?
+            .starts_with(CLASSIC_HEADER)
+        {
+            let mut header = Vec::new();
+            log.reader
+                .read_until(b'\n', &mut header)
+                .map_err(read_error)?;
+        }
+        Ok(log)
+    }
+
+    /// Copies the next `length` bytes of the log to `out`, or skips them.
+    fn copy(
+        &mut self,
+        length: usize,
+        out: Option<&mut dyn Write>,
+    ) -> Result<(), ScriptreplayError> {
+        let mut chunk = (&mut self.reader).take(length as u64);
+        let copied = match out {
+            Some(out) => io::copy(&mut chunk, out),
+            None => io::copy(&mut chunk, &mut io::sink()),
+        }
+        .map_err(|err| ScriptreplayError::Read(self.path.clone(), err))?;
+
+        if copied < length as u64 {
+            return Err(ScriptreplayError::UnexpectedEof(self.path.clone()));
+        }
+        Ok(())
+    }
+}
+
+/// The logs to take the data from. A log of both input and output takes precedence.
+#[derive(Default)]
+struct Logs {
+    io: Option<Log>,
+    input: Option<Log>,
+    output: Option<Log>,
+}
+
+impl Logs {
+    fn get(&mut self, stream: Stream) -> Option<&mut Log> {
+        if self.io.is_some() {
+            return self.io.as_mut();
+        }
+        match stream {
+            Stream::Input => self.input.as_mut(),
+            Stream::Output => self.output.as_mut(),
+            Stream::Signal | Stream::Info => None,
+        }
+    }
+}
+
+struct Settings {
+    stream: Stream,
+    mirror_input: bool,
+    divisor: f64,
+    maxdelay: Option<Duration>,
+    target: Duration,
+}
+
+impl Settings {
+    fn shows(&self, stream: Stream) -> bool {
+        stream == self.stream || (self.mirror_input && stream == Stream::Input)
+    }
+
+    /// How long to wait before showing an entry, once `elapsed` of the session has passed.
+    fn delay(&self, pending: Duration, elapsed: Duration) -> Duration {
+        // Everything before the target is shown at once.
+        if elapsed <= self.target {
+            return Duration::ZERO;
+        }
+        replay_delay(pending, self.divisor, self.maxdelay)
+    }
+}
+
+fn replay(
+    timing_path: &str,
+    logs: &mut Logs,
+    settings: &Settings,
+    out: &mut impl Write,
+) -> Result<(), ScriptreplayError> {
+    let content = fs::read_to_string(timing_path)
+        .map_err(|err| ScriptreplayError::Open(timing_path.into(), err))?;
+
+    // Delays are relative to the previous entry, including the ones which aren't shown.
+    let mut pending = Duration::ZERO;
+    let mut elapsed = Duration::ZERO;
+
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = Entry::parse(line)
+            .map_err(|err| ScriptreplayError::Timing(timing_path.into(), i + 1, err))?;
+        pending += entry.delay;
+        elapsed += entry.delay;
+
+        let shown = settings.shows(entry.stream);
+        if shown {
+            thread::sleep(settings.delay(pending, elapsed));
+            pending = Duration::ZERO;
+        }
+
+        match &entry.payload {
+            Payload::Length(length) => {
+                // Data of streams which aren't shown still has to be skipped in a shared log.
+                let Some(log) = logs.get(entry.stream) else {
+                    continue;
+                };
+                if shown {
+                    log.copy(*length, Some(out))?;
+                } else {
+                    log.copy(*length, None)?;
+                }
+            }
+            Payload::Field(name, value) if shown => {
+                writeln!(out, "{name} {value}").map_err(ScriptreplayError::Write)?;
+            }
+            Payload::Field(..) => (),
+        }
+
+        if shown {
+            out.flush().map_err(ScriptreplayError::Write)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn open_logs(
+    matches: &ArgMatches,
+    settings: &Settings,
+    typescript: Option<&str>,
+) -> Result<Logs, ScriptreplayError> {
+    let mut logs = Logs::default();
+
+    if let Some(path) = matches.get_one::<String>(options::LOG_IO) {
+        logs.io = Some(Log::open(path)?);
+        return Ok(logs);
+    }
+
+    if settings.shows(Stream::Input) {
+        let path = matches
+            .get_one::<String>(options::LOG_IN)
+            .ok_or(ScriptreplayError::MissingLog("input"))?;
+        logs.input = Some(Log::open(path)?);
+    }
+
+    if settings.shows(Stream::Output) {
+        let path = matches
+            .get_one::<String>(options::LOG_OUT)
+            .or_else(|| matches.get_one::<String>(options::TYPESCRIPT))
+            .map(String::as_str)
+            .or(typescript)
+            .unwrap_or("typescript");
+        logs.output = Some(Log::open(path)?);
+    }
+
+    Ok(logs)
+}
+
+#[uucore::main]
+pub fn uumain(args: impl uucore::Args) -> UResult<()> {
+    let matches: ArgMatches = uu_app().try_get_matches_from(args)?;
+
+    // The timing file may be given with -t, which shifts the other positional arguments.
+    let mut files = matches
+        .get_many::<String>(options::FILES)
+        .into_iter()
+        .flatten()
+        .map(String::as_str);
+    let timing_path = matches
+        .get_one::<String>(options::LOG_TIMING)
+        .map(String::as_str)
+        .or_else(|| files.next())
+        .ok_or(ScriptreplayError::MissingTiming)?;
+    let typescript = files.next();
+    let divisor_arg = files
+        .next()
+        .map(parse_divisor)
+        .transpose()
+        .map_err(|err| USimpleError::new(1, err))?;
+
+    let stream = match matches
+        .get_one::<String>(options::STREAM)
+        .map(String::as_str)
+    {
+        Some("in") => Stream::Input,
+        Some("signal") => Stream::Signal,
+        Some("info") => Stream::Info,
+        _ => Stream::Output,
+    };
+    let divisor = matches
+        .get_one::<f64>(options::DIVISOR)
+        .copied()
+        .or(divisor_arg)
+        .unwrap_or(1.0);
+
+    let settings = Settings {
+        stream,
+        mirror_input: matches.get_flag(options::CM_IN),
+        divisor,
+        maxdelay: matches.get_one::<Duration>(options::MAXDELAY).copied(),
+        target: matches
+            .get_one::<Duration>(options::TARGET)
+            .copied()
+            .unwrap_or_default(),
+    };
+
+    let mut logs = open_logs(&matches, &settings, typescript)?;
+    replay(timing_path, &mut logs, &settings, &mut io::stdout().lock())?;
+    Ok(())
+}
+
+pub fn uu_app() -> Command {
+    Command::new(uucore::util_name())
+        .version(crate_version!())
+        .about(ABOUT)
+        .override_usage(format_usage(USAGE))
+        .infer_long_args(true)
+        .arg(
+            Arg::new(options::LOG_TIMING)
+                .short('t')
+                .long(options::LOG_TIMING)
+                .visible_alias("timing")
+                .value_name("file")
+                .help("script timing log file"),
+        )
+        .arg(
+            Arg::new(options::LOG_IN)
+                .short('I')
+                .long(options::LOG_IN)
+                .value_name("file")
+                .help("script stdin log file"),
+        )
+        .arg(
+            Arg::new(options::LOG_OUT)
+                .short('O')
+                .long(options::LOG_OUT)
+                .value_name("file")
+                .help("script stdout log file (default)"),
+        )
+        .arg(
+            Arg::new(options::LOG_IO)
+                .short('B')
+                .long(options::LOG_IO)
+                .value_name("file")
+                .help("script stdin and stdout log file")
+                .conflicts_with_all([options::LOG_IN, options::LOG_OUT, options::TYPESCRIPT]),
+        )
+        .arg(
+            Arg::new(options::TYPESCRIPT)
+                .short('s')
+                .long(options::TYPESCRIPT)
+                .value_name("file")
+                .help("deprecated alias to -O")
+                .conflicts_with(options::LOG_OUT),
+        )
+        .arg(
+            Arg::new(options::DIVISOR)
+                .short('d')
+                .long(options::DIVISOR)
+                .value_name("num")
+                .help("speed up or slow down execution with time divisor")
+                .value_parser(parse_divisor),
+        )
+        .arg(
+            Arg::new(options::MAXDELAY)
+                .short('m')
+                .long(options::MAXDELAY)
+                .value_name("num")
+                .help("wait at maximum <num> seconds between updates")
+                .value_parser(parse_seconds),
+        )
+        .arg(
+            Arg::new(options::TARGET)
+                .long(options::TARGET)
+                .value_name("mark")
+                .help("replay the first <mark> seconds of the session at once")
+                .value_parser(parse_seconds),
+        )
+        .arg(
+            Arg::new(options::STREAM)
+                .short('x')
+                .long(options::STREAM)
+                .value_name("name")
+                .help("stream type (out, in, signal or info)")
+                .value_parser(["out", "in", "signal", "info"])
+                .default_value("out"),
+        )
+        .arg(
+            Arg::new(options::CM_IN)
+                .short('c')
+                .long(options::CM_IN)
+                .help("mirror the recorded input along with the output")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new(options::FILES)
+                .value_name("file")
+                .help("timing file, typescript and divisor, as in older versions")
+                .num_args(0..=3)
+                .action(ArgAction::Append),
+        )
+}
@@ -0,0 +1,193 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Helpers for the utilities which format their output the way libsmartcols does.
+
+mod table;
+
+use serde::Serialize;
+
+pub use crate::table::{Row, Table};
+
+/// A column which can be selected with `--output`, named by its header.
+pub trait TableColumn: Copy + PartialEq + 'static {
+    /// Every column, in the order they are listed in the help.
+    const ALL: &'static [Self];
+
+    fn header_text(&self) -> &'static str;
+
+    fn get_help(&self) -> &'static str;
+
+    fn get_float_right(&self) -> bool;
+
+    fn value_kind(&self) -> ValueKind {
+        ValueKind::String
+    }
+
+    // Minimum width of the column in tables, which keeps it steady when the values are short
+    fn get_width_hint(&self) -> usize {
+        0
+    }
+}
+
+// How a column is typed in JSON output, which also decides how it is aligned in tables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueKind {
+    String,
+    Number,
+    Boolean,
+    Size,
+    Array,
+}
+
+/// Parses a comma separated list of column names, ignoring case. A leading '+' appends the
+/// columns to the `default` ones, e.g. `--output +SIZE`.
+pub fn parse_columns<C: TableColumn>(list: &str, default: &[C]) -> Result<Vec<C>, String> {
+    let (mut cols, list) = match list.strip_prefix('+') {
+        Some(list) => (Vec::from(default), list),
+        None => (Vec::new(), list),
+    };
+
+    for name in list.split(',') {
+        let col = C::ALL
+            .iter()
+            .find(|col| col.header_text().eq_ignore_ascii_case(name))
+            .ok_or_else(|| format!("unknown column: {}", name))?;
+        cols.push(*col);
+    }
+
+    Ok(cols)
+}
+
+/// Describes every column, to be shown after the help of the options.
+pub fn columns_help<C: TableColumn>() -> String {
+    let width = C::ALL
+        .iter()
+        .map(|col| col.header_text().len())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<_> = C::ALL
+        .iter()
+        .map(|col| format!("{:>width$}  {}", col.header_text(), col.get_help()))
+        .collect();
+    format!("Available output columns:\n{}", lines.join("\n"))
+}
+
+// Formats sizes the way the rest of util-linux does, e.g. "512B", "4K" or "1.5M"
+pub fn size_to_human_string(bytes: u64) -> String {
+    const LETTERS: [char; 7] = ['B', 'K', 'M', 'G', 'T', 'P', 'E'];
+
+    let exp = (1..LETTERS.len())
+        .take_while(|&i| bytes >= 1 << (10 * i))
+        .last()
+        .unwrap_or(0);
+
+    if exp == 0 {
+        return format!("{}B", bytes);
+    }
+
+    let unit = 1_u64 << (10 * exp);
+    let mut whole = bytes / unit;
+    let mut tenths = ((bytes % unit) as f64 / unit as f64 * 10.0).round() as u64;
+    if tenths == 10 {
+        whole += 1;
+        tenths = 0;
+    }
+
+    if tenths == 0 {
+        format!("{}{}", whole, LETTERS[exp])
+    } else {
+        format!("{}.{}{}", whole, tenths, LETTERS[exp])
+    }
+}
+
+/// Prints `table` as JSON, indented like libsmartcols does.
+pub fn print_json(table: &impl Serialize) {
+    let mut buffer = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b"   ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+    table.serialize(&mut serializer).unwrap();
+
+    println!("{}", join_siblings(&String::from_utf8(buffer).unwrap()));
+}
+
+// Remove newlines between '}, {', at every level of the tree
+fn join_siblings(json: &str) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for line in json.lines() {
+        match lines.last_mut() {
+            Some(last) if last.ends_with("},") && line.trim_start() == "{" => last.push('{'),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    enum Column {
+        Name,
+        Size,
+    }
+
+    impl TableColumn for Column {
+        const ALL: &'static [Self] = &[Self::Name, Self::Size];
+
+        fn header_text(&self) -> &'static str {
+            match self {
+                Self::Name => "NAME",
+                Self::Size => "SIZE",
+            }
+        }
+
+        fn get_help(&self) -> &'static str {
+            match self {
+                Self::Name => "name",
+                Self::Size => "size",
+            }
+        }
+
+        fn get_float_right(&self) -> bool {
+            self == &Self::Size
+        }
+    }
+
+    #[test]
+    fn test_size_to_human_string() {
+        assert_eq!(size_to_human_string(512), "512B");
+        assert_eq!(size_to_human_string(4096), "4K");
+        assert_eq!(size_to_human_string(1536 * 1024), "1.5M");
+        assert_eq!(size_to_human_string(1 << 30), "1G");
+    }
+
+    #[test]
+    fn test_parse_columns() {
+        let default = &[Column::Name];
+        assert_eq!(
+            parse_columns("size,Name", default),
+            Ok(vec![Column::Size, Column::Name])
+        );
+        assert_eq!(
+            parse_columns("+SIZE", default),
+            Ok(vec![Column::Name, Column::Size])
+        );
+        assert_eq!(
+            parse_columns("NAME,FOO", default),
+            Err("unknown column: FOO".to_string())
+        );
+    }
+
+    #[test]
+    fn test_join_siblings() {
+        let json = "[\n   {\n      \"children\": [\n         {\n         },\n         {\n         }\n      ]\n   },\n   {\n   }\n]";
+        assert_eq!(
+            join_siblings(json),
+            "[\n   {\n      \"children\": [\n         {\n         },{\n         }\n      ]\n   },{\n   }\n]"
+        );
+    }
+}
@@ -0,0 +1,291 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use serde_json::Value;
+
+use crate::{TableColumn, ValueKind};
+
+/// A line of the output with the values of all columns, and the lines below it in the tree.
+pub struct Row {
+    pub values: Vec<Option<String>>,
+    pub children: Vec<Row>,
+}
+
+/// How rows are printed as a table, raw, as key="value" pairs or as JSON.
+pub struct Table<'a, C> {
+    pub cols: &'a [C],
+    pub noheadings: bool,
+    /// The column the tree is drawn in, the children are listed after their parent without it.
+    pub tree: Option<C>,
+    pub ascii: bool,
+    /// Sizes are only numbers in JSON when they are printed in bytes.
+    pub bytes: bool,
+}
+
+struct TreeSymbols {
+    branch: &'static str,
+    last_branch: &'static str,
+    vertical: &'static str,
+    empty: &'static str,
+}
+
+const UTF8_SYMBOLS: TreeSymbols = TreeSymbols {
+    branch: "├─",
+    last_branch: "└─",
+    vertical: "│ ",
+    empty: "  ",
+};
+
+const ASCII_SYMBOLS: TreeSymbols = TreeSymbols {
+    branch: "|-",
+    last_branch: "`-",
+    vertical: "| ",
+    empty: "  ",
+};
+
+/// A row in the order it is printed, along with the tree drawn before its value in the tree column.
+struct Line<'a> {
+    row: &'a Row,
+    prefix: String,
+    // Drawn in front of the extra lines of a row with multi-line values.
+    continuation: String,
+}
+
+fn flatten<'a>(
+    rows: &'a [Row],
+    indent: Option<&str>,
+    symbols: Option<&TreeSymbols>,
+    lines: &mut Vec<Line<'a>>,
+) {
+    for (i, row) in rows.iter().enumerate() {
+        let (prefix, child_indent, continuation) = match (symbols, indent) {
+            (Some(symbols), Some(indent)) => {
+                let is_last = i == rows.len() - 1;
+                let (branch, child_indent) = if is_last {
+                    (symbols.last_branch, format!("{indent}{}", symbols.empty))
+                } else {
+                    (symbols.branch, format!("{indent}{}", symbols.vertical))
+                };
+                let continuation = if row.children.is_empty() {
+                    child_indent.clone()
+                } else {
+                    format!("{child_indent}{}", symbols.vertical)
+                };
+                (format!("{indent}{branch}"), child_indent, continuation)
+            }
+            (Some(symbols), None) if !row.children.is_empty() => {
+                (String::new(), String::new(), symbols.vertical.to_string())
+            }
+            _ => (String::new(), String::new(), String::new()),
+        };
+
+        lines.push(Line {
+            row,
+            prefix,
+            continuation,
+        });
+        flatten(&row.children, Some(&child_indent), symbols, lines);
+    }
+}
+
+// Replaces characters which would break the output format with "\xHH" escapes
+fn escape(value: &str, is_special: impl Fn(char) -> bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_control() || is_special(c) {
+            escaped.push_str(&format!("\\x{:02x}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+impl<C: TableColumn> Table<'_, C> {
+    pub fn print_table(&self, rows: &[Row]) {
+        let symbols = match self.tree {
+            Some(_) if self.ascii => Some(&ASCII_SYMBOLS),
+            Some(_) => Some(&UTF8_SYMBOLS),
+            None => None,
+        };
+        let mut lines = Vec::new();
+        flatten(rows, None, symbols, &mut lines);
+
+        // Each row takes as many lines as its longest multi-line cell.
+        let physical_lines: Vec<Vec<String>> = lines
+            .iter()
+            .flat_map(|line| {
+                let cells: Vec<Vec<&str>> = line
+                    .row
+                    .values
+                    .iter()
+                    .map(|value| value.as_deref().unwrap_or_default().split('\n').collect())
+                    .collect();
+                let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+                (0..height).map(move |n| {
+                    self.cols
+                        .iter()
+                        .zip(&cells)
+                        .map(|(col, cell)| {
+                            let value = cell.get(n).copied().unwrap_or_default();
+                            match n {
+                                _ if self.tree != Some(*col) => value.to_string(),
+                                0 => format!("{}{}", line.prefix, value),
+                                _ => format!("{}{}", line.continuation, value),
+                            }
+                        })
+                        .collect()
+                })
+            })
+            .collect();
+
+        let mut column_widths: Vec<_> = self
+            .cols
+            .iter()
+            .map(|col| {
+                let header_width = if self.noheadings {
+                    0
+                } else {
+                    col.header_text().len()
+                };
+                header_width.max(col.get_width_hint())
+            })
+            .collect();
+
+        for line in &physical_lines {
+            for (i, value) in line.iter().enumerate() {
+                column_widths[i] = column_widths[i].max(value.chars().count());
+            }
+        }
+
+        let format_line = |values: &[String]| {
+            let cells: Vec<_> = self
+                .cols
+                .iter()
+                .zip(values)
+                .enumerate()
+                .map(|(i, (col, value))| {
+                    let width = column_widths[i];
+                    if col.get_float_right() {
+                        format!("{:>width$}", value)
+                    } else {
+                        format!("{:<width$}", value)
+                    }
+                })
+                .collect();
+            cells.join(" ").trim_end().to_string()
+        };
+
+        if !self.noheadings {
+            let headers: Vec<_> = self
+                .cols
+                .iter()
+                .map(|col| col.header_text().to_string())
+                .collect();
+            println!("{}", format_line(&headers));
+        }
+
+        for line in physical_lines {
+            println!("{}", format_line(&line));
+        }
+    }
+
+    pub fn print_raw(&self, rows: &[Row]) {
+        let mut lines = Vec::new();
+        flatten(rows, None, None, &mut lines);
+
+        if !self.noheadings {
+            let headers: Vec<_> = self.cols.iter().map(|col| col.header_text()).collect();
+            println!("{}", headers.join(" "));
+        }
+
+        for line in lines {
+            let values: Vec<_> = line
+                .row
+                .values
+                .iter()
+                .map(|value| escape(value.as_deref().unwrap_or_default(), |c| " \\".contains(c)))
+                .collect();
+            println!("{}", values.join(" "));
+        }
+    }
+
+    pub fn print_pairs(&self, rows: &[Row]) {
+        let mut lines = Vec::new();
+        flatten(rows, None, None, &mut lines);
+
+        for line in lines {
+            let pairs: Vec<_> = self
+                .cols
+                .iter()
+                .zip(&line.row.values)
+                .map(|(col, value)| {
+                    let value = escape(value.as_deref().unwrap_or_default(), |c| {
+                        "\"\\`$".contains(c)
+                    });
+                    // Shells don't allow some of the characters of the column names in variables.
+                    let name = col
+                        .header_text()
+                        .replace(['-', ':'], "_")
+                        .replace('%', "PCT");
+                    format!("{}=\"{}\"", name, value)
+                })
+                .collect();
+            println!("{}", pairs.join(" "));
+        }
+    }
+
+    /// Prints the rows as the array `name` of a JSON object.
+    pub fn print_json(&self, name: &str, rows: &[Row]) {
+        crate::print_json(&serde_json::json!({ name: self.json_records(rows) }));
+    }
+
+    fn json_records(&self, rows: &[Row]) -> Vec<Value> {
+        let mut records = Vec::new();
+
+        for row in rows {
+            let mut record: serde_json::Map<_, _> = self
+                .cols
+                .iter()
+                .zip(&row.values)
+                .map(|(col, value)| {
+                    let key = col.header_text().to_lowercase();
+                    (key, self.get_json_value(value, col))
+                })
+                .collect();
+
+            if self.tree.is_some() {
+                if !row.children.is_empty() {
+                    record.insert("children".into(), self.json_records(&row.children).into());
+                }
+                records.push(Value::Object(record));
+            } else {
+                records.push(Value::Object(record));
+                records.extend(self.json_records(&row.children));
+            }
+        }
+
+        records
+    }
+
+    // Same as the value in a table, but typed the way libsmartcols does for each column
+    fn get_json_value(&self, value: &Option<String>, col: &C) -> Value {
+        let Some(value) = value else {
+            return match col.value_kind() {
+                ValueKind::Array => Value::Array(vec![Value::Null]),
+                _ => Value::Null,
+            };
+        };
+
+        match col.value_kind() {
+            ValueKind::Boolean => (value == "1").into(),
+            ValueKind::Number => value.parse::<u64>().map_or(Value::Null, Value::from),
+            ValueKind::Size if self.bytes => value.parse::<u64>().map_or(Value::Null, Value::from),
+            ValueKind::Array => value.split('\n').collect::<Vec<_>>().into(),
+            _ => value.as_str().into(),
+        }
+    }
+}
@@ -21,6 +21,15 @@ fn test_report_mutually_exclusive_with_others() {
         .stderr_contains("the argument '--report' cannot be used with '--getalignoff'");
 }
 
+#[test]
+fn test_device_required_without_report() {
+    new_ucmd!()
+        .arg("--getsz")
+        .fails()
+        .code_is(1)
+        .stderr_contains("required arguments were not provided");
+}
+
 #[cfg(target_os = "linux")]
 mod linux {
     use crate::common::util::TestScenario;
@@ -52,6 +61,145 @@ mod linux {
                     .unwrap(),
             );
     }
+
+    #[test]
+    fn test_report_prints_header_once() {
+        new_ucmd!()
+            .arg("--report")
+            .arg("/dev/null")
+            .arg("/non/existing")
+            .fails()
+            .code_is(1)
+            .stdout_is("RO    RA   SSZ   BSZ        StartSec            Size   Device\n")
+            .stderr_contains("cannot open /non/existing: No such file or directory");
+    }
+
+    #[test]
+    fn test_report_without_devices() {
+        new_ucmd!()
+            .arg("--report")
+            .run()
+            .stdout_contains("RO    RA   SSZ   BSZ        StartSec            Size   Device\n");
+    }
+
+    #[test]
+    fn test_json_requires_report() {
+        new_ucmd!()
+            .arg("--json")
+            .arg("--getsz")
+            .arg("/dev/null")
+            .fails()
+            .code_is(1);
+    }
+
+    #[test]
+    fn test_report_loop_device() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        new_ucmd!()
+            .arg("--report")
+            .arg(device.path_str())
+            .succeeds()
+            .stdout_matches(
+                &Regex::new(&format!(
+                    "^RO    RA   SSZ   BSZ        StartSec            Size   Device\n\
+                     rw +\\d+ +512 +\\d+ +0 +1048576   {}\n$",
+                    device.path_str()
+                ))
+                .unwrap(),
+            );
+    }
+
+    #[test]
+    fn test_report_json_loop_device() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        let result = new_ucmd!()
+            .arg("--report")
+            .arg("--json")
+            .arg(device.path_str())
+            .arg("/non/existing")
+            .fails();
+
+        let json: serde_json::Value = serde_json::from_str(result.stdout_str()).unwrap();
+        let reports = json["blockdevices"].as_array().unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0]["ro"], false);
+        assert_eq!(reports[0]["ssz"], 512);
+        assert_eq!(reports[0]["startsec"], 0);
+        assert_eq!(reports[0]["size"], 1024 * 1024);
+        assert_eq!(reports[0]["device"], device.path_str());
+    }
+
+    #[test]
+    fn test_get_attributes_on_loop_device() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        new_ucmd!()
+            .args(&["--getsz", "--getsize64", "--getss", "--getzonesz"])
+            .arg(device.path_str())
+            .succeeds()
+            .stdout_is("2048\n1048576\n512\n0\n");
+
+        let number = Regex::new("^\\d+\n$").unwrap();
+        for flag in [
+            "--getalignoff",
+            "--getbsz",
+            "--getdiscardgran",
+            "--getdiscardmax",
+            "--getdiscardzeroes",
+            "--getdiskseq",
+            "--getfra",
+            "--getiomin",
+            "--getioopt",
+            "--getmaxsect",
+            "--getnr_zones",
+            "--getpbsz",
+            "--getra",
+            "--getro",
+            "--getsize",
+        ] {
+            new_ucmd!()
+                .arg(flag)
+                .arg(device.path_str())
+                .succeeds()
+                .stdout_matches(&number);
+        }
+    }
+
+    #[test]
+    fn test_set_attributes_on_loop_device() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        new_ucmd!()
+            .args(&["-v", "--setro", "--getro", "--setrw", "--getro"])
+            .arg(device.path_str())
+            .succeeds()
+            .stdout_is(
+                "set read-only succeeded.\n\
+                 get read-only: 1\n\
+                 set read-write succeeded.\n\
+                 get read-only: 0\n",
+            );
+
+        new_ucmd!()
+            .args(&["--setra", "512", "--getra", "--setbsz", "1024", "--getbsz"])
+            .args(&["--flushbufs"])
+            .arg(device.path_str())
+            .succeeds()
+            .stdout_is("512\n1024\n");
+    }
+
+    #[test]
+    fn test_sysfs_attribute_on_non_block_device() {
+        new_ucmd!()
+            .arg("-v")
+            .arg("--getdiscardgran")
+            .arg("/dev/null")
+            .fails()
+            .code_is(1)
+            .stdout_is("get discard granularity in bytes failed.\n");
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
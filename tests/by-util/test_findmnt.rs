@@ -0,0 +1,244 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::common::util::TestScenario;
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_unknown_column() {
+    new_ucmd!()
+        .args(&["-o", "TARGET,FOO"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("unknown column: FOO");
+}
+
+#[test]
+fn test_missing_tab_file() {
+    new_ucmd!()
+        .args(&["-F", "missing"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("findmnt: can't read missing: No such file or directory");
+}
+
+fn mountinfo_test_with_args(args: &[&str], expected_output: &str) {
+    new_ucmd!()
+        .args(&["-F", "mountinfo"])
+        .args(args)
+        .succeeds()
+        .no_stderr()
+        .stdout_is(expected_output);
+}
+
+#[test]
+fn test_tree() {
+    mountinfo_test_with_args(
+        &[],
+        concat!(
+            "TARGET                   SOURCE                     FSTYPE     OPTIONS\n",
+            "/                        /dev/mapper/vg-root        ext4       rw,relatime,errors=remount-ro\n",
+            "├─/proc                  proc                       proc       rw,nosuid,nodev,noexec,relatime\n",
+            "├─/sys                   sysfs                      sysfs      rw,nosuid,nodev,noexec,relatime\n",
+            "│ └─/sys/kernel/security securityfs                 securityfs rw,nosuid,nodev,noexec,relatime\n",
+            "├─/dev                   udev                       devtmpfs   rw,nosuid,relatime,size=4012345k,mode=755\n",
+            "│ └─/dev/pts             devpts                     devpts     rw,nosuid,noexec,relatime,gid=5,mode=620,ptmxmode=000\n",
+            "├─/boot/efi              /dev/sda1                  vfat       rw,relatime,fmask=0077,dmask=0077\n",
+            "├─/tmp                   tmpfs                      tmpfs      rw,nosuid,nodev,size=1024k\n",
+            "├─/home                  /dev/mapper/vg-root[/home] ext4       rw,relatime,errors=remount-ro\n",
+            "└─/media/my cdrom        /dev/sr0                   iso9660    ro,relatime\n",
+        ),
+    );
+}
+
+#[test]
+fn test_filtered_tree() {
+    // Filesystems below ones which are filtered out move up the tree.
+    mountinfo_test_with_args(
+        &[
+            "-a",
+            "-t",
+            "noproc,sysfs",
+            "-o",
+            "TARGET,ID,PARENT,PROPAGATION",
+        ],
+        concat!(
+            "TARGET                 ID PARENT PROPAGATION\n",
+            "/                      22      1 shared\n",
+            "|-/sys/kernel/security 25     24 shared\n",
+            "|-/dev                 26     22 shared\n",
+            "| `-/dev/pts           27     26 shared\n",
+            "|-/boot/efi            28     22 shared\n",
+            "|-/tmp                 29     22 shared\n",
+            "|-/home                30     22 shared\n",
+            "`-/media/my cdrom      31     22 slave\n",
+        ),
+    );
+}
+
+#[test]
+fn test_list() {
+    mountinfo_test_with_args(
+        &[
+            "-l",
+            "-v",
+            "-t",
+            "ext4",
+            "-o",
+            "TARGET,SOURCE,FSROOT,MAJ:MIN",
+        ],
+        concat!(
+            "TARGET SOURCE              FSROOT MAJ:MIN\n",
+            "/      /dev/mapper/vg-root /      253:0\n",
+            "/home  /dev/mapper/vg-root /home  253:0\n",
+        ),
+    );
+}
+
+#[test]
+fn test_raw() {
+    mountinfo_test_with_args(
+        &["-r", "-n", "-o", "TARGET,OPT-FIELDS", "-t", "iso9660,vfat"],
+        "/boot/efi shared:30\n/media/my\\x20cdrom master:7\n",
+    );
+}
+
+#[test]
+fn test_pairs() {
+    mountinfo_test_with_args(
+        &["-P", "-o", "TARGET,MAJ:MIN,VFS-OPTIONS,FS-OPTIONS", "-t", "devpts"],
+        "TARGET=\"/dev/pts\" MAJ_MIN=\"0:23\" VFS_OPTIONS=\"rw,nosuid,noexec,relatime\" FS_OPTIONS=\"rw,gid=5,mode=620,ptmxmode=000\"\n",
+    );
+}
+
+#[test]
+fn test_json() {
+    new_ucmd!()
+        .args(&["-F", "mountinfo", "-J", "-t", "ext4,vfat,devpts"])
+        .args(&["-o", "TARGET,SOURCE,ID,OPTIONS"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is_fixture("test_findmnt_json.expected");
+}
+
+#[test]
+fn test_options() {
+    mountinfo_test_with_args(&["-n", "-o", "TARGET", "-O", "nosuid,mode=755"], "/dev\n");
+    mountinfo_test_with_args(
+        &["-n", "-l", "-o", "TARGET", "-O", "+noexec,nodev"],
+        "/proc\n/sys\n/sys/kernel/security\n/dev/pts\n",
+    );
+}
+
+#[test]
+fn test_source() {
+    mountinfo_test_with_args(&["-n", "-S", "253:0", "-o", "TARGET"], "/\n/home\n");
+    mountinfo_test_with_args(
+        &["-n", "-S", "/dev/sr0", "-o", "TARGET"],
+        "/media/my cdrom\n",
+    );
+}
+
+#[test]
+fn test_target() {
+    // Any path below a mountpoint finds the filesystem containing it.
+    mountinfo_test_with_args(
+        &["-n", "-T", "/home/user/file", "-o", "SOURCE"],
+        "/dev/mapper/vg-root[/home]\n",
+    );
+    mountinfo_test_with_args(&["-n", "-T", "/etc/passwd", "-o", "TARGET"], "/\n");
+}
+
+#[test]
+fn test_source_or_target() {
+    mountinfo_test_with_args(&["-n", "-o", "TARGET", "/dev/sda1"], "/boot/efi\n");
+    mountinfo_test_with_args(&["-n", "-o", "SOURCE", "/tmp"], "tmpfs\n");
+    mountinfo_test_with_args(
+        &["-n", "-o", "TARGET", "/dev/mapper/vg-root", "/home"],
+        "/home\n",
+    );
+}
+
+#[test]
+fn test_first_only() {
+    mountinfo_test_with_args(&["-f", "-n", "-o", "TARGET", "-t", "ext4"], "/\n");
+}
+
+#[test]
+fn test_not_found() {
+    new_ucmd!()
+        .args(&["-F", "mountinfo", "/not/mounted"])
+        .fails()
+        .code_is(1)
+        .no_output();
+}
+
+#[test]
+fn test_fstab() {
+    new_ucmd!()
+        .args(&["-F", "fstab", "-s", "-o", "+FREQ,PASSNO"])
+        .succeeds()
+        .stderr_is(
+            "findmnt: warning: fstab: parse error at line 8 -- ignored: missing filesystem type\n",
+        )
+        .stdout_is(concat!(
+            "TARGET    SOURCE              FSTYPE OPTIONS           FREQ PASSNO\n",
+            "/         /dev/mapper/vg-root ext4   errors=remount-ro    0      1\n",
+            "/boot/efi UUID=3A1B-77C2      vfat   umask=0077           0      1\n",
+            "none      /dev/mapper/vg-swap swap   sw                   0      0\n",
+            "/tmp      tmpfs               tmpfs  size=1024k           0      0\n",
+        ));
+}
+
+#[test]
+fn test_poll_timeout() {
+    // Files other than the kernel mount table never signal changes.
+    new_ucmd!()
+        .args(&["-F", "mountinfo", "--poll", "-w", "100"])
+        .fails()
+        .code_is(1)
+        .no_output();
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::process::Command;
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use crate::common::util::TestScenario;
+
+    #[test]
+    fn test_poll_mount() {
+        let ts = TestScenario::new(util_name!());
+        ts.fixtures.mkdir("mnt");
+        let target = ts.fixtures.plus_as_string("mnt");
+
+        let child = ts
+            .ucmd()
+            .args(&["--poll=mount", "-f", "-w", "5000", "-n"])
+            .args(&["-o", "ACTION,FSTYPE", "-T", &target])
+            .run_no_wait();
+        sleep(Duration::from_millis(500));
+
+        let mounted = Command::new("mount")
+            .args(["-t", "tmpfs", "findmnt-test"])
+            .arg(&target)
+            .status()
+            .is_ok_and(|status| status.success());
+        if !mounted {
+            println!("test skipped: mounting a tmpfs requires root");
+            return;
+        }
+
+        let result = child.wait().unwrap();
+        Command::new("umount").arg(&target).status().unwrap();
+        result.success().stdout_is("mount tmpfs\n");
+    }
+}
@@ -0,0 +1,164 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::os::fd::AsRawFd;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use crate::common::util::{TestScenario, UChild};
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_bad_file_descriptor() {
+    new_ucmd!()
+        .arg("lockfile")
+        .fails()
+        .code_is(1)
+        .stderr_is("flock: bad file descriptor: 'lockfile'\n");
+}
+
+#[test]
+fn test_no_fork_conflicts_with_close() {
+    new_ucmd!()
+        .args(&["-F", "-o", "lockfile", "true"])
+        .fails()
+        .code_is(1);
+}
+
+#[test]
+fn test_runs_command() {
+    let (at, mut ucmd) = at_and_ucmd!();
+
+    ucmd.args(&["lockfile", "echo", "-n", "locked"])
+        .succeeds()
+        .stdout_is("locked");
+
+    assert!(at.file_exists("lockfile"));
+}
+
+#[test]
+fn test_shell_command_exit_code() {
+    new_ucmd!()
+        .args(&["lockfile", "-c", "exit 3"])
+        .fails()
+        .code_is(3);
+}
+
+#[test]
+fn test_cannot_open_lock_file() {
+    new_ucmd!()
+        .args(&["missing/lockfile", "true"])
+        .fails()
+        .code_is(66)
+        .stderr_contains("cannot open lock file missing/lockfile");
+}
+
+// Runs `flock <args> lockfile sleep 10` in the background and waits for it to hold the lock.
+fn hold_lock(scene: &TestScenario, args: &[&str]) -> UChild {
+    let at = &scene.fixtures;
+    let child = scene
+        .ucmd()
+        .args(args)
+        .args(&["lockfile", "-c", "touch ready && sleep 10"])
+        .run_no_wait();
+
+    let start = Instant::now();
+    while !at.file_exists("ready") {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        sleep(Duration::from_millis(10));
+    }
+    child
+}
+
+#[test]
+fn test_nonblock_conflict() {
+    let scene = TestScenario::new(util_name!());
+    let mut holder = hold_lock(&scene, &["-x"]);
+
+    scene
+        .ucmd()
+        .args(&["-n", "lockfile", "true"])
+        .fails()
+        .code_is(1)
+        .no_output();
+
+    scene
+        .ucmd()
+        .args(&["-n", "-E", "42", "-s", "lockfile", "true"])
+        .fails()
+        .code_is(42);
+
+    holder.kill();
+}
+
+#[test]
+fn test_timeout_conflict() {
+    let scene = TestScenario::new(util_name!());
+    let mut holder = hold_lock(&scene, &[]);
+
+    let start = Instant::now();
+    scene
+        .ucmd()
+        .args(&["-w", "0.2", "lockfile", "true"])
+        .fails()
+        .code_is(1);
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    holder.kill();
+}
+
+#[test]
+fn test_shared_locks_do_not_conflict() {
+    let scene = TestScenario::new(util_name!());
+    let mut holder = hold_lock(&scene, &["-s"]);
+
+    scene
+        .ucmd()
+        .args(&["-s", "-n", "lockfile", "echo", "shared"])
+        .succeeds()
+        .stdout_is("shared\n");
+
+    holder.kill();
+}
+
+#[test]
+fn test_lock_is_released_after_command() {
+    let scene = TestScenario::new(util_name!());
+
+    scene.ucmd().args(&["lockfile", "true"]).succeeds();
+    scene.ucmd().args(&["-n", "lockfile", "true"]).succeeds();
+}
+
+#[test]
+fn test_file_descriptor() {
+    let scene = TestScenario::new(util_name!());
+    let lock_file = scene.fixtures.make_file("lockfile");
+    let script = format!(
+        "{} {} -n 9 9>lockfile; echo $?",
+        scene.bin_path.display(),
+        scene.util_name
+    );
+
+    scene
+        .cmd("sh")
+        .args(&["-c", &script])
+        .succeeds()
+        .stdout_is("0\n");
+
+    assert_eq!(
+        unsafe { libc::flock(lock_file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) },
+        0
+    );
+
+    scene
+        .cmd("sh")
+        .args(&["-c", &script])
+        .succeeds()
+        .stdout_is("1\n");
+}
@@ -21,8 +21,33 @@ fn test_operations_mutually_exclusive() {
         .stderr_contains("the argument '--freeze' cannot be used with '--unfreeze'");
 }
 
+#[test]
+fn test_command_requires_freeze() {
+    new_ucmd!()
+        .args(&["--unfreeze", "/foo", "true"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("the argument '--unfreeze' cannot be used with '[command]...'");
+    new_ucmd!()
+        .args(&["--unfreeze", "--timeout", "1", "/foo"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("the argument '--unfreeze' cannot be used with '--timeout <seconds>'");
+}
+
+#[test]
+fn test_invalid_timeout() {
+    new_ucmd!()
+        .args(&["--freeze", "--timeout", "0", "/foo"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("invalid timeout value: '0'");
+}
+
 #[cfg(target_os = "linux")]
 mod linux {
+    use std::process::Command;
+
     use crate::common::util::TestScenario;
 
     #[test]
@@ -44,6 +69,59 @@ mod linux {
             .code_is(1)
             .stderr_contains("not a directory");
     }
+
+    #[test]
+    fn test_fails_on_ioctl_error() {
+        // Either the filesystem isn't frozen, or we aren't allowed to thaw it.
+        let (at, mut ucmd) = at_and_ucmd!();
+        at.mkdir("dir");
+
+        ucmd.args(&["--unfreeze", "dir"])
+            .fails()
+            .code_is(1)
+            .stderr_contains("fsfreeze: failed to unfreeze the filesystem: ");
+    }
+
+    #[test]
+    fn test_freeze_while_running_command() {
+        let device = loop_device_or_skip!(16 * 1024 * 1024);
+        let ts = TestScenario::new(util_name!());
+        ts.fixtures.mkdir("mnt");
+        let mountpoint = ts.fixtures.plus_as_string("mnt");
+
+        let prepared = Command::new("mkfs.ext4")
+            .args(["-q", device.path_str()])
+            .status()
+            .is_ok_and(|status| status.success())
+            && Command::new("mount")
+                .args([device.path_str(), &mountpoint])
+                .status()
+                .is_ok_and(|status| status.success());
+        if !prepared {
+            println!("test skipped: mkfs.ext4 or mount failed");
+            return;
+        }
+
+        let ran_command = ts
+            .ucmd()
+            .args(&["--freeze", "mnt", "sh", "-c", "exit 3"])
+            .run();
+        let timed_out = ts
+            .ucmd()
+            .args(&["--freeze", "--timeout", "0.2", "mnt", "sleep", "10"])
+            .run();
+        // Thawing a filesystem which isn't frozen fails.
+        let thawed = ts.ucmd().args(&["--unfreeze", "mnt"]).run();
+        Command::new("umount").arg(&mountpoint).status().unwrap();
+
+        ran_command.code_is(3).no_output();
+        timed_out
+            .code_is(1)
+            .stderr_is("fsfreeze: timed out after 0.2s, the filesystem was thawed\n");
+        thawed
+            .code_is(1)
+            .stderr_is("fsfreeze: failed to unfreeze the filesystem: Invalid argument\n");
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
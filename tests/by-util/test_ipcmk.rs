@@ -0,0 +1,99 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::common::util::TestScenario;
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_resource_kind_is_required() {
+    new_ucmd!().arg("--mode=600").fails().code_is(1);
+}
+
+#[test]
+fn test_invalid_mode() {
+    new_ucmd!()
+        .args(&["--queue", "--mode=999"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("invalid mode: 999");
+}
+
+#[test]
+fn test_zero_size() {
+    new_ucmd!()
+        .args(&["--shmem", "0"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("invalid size: 0");
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ptr;
+
+    use crate::common::util::TestScenario;
+
+    fn created_id(stdout: &str, prefix: &str) -> libc::c_int {
+        stdout
+            .lines()
+            .find_map(|line| line.strip_prefix(prefix))
+            .and_then(|id| id.trim().parse().ok())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_create_queue() {
+        let result = new_ucmd!()
+            .args(&["--queue", "--key=0", "--mode=600"])
+            .succeeds();
+
+        let id = created_id(result.stdout_str(), "Message queue id: ");
+
+        let mut stat: libc::msqid_ds = unsafe { std::mem::zeroed() };
+        assert_eq!(unsafe { libc::msgctl(id, libc::IPC_STAT, &mut stat) }, 0);
+        assert_eq!(stat.msg_perm.mode & 0o777, 0o600);
+
+        assert_eq!(
+            unsafe { libc::msgctl(id, libc::IPC_RMID, ptr::null_mut()) },
+            0
+        );
+    }
+
+    #[test]
+    fn test_create_shmem() {
+        let result = new_ucmd!().args(&["--shmem", "4KiB", "--key=0"]).succeeds();
+
+        let id = created_id(result.stdout_str(), "Shared memory id: ");
+
+        let mut stat: libc::shmid_ds = unsafe { std::mem::zeroed() };
+        assert_eq!(unsafe { libc::shmctl(id, libc::IPC_STAT, &mut stat) }, 0);
+        assert_eq!(stat.shm_segsz, 4096);
+        assert_eq!(stat.shm_perm.mode & 0o777, 0o644);
+
+        assert_eq!(
+            unsafe { libc::shmctl(id, libc::IPC_RMID, ptr::null_mut()) },
+            0
+        );
+    }
+
+    #[test]
+    fn test_create_semaphore() {
+        let result = new_ucmd!()
+            .args(&["--semaphore", "3", "--key=0"])
+            .succeeds();
+
+        let id = created_id(result.stdout_str(), "Semaphore id: ");
+
+        let mut stat: libc::semid_ds = unsafe { std::mem::zeroed() };
+        assert_eq!(unsafe { libc::semctl(id, 0, libc::IPC_STAT, &mut stat) }, 0);
+        assert_eq!(stat.sem_nsems, 3);
+
+        assert_eq!(unsafe { libc::semctl(id, 0, libc::IPC_RMID) }, 0);
+    }
+}
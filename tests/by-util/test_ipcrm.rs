@@ -0,0 +1,119 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::common::util::TestScenario;
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_resource_is_required() {
+    new_ucmd!().arg("--verbose").fails().code_is(1);
+}
+
+#[test]
+fn test_invalid_all_category() {
+    new_ucmd!().arg("--all=foo").fails().code_is(1);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ptr;
+
+    use crate::common::util::TestScenario;
+
+    fn create_queue(key: libc::key_t) -> libc::c_int {
+        let id = unsafe { libc::msgget(key, libc::IPC_CREAT | libc::IPC_EXCL | 0o600) };
+        assert_ne!(id, -1);
+        id
+    }
+
+    fn queue_exists(id: libc::c_int) -> bool {
+        let mut stat: libc::msqid_ds = unsafe { std::mem::zeroed() };
+        unsafe { libc::msgctl(id, libc::IPC_STAT, &mut stat) == 0 }
+    }
+
+    #[test]
+    fn test_remove_queue_by_id() {
+        let id = create_queue(libc::IPC_PRIVATE);
+
+        new_ucmd!()
+            .args(&["--queue-id", &id.to_string(), "--verbose"])
+            .succeeds()
+            .stdout_is(format!("removing message queue id `{id}'\n"));
+
+        assert!(!queue_exists(id));
+    }
+
+    #[test]
+    fn test_remove_queue_by_key() {
+        // Pick a key derived from the PID to avoid clashing with concurrent tests.
+        let key = 0x7575_0000 | (std::process::id() as libc::key_t & 0xffff);
+        let id = create_queue(key);
+
+        new_ucmd!()
+            .args(&["-Q", &format!("{key:#x}")])
+            .succeeds()
+            .no_output();
+
+        assert!(!queue_exists(id));
+    }
+
+    #[test]
+    fn test_remove_shmem_and_semaphore_by_id() {
+        let shm_id = unsafe { libc::shmget(libc::IPC_PRIVATE, 4096, libc::IPC_CREAT | 0o600) };
+        assert_ne!(shm_id, -1);
+        let sem_id = unsafe { libc::semget(libc::IPC_PRIVATE, 1, libc::IPC_CREAT | 0o600) };
+        assert_ne!(sem_id, -1);
+
+        new_ucmd!()
+            .args(&["-m", &shm_id.to_string(), "-s", &sem_id.to_string()])
+            .succeeds()
+            .no_output();
+
+        let mut stat: libc::shmid_ds = unsafe { std::mem::zeroed() };
+        assert_eq!(
+            unsafe { libc::shmctl(shm_id, libc::IPC_STAT, &mut stat) },
+            -1
+        );
+        assert_eq!(unsafe { libc::semctl(sem_id, 0, libc::IPC_RMID) }, -1);
+    }
+
+    #[test]
+    fn test_invalid_id() {
+        new_ucmd!()
+            .args(&["--queue-id", "2147483647"])
+            .fails()
+            .code_is(1)
+            .stderr_is("ipcrm: invalid id (2147483647)\n");
+    }
+
+    #[test]
+    fn test_illegal_key() {
+        new_ucmd!()
+            .args(&["--shmem-key", "0"])
+            .fails()
+            .code_is(1)
+            .stderr_is("ipcrm: illegal key (0)\n");
+    }
+
+    #[test]
+    fn test_errors_do_not_stop_processing() {
+        let id = create_queue(libc::IPC_PRIVATE);
+
+        new_ucmd!()
+            .args(&["-q", "2147483647", "-q", &id.to_string()])
+            .fails()
+            .code_is(1)
+            .stderr_is("ipcrm: invalid id (2147483647)\n");
+
+        if queue_exists(id) {
+            unsafe { libc::msgctl(id, libc::IPC_RMID, ptr::null_mut()) };
+            panic!("message queue {id} was not removed");
+        }
+    }
+}
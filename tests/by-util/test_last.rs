@@ -108,3 +108,232 @@ fn test_short_invalid_utmp_file() {
         .succeeds()
         .stdout_matches(&regex);
 }
+
+// Builds a login record as stored in wtmp and btmp.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn utmp_record(ut_type: libc::c_short, pid: i32, line: &str, user: &str, time: i64) -> Vec<u8> {
+    fn copy(dst: &mut [libc::c_char], src: &str) {
+        for (d, s) in dst.iter_mut().zip(src.bytes()) {
+            *d = s as libc::c_char;
+        }
+    }
+
+    let mut ut: libc::utmpx = unsafe { std::mem::zeroed() };
+    ut.ut_type = ut_type;
+    ut.ut_pid = pid;
+    copy(&mut ut.ut_line, line);
+    copy(&mut ut.ut_user, user);
+    copy(&mut ut.ut_host, "10.0.0.1");
+    ut.ut_tv.tv_sec = time as _;
+    let bytes = unsafe {
+        std::slice::from_raw_parts(
+            (&ut as *const libc::utmpx).cast::<u8>(),
+            std::mem::size_of::<libc::utmpx>(),
+        )
+    };
+    bytes.to_vec()
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_failed_logins() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    let mut btmp = utmp_record(libc::LOGIN_PROCESS, 10, "ssh:notty", "root", 1_700_000_000);
+    btmp.extend(utmp_record(
+        libc::LOGIN_PROCESS,
+        11,
+        "ssh:notty",
+        "admin",
+        1_700_000_600,
+    ));
+    at.write_bytes("btmp", &btmp);
+
+    ucmd.env("TZ", "UTC")
+        .args(&["--failed", "-f", "btmp"])
+        .succeeds()
+        .stdout_is(
+            "admin    ssh:notty    10.0.0.1         Tue Nov 14 22:23 - 22:23  (00:00)\n\
+             root     ssh:notty    10.0.0.1         Tue Nov 14 22:13 - 22:13  (00:00)\n\
+             \n\
+             btmp begins Tue Nov 14 22:13:20 2023\n",
+        );
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_system_events() {
+    let ts = TestScenario::new(util_name!());
+    let start = 1_700_000_000;
+    let runlevel = |previous: u8, level: u8| i32::from(previous) * 256 + i32::from(level);
+    let records = [
+        utmp_record(libc::BOOT_TIME, 0, "~", "reboot", start),
+        utmp_record(
+            libc::RUN_LVL,
+            runlevel(b'N', b'3'),
+            "~",
+            "runlevel",
+            start + 60,
+        ),
+        utmp_record(
+            libc::RUN_LVL,
+            runlevel(b'3', b'5'),
+            "~",
+            "runlevel",
+            start + 120,
+        ),
+        utmp_record(libc::USER_PROCESS, 100, "pts/0", "alice", start + 180),
+        // No shutdown, the system crashed.
+        utmp_record(libc::BOOT_TIME, 0, "~", "reboot", start + 3600),
+        utmp_record(libc::USER_PROCESS, 200, "pts/1", "bob", start + 3660),
+        utmp_record(libc::RUN_LVL, 0, "~~", "shutdown", start + 7200),
+        utmp_record(libc::BOOT_TIME, 0, "~", "reboot", start + 7500),
+    ];
+    ts.fixtures.write_bytes("wtmp", &records.concat());
+
+    let result = ts
+        .ucmd()
+        .env("TZ", "UTC")
+        .args(&["-x", "-R", "-f", "wtmp"])
+        .succeeds();
+    let lines: Vec<&str> = result.stdout_str().lines().collect();
+    assert_eq!(
+        lines[..8],
+        [
+            "reboot   system boot  Wed Nov 15 00:18   still running",
+            "shutdown system down  Wed Nov 15 00:13 - 00:18  (00:05)",
+            "bob      pts/1        Tue Nov 14 23:14 - down   (00:59)",
+            "reboot   system boot  Tue Nov 14 23:13 - 00:13  (01:00)",
+            "alice    pts/0        Tue Nov 14 22:16 - crash  (00:57)",
+            "runlevel (lvl 3 to 5) Tue Nov 14 22:15 - 23:13  (00:58)",
+            "runlevel (to lvl 3)   Tue Nov 14 22:14 - 22:15  (00:01)",
+            "reboot   system boot  Tue Nov 14 22:13 - crash  (01:00)",
+        ]
+    );
+}
+
+// A boot, then a session which ended and one which didn't.
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn sessions_wtmp() -> Vec<u8> {
+    // Nov 14 22:13:20 UTC
+    let start = 1_700_000_000;
+    [
+        utmp_record(libc::BOOT_TIME, 0, "~", "reboot", start),
+        utmp_record(libc::USER_PROCESS, 100, "pts/0", "alice", start + 180),
+        utmp_record(libc::DEAD_PROCESS, 100, "pts/0", "", start + 600),
+        utmp_record(libc::USER_PROCESS, 200, "pts/1", "bob", start + 1200),
+    ]
+    .concat()
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_time_window() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes("wtmp", &sessions_wtmp());
+
+    let users = |args: &[&str]| -> Vec<String> {
+        let result = ts
+            .ucmd()
+            .env("TZ", "UTC")
+            .args(&["-f", "wtmp"])
+            .args(args)
+            .succeeds();
+        result
+            .stdout_str()
+            .lines()
+            .take_while(|line| !line.is_empty())
+            .map(|line| line.split(' ').next().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(users(&[]), ["bob", "alice", "reboot"]);
+    assert_eq!(users(&["-p", "2023-11-14 22:20"]), ["alice", "reboot"]);
+    assert_eq!(users(&["--since", "2023-11-14 22:25"]), ["bob", "reboot"]);
+    assert_eq!(users(&["--until", "2023-11-14 22:15"]), ["reboot"]);
+    assert_eq!(
+        users(&["-s", "2023-11-14 22:20", "-t", "2023-11-14 22:40"]),
+        ["bob", "alice", "reboot"]
+    );
+
+    ts.ucmd()
+        .args(&["-f", "wtmp", "--since", "yesterday-ish"])
+        .fails()
+        .code_is(1)
+        .stderr_is("last: invalid time value \"yesterday-ish\"\n");
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_json_output() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write_bytes("wtmp", &sessions_wtmp());
+
+    let result = ucmd
+        .env("TZ", "UTC")
+        .args(&["-f", "wtmp", "--json"])
+        .succeeds();
+    // Indented like the other utilities, the way libsmartcols does
+    assert!(result.stdout_str().contains("\n   \"last\": [\n      {\n"));
+    let json: serde_json::Value = serde_json::from_str(result.stdout_str()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "last": [
+                {
+                    "user": "bob",
+                    "tty": "pts/1",
+                    "host": "10.0.0.1",
+                    "login": "2023-11-14T22:33:20+00:00",
+                    "logout": null,
+                    "duration": null,
+                    "state": "still logged in"
+                },
+                {
+                    "user": "alice",
+                    "tty": "pts/0",
+                    "host": "10.0.0.1",
+                    "login": "2023-11-14T22:16:20+00:00",
+                    "logout": "2023-11-14T22:23:20+00:00",
+                    "duration": 420,
+                    "state": "gone"
+                },
+                {
+                    "user": "reboot",
+                    "tty": "system boot",
+                    "host": "10.0.0.1",
+                    "login": "2023-11-14T22:13:20+00:00",
+                    "logout": null,
+                    "duration": null,
+                    "state": "still running"
+                }
+            ],
+            "begins": "2023-11-14T22:13:20+00:00"
+        })
+    );
+}
+
+#[test]
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+fn test_raw_output() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write_bytes("wtmp", &sessions_wtmp());
+
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["-f", "wtmp", "--raw", "alice"])
+        .succeeds()
+        .stdout_is(
+            "alice\tpts/0\t10.0.0.1\t2023-11-14T22:16:20+00:00\t2023-11-14T22:23:20+00:00\t420\tgone\n\
+             \twtmp\t\t2023-11-14T22:13:20+00:00\t\t\tbegins\n",
+        );
+    ts.ucmd()
+        .env("TZ", "UTC")
+        .args(&["-f", "wtmp", "--raw", "--separator", ",", "bob"])
+        .succeeds()
+        .stdout_contains("bob,pts/1,10.0.0.1,2023-11-14T22:33:20+00:00,,,still logged in\n")
+        .stdout_contains(",wtmp,,2023-11-14T22:13:20+00:00,,,begins\n");
+    ts.ucmd()
+        .args(&["-f", "wtmp", "--separator", ","])
+        .fails()
+        .code_is(1);
+}
@@ -0,0 +1,187 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::common::util::TestScenario;
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_detach_requires_device() {
+    new_ucmd!().arg("-d").fails().code_is(1);
+}
+
+#[test]
+fn test_find_conflicts_with_list() {
+    new_ucmd!()
+        .args(&["-f", "-l"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("cannot be used with");
+}
+
+#[test]
+fn test_find_conflicts_with_json() {
+    new_ucmd!()
+        .args(&["-f", "-J"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("cannot be used with");
+}
+
+#[test]
+fn test_unknown_column() {
+    new_ucmd!()
+        .args(&["-l", "-O", "NAME,FOO"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("unknown column: FOO");
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::fs::{self, File, Permissions};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    use crate::common::loop_device::LoopDevice;
+    use crate::common::util::{TestScenario, TESTS_BINARY};
+
+    #[test]
+    fn test_show_device() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        let stdout = new_ucmd!().arg(device.path()).succeeds().stdout_move_str();
+        assert!(stdout.starts_with(&format!("{}: [", device.path_str())));
+        assert!(stdout.ends_with(&format!("({})\n", device.backing_file().display())));
+    }
+
+    #[test]
+    fn test_associated() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        let stdout = new_ucmd!()
+            .arg("-j")
+            .arg(device.backing_file())
+            .succeeds()
+            .stdout_move_str();
+        assert_eq!(stdout.lines().count(), 1);
+        assert!(stdout.starts_with(&format!("{}: [", device.path_str())));
+
+        new_ucmd!()
+            .arg("-j")
+            .arg(device.backing_file())
+            .args(&["-o", "512"])
+            .succeeds()
+            .no_output();
+    }
+
+    #[test]
+    fn test_list_json() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        let result = new_ucmd!()
+            .args(&["-l", "-J", "-O", "NAME,BACK-FILE,RO,OFFSET,LOG-SEC"])
+            .arg(device.path())
+            .succeeds();
+
+        let json: serde_json::Value = serde_json::from_str(result.stdout_str()).unwrap();
+        let devices = json["loopdevices"].as_array().unwrap();
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0]["name"], device.path_str());
+        assert_eq!(
+            devices[0]["back-file"],
+            device.backing_file().to_str().unwrap()
+        );
+        assert_eq!(devices[0]["ro"], false);
+        assert_eq!(devices[0]["offset"], 0);
+        assert_eq!(devices[0]["log-sec"], 512);
+    }
+
+    #[test]
+    fn test_json_implies_list() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        let result = new_ucmd!().arg("-J").arg(device.path()).succeeds();
+        let json: serde_json::Value = serde_json::from_str(result.stdout_str()).unwrap();
+        assert_eq!(json["loopdevices"][0]["name"], device.path_str());
+
+        let result = new_ucmd!().args(&["-J", "-a"]).succeeds();
+        let json: serde_json::Value = serde_json::from_str(result.stdout_str()).unwrap();
+        let devices = json["loopdevices"].as_array().unwrap();
+        assert!(devices.iter().any(|dev| dev["name"] == device.path_str()));
+    }
+
+    #[test]
+    fn test_list_unprivileged() {
+        let device = loop_device_or_skip!(1024 * 1024);
+
+        // Other users may not be able to reach the test binary, so run a copy of it.
+        let ts = TestScenario::new(util_name!());
+        fs::set_permissions(&ts.fixtures.subdir, Permissions::from_mode(0o755)).unwrap();
+        fs::copy(TESTS_BINARY, ts.fixtures.plus("util-linux")).unwrap();
+
+        // "nobody" can't open the loop devices, so everything has to come from sysfs.
+        let output = Command::new(ts.fixtures.plus("util-linux"))
+            .args(["losetup", "-l", "-n", "-O", "NAME,BACK-FILE"])
+            .uid(65534)
+            .gid(65534)
+            .output()
+            .unwrap();
+
+        assert!(output.status.success());
+        assert!(output.stderr.is_empty());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let expected = format!("{} {}", device.path_str(), device.backing_file().display());
+        assert!(
+            stdout.lines().any(|line| line == expected),
+            "{expected:?} not in {stdout:?}"
+        );
+    }
+
+    #[test]
+    fn test_set_up_and_detach() {
+        loop_device_or_skip!();
+
+        let (at, mut ucmd) = at_and_ucmd!();
+        File::create(at.plus("disk.img"))
+            .unwrap()
+            .set_len(1024 * 1024)
+            .unwrap();
+
+        let path = ucmd
+            .args(&["-f", "--show", "-r", "-o", "512", "disk.img"])
+            .succeeds()
+            .stdout_move_str();
+        let device = LoopDevice::adopt(path.trim_end());
+
+        new_ucmd!()
+            .args(&["-l", "-n", "-O", "RO,OFFSET,BACK-FILE"])
+            .arg(device.path())
+            .succeeds()
+            .stdout_is(format!(
+                "1 512 {}\n",
+                at.plus("disk.img").canonicalize().unwrap().display()
+            ));
+
+        new_ucmd!()
+            .arg("-d")
+            .arg(device.path())
+            .succeeds()
+            .no_output();
+
+        new_ucmd!()
+            .arg(device.path())
+            .fails()
+            .code_is(1)
+            .stderr_contains(format!(
+                "losetup: {}: No such device or address",
+                device.path_str()
+            ));
+    }
+}
@@ -0,0 +1,290 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::common::util::{AtPath, TestScenario};
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_unknown_column() {
+    new_ucmd!()
+        .args(&["-o", "NAME,FOO"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("unknown column: FOO");
+}
+
+#[test]
+fn test_not_a_block_device() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.touch("file");
+
+    ucmd.args(&["file", "missing"])
+        .fails()
+        .code_is(32)
+        .no_stdout()
+        .stderr_is("lsblk: file: not a block device\nlsblk: missing: not a block device\n");
+}
+
+fn write_attributes(at: &AtPath, dir: &str, attributes: &[(&str, &str)]) {
+    for (name, value) in attributes {
+        let path = format!("{dir}/{name}");
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            at.mkdir_all(parent);
+        }
+        at.write(&path, &format!("{value}\n"));
+    }
+}
+
+/// Builds up a fake system root below "root":
+///
+/// - sda, a disk with an EFI partition and an LVM volume on its second partition
+/// - sr0, a CD-ROM drive with a disc inserted
+/// - loop0, an unused loop device
+/// - ram0, a RAM disk
+fn make_sysroot(at: &AtPath) {
+    // Kernel name, directory below /sys and attributes of each device
+    type Device<'a> = (&'a str, &'a str, &'a [(&'a str, &'a str)]);
+    let devices: &[Device] = &[
+        (
+            "sda",
+            "block/sda",
+            &[
+                ("dev", "8:0"),
+                ("size", "41943040"),
+                ("removable", "0"),
+                ("ro", "0"),
+                ("alignment_offset", "0"),
+                ("device/model", "QEMU HARDDISK   "),
+                ("device/vendor", "ATA     "),
+                ("device/serial", "QM00001"),
+                ("device/state", "running"),
+                ("device/type", "0"),
+                ("queue/discard_granularity", "4096"),
+                ("queue/logical_block_size", "512"),
+                ("queue/rotational", "1"),
+                ("queue/scheduler", "mq-deadline [bfq] none"),
+            ],
+        ),
+        (
+            "sda1",
+            "block/sda/sda1",
+            &[
+                ("dev", "8:1"),
+                ("size", "2048"),
+                ("partition", "1"),
+                ("ro", "0"),
+                ("alignment_offset", "0"),
+            ],
+        ),
+        (
+            "sda2",
+            "block/sda/sda2",
+            &[
+                ("dev", "8:2"),
+                ("size", "41936896"),
+                ("partition", "2"),
+                ("ro", "0"),
+                ("alignment_offset", "0"),
+            ],
+        ),
+        (
+            "dm-0",
+            "block/dm-0",
+            &[
+                ("dev", "253:0"),
+                ("size", "41936896"),
+                ("removable", "0"),
+                ("ro", "0"),
+                ("dm/name", "vg-root"),
+                ("dm/suspended", "0"),
+                ("dm/uuid", "LVM-Xm2bB4aQ"),
+                ("queue/rotational", "1"),
+            ],
+        ),
+        (
+            "sr0",
+            "block/sr0",
+            &[
+                ("dev", "11:0"),
+                ("size", "2097152"),
+                ("removable", "1"),
+                ("ro", "0"),
+                ("device/model", "QEMU DVD-ROM"),
+                ("device/type", "5"),
+                ("queue/rotational", "1"),
+            ],
+        ),
+        ("loop0", "block/loop0", &[("dev", "7:0"), ("size", "0")]),
+        ("ram0", "block/ram0", &[("dev", "1:0"), ("size", "8192")]),
+    ];
+
+    for (name, dir, attributes) in devices {
+        write_attributes(at, &format!("root/sys/{dir}"), attributes);
+        at.mkdir_all("root/sys/class/block");
+        at.relative_symlink_dir(
+            &format!("../../{dir}"),
+            &format!("root/sys/class/block/{name}"),
+        );
+    }
+
+    at.mkdir_all("root/sys/block/sda/sda2/holders");
+    at.relative_symlink_dir("../../../dm-0", "root/sys/block/sda/sda2/holders/dm-0");
+    at.mkdir_all("root/sys/block/dm-0/slaves");
+    at.relative_symlink_dir("../../sda/sda2", "root/sys/block/dm-0/slaves/sda2");
+
+    at.mkdir_all("root/proc/self");
+    at.write(
+        "root/proc/self/mountinfo",
+        "28 1 253:0 / / rw,relatime - ext4 /dev/mapper/vg-root rw\n\
+         29 28 8:1 / /boot/efi rw,relatime - vfat /dev/sda1 rw\n\
+         30 28 11:0 / /media/my\\040cdrom ro,relatime - iso9660 /dev/sr0 ro\n\
+         31 28 253:0 /home /home rw,relatime - ext4 /dev/mapper/vg-root rw\n",
+    );
+}
+
+fn sysroot_test_with_args(args: &[&str], expected_output: &str) {
+    let (at, mut ucmd) = at_and_ucmd!();
+    make_sysroot(&at);
+
+    ucmd.arg("--sysroot")
+        .arg(at.plus("root"))
+        .args(args)
+        .succeeds()
+        .no_stderr()
+        .stdout_is(expected_output);
+}
+
+#[test]
+fn test_tree() {
+    sysroot_test_with_args(
+        &[],
+        concat!(
+            "NAME        MAJ:MIN RM  SIZE RO TYPE MOUNTPOINTS\n",
+            "sda           8:0    0   20G  0 disk\n",
+            "├─sda1        8:1    0    1M  0 part /boot/efi\n",
+            "└─sda2        8:2    0   20G  0 part\n",
+            "  └─vg-root 253:0    0   20G  0 lvm  /\n",
+            "                                     /home\n",
+            "sr0          11:0    1    1G  0 rom  /media/my cdrom\n",
+        ),
+    );
+}
+
+#[test]
+fn test_ascii_tree() {
+    sysroot_test_with_args(
+        &["-i", "-n", "-o", "NAME,KNAME,PKNAME"],
+        concat!(
+            "sda         sda\n",
+            "|-sda1      sda1 sda\n",
+            "`-sda2      sda2 sda\n",
+            "  `-vg-root dm-0 sda2\n",
+            "sr0         sr0\n",
+        ),
+    );
+}
+
+#[test]
+fn test_list() {
+    sysroot_test_with_args(
+        &["-l", "-p", "-o", "NAME,TYPE,MOUNTPOINT"],
+        "NAME                TYPE MOUNTPOINT\n\
+         /dev/sda            disk\n\
+         /dev/sda1           part /boot/efi\n\
+         /dev/sda2           part\n\
+         /dev/mapper/vg-root lvm  /\n\
+         /dev/sr0            rom  /media/my cdrom\n",
+    );
+}
+
+#[test]
+fn test_raw() {
+    sysroot_test_with_args(
+        &["-r", "-o", "NAME,MAJ:MIN,MOUNTPOINTS"],
+        "NAME MAJ:MIN MOUNTPOINTS\n\
+         sda 8:0 \n\
+         sda1 8:1 /boot/efi\n\
+         sda2 8:2 \n\
+         vg-root 253:0 /\\x0a/home\n\
+         sr0 11:0 /media/my\\x20cdrom\n",
+    );
+}
+
+#[test]
+fn test_pairs() {
+    sysroot_test_with_args(
+        &["-P", "-d", "-o", "NAME,MODEL,VENDOR,SERIAL,STATE,ROTA,SCHED"],
+        "NAME=\"sda\" MODEL=\"QEMU HARDDISK\" VENDOR=\"ATA\" SERIAL=\"QM00001\" STATE=\"running\" ROTA=\"1\" SCHED=\"bfq\"\n\
+         NAME=\"sr0\" MODEL=\"QEMU DVD-ROM\" VENDOR=\"\" SERIAL=\"\" STATE=\"\" ROTA=\"1\" SCHED=\"\"\n",
+    );
+}
+
+#[test]
+fn test_bytes() {
+    sysroot_test_with_args(
+        &["-b", "-d", "-o", "NAME,SIZE,DISC-GRAN"],
+        "NAME        SIZE DISC-GRAN\n\
+         sda  21474836480      4096\n\
+         sr0   1073741824\n",
+    );
+}
+
+#[test]
+fn test_all() {
+    sysroot_test_with_args(
+        &["-a", "-d", "-n", "-o", "NAME,TYPE,SIZE"],
+        "ram0  disk    4M\n\
+         loop0 loop    0B\n\
+         sda   disk   20G\n\
+         sr0   rom     1G\n",
+    );
+}
+
+#[test]
+fn test_include_and_exclude() {
+    sysroot_test_with_args(&["-I", "11", "-n", "-o", "NAME"], "sr0\n");
+    sysroot_test_with_args(
+        &["-e", "11", "-l", "-n", "-o", "NAME"],
+        "ram0\nsda\nsda1\nsda2\nvg-root\n",
+    );
+}
+
+#[test]
+fn test_json() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    make_sysroot(&at);
+
+    ucmd.arg("--sysroot")
+        .arg(at.plus("root"))
+        .args(&[
+            "-J",
+            "-o",
+            "NAME,MAJ:MIN,RM,SIZE,RO,TYPE,MOUNTPOINTS,LOG-SEC",
+        ])
+        .succeeds()
+        .no_stderr()
+        .stdout_is_fixture("test_lsblk_json.expected");
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use crate::common::util::TestScenario;
+
+    #[test]
+    fn test_loop_device() {
+        let device = loop_device_or_skip!(1024 * 1024);
+        let name = device.path_str().trim_start_matches("/dev/").to_string();
+
+        new_ucmd!()
+            .args(&["-n", "-b", "-o", "NAME,TYPE,SIZE,RO"])
+            .arg(device.path())
+            .succeeds()
+            .stdout_is(format!("{name} loop 1048576 0\n"));
+    }
+}
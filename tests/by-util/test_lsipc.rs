@@ -0,0 +1,109 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use crate::common::util::TestScenario;
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_name_requires_posix_kind() {
+    new_ucmd!().args(&["--name", "foo"]).fails().code_is(1);
+    new_ucmd!()
+        .args(&["--shmems", "--name", "foo"])
+        .fails()
+        .code_is(1);
+}
+
+#[test]
+fn test_id_conflicts_with_posix_kind() {
+    new_ucmd!()
+        .args(&["--posix-shmems", "--id", "0"])
+        .fails()
+        .code_is(1);
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::path::PathBuf;
+
+    use crate::common::util::TestScenario;
+
+    // Removes the POSIX shared memory object when the test ends.
+    struct ShmObject(PathBuf);
+
+    impl ShmObject {
+        fn create(name: &str, size: usize) -> Self {
+            let path = PathBuf::from("/dev/shm").join(name);
+            std::fs::write(&path, vec![0_u8; size]).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for ShmObject {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_posix_shared_memory() {
+        let name = format!("uu-lsipc-test-{}", std::process::id());
+        let _object = ShmObject::create(&name, 42);
+
+        new_ucmd!()
+            .args(&["--posix-shmems", "--raw", "--bytes", "-o", "NAME,SIZE"])
+            .succeeds()
+            .stdout_contains(format!("/{name} 42\n"));
+    }
+
+    #[test]
+    fn test_posix_shared_memory_by_name() {
+        let name = format!("uu-lsipc-test-name-{}", std::process::id());
+        let _object = ShmObject::create(&name, 7);
+
+        new_ucmd!()
+            .args(&["-M", "-N", &name, "--bytes"])
+            .succeeds()
+            .stdout_contains(format!("/{name}"))
+            .stdout_contains("Segment size:");
+    }
+
+    #[test]
+    fn test_posix_name_not_found() {
+        new_ucmd!()
+            .args(&["-M", "-N", "uu-lsipc-test-does-not-exist"])
+            .succeeds()
+            .stderr_contains("name uu-lsipc-test-does-not-exist not found");
+    }
+
+    #[test]
+    fn test_shared_memory_attached_processes() {
+        let id = unsafe { libc::shmget(libc::IPC_PRIVATE, 4096, libc::IPC_CREAT | 0o600) };
+        assert_ne!(id, -1);
+        let address = unsafe { libc::shmat(id, std::ptr::null(), 0) };
+        assert_ne!(address as isize, -1);
+
+        let pid = std::process::id().to_string();
+
+        let result = new_ucmd!()
+            .args(&["-m", "--raw", "--noheadings", "-o", "ID,APIDS"])
+            .succeeds();
+
+        let detail = new_ucmd!().args(&["-m", "-i", &id.to_string()]).succeeds();
+
+        unsafe {
+            libc::shmdt(address);
+            libc::shmctl(id, libc::IPC_RMID, std::ptr::null_mut());
+        }
+
+        result.stdout_contains(format!("{id} {pid}\n"));
+        detail
+            .stdout_contains("Attachments:\n")
+            .stdout_contains(format!("{pid} "));
+    }
+}
@@ -15,9 +15,107 @@ fn test_column_headers() {
     let header_line = stdout.lines().next().unwrap();
     let cols: Vec<_> = header_line.split_whitespace().collect();
 
-    assert_eq!(cols.len(), 7);
+    assert_eq!(cols.len(), 9);
     assert_eq!(
         cols,
-        vec!["COMMAND", "PID", "TYPE", "MODE", "M", "START", "END"]
+        vec!["COMMAND", "PID", "TYPE", "SIZE", "MODE", "M", "START", "END", "PATH"]
     );
 }
+
+#[cfg(target_os = "linux")]
+fn lock_fixture(ts: &TestScenario) -> (std::fs::File, std::path::PathBuf) {
+    use std::os::fd::AsRawFd;
+
+    ts.fixtures.write("locked", "0123456789");
+    let file = std::fs::File::open(ts.fixtures.plus("locked")).unwrap();
+    assert_eq!(unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) }, 0);
+
+    let path = std::fs::canonicalize(ts.fixtures.plus("locked")).unwrap();
+    (file, path)
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_lock_path_and_size() {
+    let ts = TestScenario::new(util_name!());
+    let (_file, path) = lock_fixture(&ts);
+    let pid = std::process::id().to_string();
+
+    let res = ts.ucmd().succeeds();
+    let line = res
+        .stdout_str()
+        .lines()
+        .find(|line| line.ends_with(path.to_str().unwrap()))
+        .unwrap_or_else(|| panic!("lock on {path:?} not listed"));
+
+    let cols: Vec<_> = line.split_whitespace().collect();
+    assert_eq!(&cols[1..4], [pid.as_str(), "FLOCK", "10B"]);
+    // The lock applies to the whole file, so it ends where the file ends
+    assert_eq!(cols[7], "10");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_output_columns() {
+    let res = new_ucmd!()
+        .args(&["--output", "pid,Type", "--noheadings"])
+        .succeeds();
+    assert!(res
+        .stdout_str()
+        .lines()
+        .all(|line| line.split_whitespace().count() == 2));
+
+    new_ucmd!()
+        .args(&["-o", "+INODE", "--pid", "1", "--raw"])
+        .succeeds()
+        .stdout_contains("COMMAND PID TYPE SIZE MODE M START END PATH INODE\n");
+
+    new_ucmd!()
+        .args(&["--output-all", "--pid", "1", "--raw"])
+        .succeeds()
+        .stdout_contains(
+            "COMMAND PID TYPE SIZE INODE MAJ:MIN MODE M START END PATH BLOCKER HOLDERS\n",
+        );
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_unknown_column() {
+    new_ucmd!()
+        .args(&["-o", "COMMAND,FOO"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("unknown column: FOO");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_json_for_pid() {
+    let ts = TestScenario::new(util_name!());
+    let (_file, path) = lock_fixture(&ts);
+    let pid = std::process::id();
+
+    let res = ts
+        .ucmd()
+        .args(&["--json", "--bytes", "--pid", &pid.to_string()])
+        .args(&["-o", "PID,TYPE,SIZE,M,END,PATH,HOLDERS"])
+        .succeeds();
+
+    let json: serde_json::Value = serde_json::from_str(res.stdout_str()).unwrap();
+    let locks = json["locks"].as_array().unwrap();
+    assert!(locks.iter().all(|lock| lock["pid"] == pid));
+
+    let lock = locks
+        .iter()
+        .find(|lock| lock["path"] == path.to_str().unwrap())
+        .unwrap();
+    assert_eq!(lock["type"], "FLOCK");
+    assert_eq!(lock["size"], 10);
+    assert_eq!(lock["m"], false);
+    assert_eq!(lock["end"], 10);
+    assert!(lock["holders"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|holder| holder.as_str().unwrap().starts_with(&format!("{pid},"))));
+}
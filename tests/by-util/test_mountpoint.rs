@@ -9,3 +9,134 @@ use crate::common::util::TestScenario;
 fn test_invalid_arg() {
     new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
 }
+
+#[test]
+fn test_root_is_mountpoint() {
+    new_ucmd!()
+        .arg("/")
+        .succeeds()
+        .no_stderr()
+        .stdout_is("/ is a mountpoint\n");
+}
+
+#[test]
+fn test_directory_is_not_mountpoint() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("dir");
+
+    ucmd.arg("dir")
+        .fails()
+        .code_is(32)
+        .no_stderr()
+        .stdout_is("dir is not a mountpoint\n");
+}
+
+#[test]
+fn test_quiet() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.mkdir("dir");
+
+    ucmd.args(&["-q", "dir"]).fails().code_is(32).no_output();
+    new_ucmd!().args(&["-q", "/"]).succeeds().no_output();
+    new_ucmd!()
+        .args(&["-q", "missing"])
+        .fails()
+        .code_is(1)
+        .no_output();
+}
+
+#[test]
+fn test_missing_path() {
+    new_ucmd!()
+        .arg("missing")
+        .fails()
+        .code_is(1)
+        .no_stdout()
+        .stderr_is("mountpoint: missing: No such file or directory\n");
+}
+
+#[test]
+fn test_fs_devno() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::metadata("/").unwrap().dev();
+    new_ucmd!().args(&["-d", "/"]).succeeds().stdout_is(format!(
+        "{}:{}\n",
+        libc::major(dev),
+        libc::minor(dev)
+    ));
+}
+
+#[test]
+fn test_devno_not_a_block_device() {
+    new_ucmd!()
+        .args(&["-x", "/dev/null"])
+        .fails()
+        .code_is(32)
+        .no_stdout()
+        .stderr_is("mountpoint: /dev/null: not a block device\n");
+}
+
+#[test]
+fn test_nofollow() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.symlink_dir("/", "link");
+
+    ts.ucmd()
+        .arg("link")
+        .succeeds()
+        .stdout_is("link is a mountpoint\n");
+    ts.ucmd()
+        .args(&["--nofollow", "link"])
+        .fails()
+        .code_is(32)
+        .stdout_is("link is not a mountpoint\n");
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::process::Command;
+
+    use crate::common::util::TestScenario;
+
+    #[test]
+    fn test_devno() {
+        use std::os::unix::fs::MetadataExt;
+
+        let device = loop_device_or_skip!(1024 * 1024);
+        let rdev = std::fs::metadata(device.path()).unwrap().rdev();
+
+        new_ucmd!()
+            .arg("-x")
+            .arg(device.path())
+            .succeeds()
+            .stdout_is(format!("{}:{}\n", libc::major(rdev), libc::minor(rdev)));
+    }
+
+    #[test]
+    fn test_bind_mount() {
+        // A bind mount stays on the same filesystem, which used to fool the detection.
+        let (at, mut ucmd) = at_and_ucmd!();
+        at.mkdir("dir");
+
+        let mounted = Command::new("mount")
+            .args([
+                "--bind",
+                &at.plus_as_string("dir"),
+                &at.plus_as_string("dir"),
+            ])
+            .status()
+            .is_ok_and(|status| status.success());
+        if !mounted {
+            println!("test skipped: bind mounts require root");
+            return;
+        }
+
+        let result = ucmd.arg("dir").run();
+        Command::new("umount")
+            .arg(at.plus_as_string("dir"))
+            .status()
+            .unwrap();
+        result.success().stdout_is("dir is a mountpoint\n");
+    }
+}
@@ -11,6 +11,284 @@ mod linux {
     fn test_help() {
         new_ucmd!().arg("--help").succeeds();
     }
+
+    #[test]
+    fn test_advanced_timing_headers() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        ucmd.env("SHELL", "/bin/sh")
+            .args(&["-q", "-m", "advanced", "-T", "timing", "-c", "exit 3"])
+            .arg("typescript")
+            .succeeds();
+
+        let timing = at.read("timing");
+        let lines: Vec<&str> = timing.lines().collect();
+        assert!(lines[0].starts_with("H 0.000000 START_TIME "));
+        assert!(lines.contains(&"H 0.000000 SHELL /bin/sh"));
+        assert!(lines.contains(&"H 0.000000 COMMAND exit 3"));
+        assert!(lines.contains(&"H 0.000000 TIMING_LOG timing"));
+        assert!(lines[lines.len() - 2].contains(" DURATION "));
+        assert_eq!(lines[lines.len() - 1], "H 0.000000 EXIT_CODE 3");
+    }
+
+    #[test]
+    fn test_classic_timing_has_no_headers() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        ucmd.args(&["-q", "-T", "timing", "-c", "exit 3", "typescript"])
+            .succeeds();
+
+        // Classic timing files only record the output, as "<delay> <length>".
+        let timing = at.read("timing");
+        assert!(timing
+            .lines()
+            .all(|line| line.starts_with(|c: char| c.is_ascii_digit())));
+    }
+
+    #[test]
+    fn test_window_resize_is_logged() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        let mut child = ucmd
+            .terminal_size(libc::winsize {
+                ws_row: 30,
+                ws_col: 100,
+                ws_xpixel: 0,
+                ws_ypixel: 0,
+            })
+            .args(&["-q", "-m", "advanced", "-T", "timing", "-c", "sleep 1"])
+            .arg("typescript")
+            .run_no_wait();
+        child.delay(300);
+        unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGWINCH) };
+        child.wait().unwrap().success();
+
+        let timing = at.read("timing");
+        assert!(timing.contains("H 0.000000 COLUMNS 100\n"));
+        assert!(timing.contains("H 0.000000 LINES 30\n"));
+        assert!(timing
+            .lines()
+            .any(|line| line.starts_with("S ") && line.ends_with(" SIGWINCH ROWS=30 COLS=100")));
+    }
+
+    #[test]
+    fn test_terminate_signal_is_forwarded() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        let mut child = ucmd
+            .terminal_simulation(true)
+            .args(&["-q", "-e", "-m", "advanced", "-T", "timing"])
+            .args(&["-c", "sleep 10", "typescript"])
+            .run_no_wait();
+        child.delay(300);
+        unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGTERM) };
+        // The shell running the command is killed, not script itself.
+        child.wait().unwrap().code_is(128 + libc::SIGTERM);
+
+        let timing = at.read("timing");
+        assert!(timing
+            .lines()
+            .any(|line| line.starts_with("S ") && line.ends_with(" SIGTERM")));
+        assert!(timing.ends_with("H 0.000000 EXIT_CODE 143\n"));
+    }
+
+    #[test]
+    fn test_large_output_is_recorded_exactly() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        // The pseudoterminal turns "\n" into "\r\n".
+        let expected: String = (1..=700_000).map(|i| format!("{i}\r\n")).collect();
+        assert!(expected.len() > 5_000_000);
+
+        ucmd.args(&["-q", "-T", "timing", "-c", "seq 1 700000", "typescript"])
+            .succeeds()
+            .stdout_only(&expected);
+        assert_eq!(at.read("typescript"), expected);
+
+        let recorded: usize = at
+            .read("timing")
+            .lines()
+            .map(|line| line.split(' ').nth(1).unwrap().parse::<usize>().unwrap())
+            .sum();
+        assert_eq!(recorded, expected.len());
+    }
+
+    #[test]
+    fn test_output_after_a_pause_is_recorded() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        ucmd.args(&[
+            "-q",
+            "--buffer-size",
+            "16",
+            "-c",
+            "echo hello; sleep 0.2; echo world",
+        ])
+        .arg("typescript")
+        .succeeds()
+        .stdout_only("hello\r\nworld\r\n");
+        assert_eq!(at.read("typescript"), "hello\r\nworld\r\n");
+    }
+
+    #[test]
+    fn test_output_of_background_jobs_is_recorded() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        // The shell exits right away, while its background job still holds the terminal. HUP is
+        // ignored, as the job would be hung up when the shell exits otherwise.
+        ucmd.args(&[
+            "-q",
+            "-c",
+            "trap '' HUP; (sleep 0.3; echo late) & echo early",
+        ])
+        .arg("typescript")
+        .succeeds()
+        .stdout_only("early\r\nlate\r\n");
+        assert_eq!(at.read("typescript"), "early\r\nlate\r\n");
+    }
+
+    #[test]
+    fn test_invalid_buffer_size() {
+        new_ucmd!()
+            .args(&["-q", "--buffer-size", "0", "-c", "true"])
+            .fails()
+            .code_is(1)
+            .stderr_is("script: buffer size must be positive\n");
+    }
+
+    #[test]
+    fn test_piped_input() {
+        let ts = TestScenario::new(util_name!());
+
+        // The end of the input is passed on to the session, which would wait forever
+        // otherwise.
+        ts.ucmd()
+            .args(&["-q", "-c", "cat; echo done", "typescript"])
+            .pipe_in("hello\n")
+            .succeeds()
+            .stdout_only("hello\r\nhello\r\ndone\r\n");
+        ts.ucmd()
+            .args(&["-q", "-E", "never", "-c", "cat; echo done", "typescript"])
+            .pipe_in("hello\n")
+            .succeeds()
+            .stdout_only("hello\r\ndone\r\n");
+        ts.ucmd()
+            .args(&["-q", "-E", "never", "-c", "cat; echo done", "typescript"])
+            .pipe_in("partial")
+            .succeeds()
+            .stdout_only("partialdone\r\n");
+        assert_eq!(ts.fixtures.read("typescript"), "partialdone\r\n");
+    }
+
+    #[test]
+    fn test_messages_stay_out_of_pipes() {
+        new_ucmd!()
+            .args(&["-c", "echo hello", "typescript"])
+            .succeeds()
+            .stdout_is("hello\r\n")
+            .stderr_is("Script started, file is typescript\nScript done, file is typescript\n");
+    }
+
+    #[test]
+    fn test_rotate_by_size() {
+        let ts = TestScenario::new(util_name!());
+        let expected: String = (1..=2000).map(|i| format!("{i}\r\n")).collect();
+
+        ts.ucmd()
+            .args(&["-q", "-m", "advanced", "-T", "timing", "-c", "seq 1 2000"])
+            .args(&[
+                "--buffer-size",
+                "512",
+                "--rotate-size",
+                "1K",
+                "--rotate-keep",
+                "3",
+            ])
+            .arg("typescript")
+            .succeeds()
+            .stdout_only(&expected);
+
+        let chunks: Vec<String> = ["typescript.3", "typescript.2", "typescript.1", "typescript"]
+            .iter()
+            .map(|name| ts.fixtures.read(name))
+            .collect();
+        assert!(!ts.fixtures.file_exists("typescript.4"));
+        assert!(!ts.fixtures.file_exists("timing.4"));
+
+        // The oldest chunks are gone, each of the others starts with a header.
+        let mut recorded = String::new();
+        for chunk in &chunks {
+            let (header, data) = chunk.split_once('\n').unwrap();
+            assert!(header.starts_with("Script started on "));
+            assert!(data.len() <= 1024 + 512);
+            recorded.push_str(data);
+        }
+        assert!(expected.ends_with(&recorded));
+        assert!(ts
+            .fixtures
+            .read("timing.2")
+            .starts_with("H 0.000000 START_TIME "));
+
+        // A chunk can be replayed without the others.
+        ts.cmd(&ts.bin_path)
+            .args(&[
+                "scriptreplay",
+                "-t",
+                "timing.2",
+                "-s",
+                "typescript.2",
+                "-d",
+                "1000",
+            ])
+            .succeeds()
+            .stdout_only(chunks[1].split_once('\n').unwrap().1);
+    }
+
+    #[test]
+    fn test_rotate_by_time() {
+        let (at, mut ucmd) = at_and_ucmd!();
+
+        ucmd.args(&["-q", "--rotate-time", "0.005"])
+            .args(&["-c", "echo before; sleep 0.5; echo after", "typescript"])
+            .succeeds()
+            .stdout_only("before\r\nafter\r\n");
+        assert!(at.read("typescript.1").ends_with("]\nbefore\r\n"));
+        assert!(at.read("typescript").ends_with("]\nafter\r\n"));
+    }
+
+    #[test]
+    fn test_invalid_rotation() {
+        new_ucmd!()
+            .args(&["-q", "--rotate-size", "0", "-c", "true"])
+            .fails()
+            .code_is(1)
+            .stderr_is("script: rotation size must be positive\n");
+        new_ucmd!()
+            .args(&["-q", "--rotate-time", "soon", "-c", "true"])
+            .fails()
+            .code_is(1)
+            .stderr_is("script: Invalid number of minutes: soon\n");
+        new_ucmd!()
+            .args(&["-q", "--rotate-time", "1e308", "-c", "true"])
+            .fails()
+            .code_is(1)
+            .stderr_is("script: Invalid number of minutes: 1e308\n");
+    }
+
+    #[test]
+    fn test_terminal_settings_are_restored() {
+        let ts = TestScenario::new(util_name!());
+        let script = format!(
+            "stty -g > before; {} script -q -c true typescript; stty -g > after",
+            ts.bin_path.display()
+        );
+
+        ts.cmd("sh")
+            .terminal_simulation(true)
+            .args(&["-c", &script])
+            .succeeds();
+        assert_eq!(ts.fixtures.read("before"), ts.fixtures.read("after"));
+    }
 }
 
 #[cfg(not(target_os = "linux"))]
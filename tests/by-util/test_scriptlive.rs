@@ -0,0 +1,110 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use crate::common::util::TestScenario;
+
+    #[test]
+    fn test_invalid_arg() {
+        new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+    }
+
+    #[test]
+    fn test_missing_files() {
+        new_ucmd!()
+            .fails()
+            .code_is(1)
+            .stderr_contains("scriptlive: timing file not specified");
+        new_ucmd!()
+            .args(&["-t", "timing"])
+            .fails()
+            .code_is(1)
+            .stderr_contains("scriptlive: input log file not specified");
+    }
+
+    #[test]
+    fn test_classic_timing_file() {
+        let (at, mut ucmd) = at_and_ucmd!();
+        at.write("timing", "0.1 6\n");
+        at.write("in", "hello\n");
+
+        ucmd.args(&["timing", "in"])
+            .fails()
+            .code_is(1)
+            .no_stdout()
+            .stderr_contains("timing file timing: no input recorded");
+    }
+
+    #[test]
+    fn test_truncated_log() {
+        let (at, mut ucmd) = at_and_ucmd!();
+        at.write("timing", "I 0.1 8\nO 0.1 4\nI 0.1 5\n");
+        at.write("io", "echo hi\nhi\r\nex");
+
+        // Nothing is run when the input can't be replayed entirely.
+        ucmd.args(&["-t", "timing", "-B", "io"])
+            .fails()
+            .code_is(1)
+            .no_stdout()
+            .stderr_is("scriptlive: io: unexpected end of file\n");
+    }
+
+    #[test]
+    fn test_replay_input() {
+        let ts = TestScenario::new(util_name!());
+        ts.fixtures.write("timing", "I 0.1 8\nO 0.1 4\nI 0.2 5\n");
+        ts.fixtures.write("io", "echo hi\nhi\r\nexit\n");
+        ts.fixtures.write("in", "echo hi\nexit\n");
+
+        ts.ucmd()
+            .env("SHELL", "/bin/sh")
+            .args(&["-t", "timing", "-B", "io", "-d", "10"])
+            .succeeds()
+            .stdout_contains(">>> scriptlive: Starting your typescript execution by /bin/sh.\n")
+            .stdout_contains("hi\r\n")
+            .stdout_contains(">>> scriptlive: Done.\n");
+        ts.ucmd()
+            .env("SHELL", "/bin/sh")
+            .args(&["timing", "in", "-m", "0.01"])
+            .args(&["-c", "read line; echo \"got $line\"; exit 7"])
+            .fails()
+            .code_is(7)
+            .stdout_contains("got echo hi\r\n");
+    }
+
+    #[test]
+    fn test_huge_delays() {
+        let ts = TestScenario::new(util_name!());
+        ts.fixtures.write("timing", "I 0.5 5\n");
+        ts.fixtures.write("in", "exit\n");
+
+        ts.ucmd()
+            .args(&["timing", "in", "-m", "1e300"])
+            .fails()
+            .code_is(1)
+            .stderr_contains("invalid number of seconds: '1e300'");
+        // The stretched delay is still capped by --maxdelay.
+        ts.ucmd()
+            .env("SHELL", "/bin/sh")
+            .args(&["timing", "in", "-d", "1e-300", "-m", "0"])
+            .succeeds()
+            .stdout_contains(">>> scriptlive: Done.\n");
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod non_linux {
+    use crate::common::util::TestScenario;
+
+    #[test]
+    fn test_fails_on_unsupported_platforms() {
+        new_ucmd!()
+            .args(&["timing", "in"])
+            .fails()
+            .code_is(1)
+            .stderr_is("scriptlive: `scriptlive` is available only on Linux.\n");
+    }
+}
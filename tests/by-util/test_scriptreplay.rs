@@ -0,0 +1,183 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+use std::time::{Duration, Instant};
+
+use crate::common::util::TestScenario;
+
+#[test]
+fn test_invalid_arg() {
+    new_ucmd!().arg("--definitely-invalid").fails().code_is(1);
+}
+
+#[test]
+fn test_missing_timing_file() {
+    new_ucmd!()
+        .fails()
+        .code_is(1)
+        .stderr_contains("scriptreplay: timing file not specified");
+}
+
+#[test]
+fn test_classic() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("timing", "0.010000 6\n0.020000 6\n");
+    at.write("typescript", "hello\nworld\n");
+
+    ucmd.args(&["-t", "timing", "-d", "100"])
+        .succeeds()
+        .no_stderr()
+        .stdout_is("hello\nworld\n");
+}
+
+#[test]
+fn test_positional_arguments() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write("timing", "0.010000 6\n");
+    ts.fixtures
+        .write("session", "Script started on 2024-01-01 10:00:00\nhello\n");
+
+    // The header line of older typescripts is skipped.
+    ts.ucmd()
+        .args(&["timing", "session", "100"])
+        .succeeds()
+        .stdout_is("hello\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "session", "100"])
+        .succeeds()
+        .stdout_is("hello\n");
+    ts.ucmd()
+        .args(&["timing", "session", "0"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("divisor must be a positive number: '0'");
+}
+
+#[test]
+fn test_advanced_streams() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write(
+        "timing",
+        "H 0.000000 TERM xterm\n\
+         I 0.010000 3\n\
+         O 0.010000 6\n\
+         S 0.010000 SIGWINCH ROWS=24 COLS=80\n\
+         O 0.010000 6\n",
+    );
+    ts.fixtures.write("io", "ls\nfile1\nfile2\n");
+
+    ts.ucmd()
+        .args(&["-t", "timing", "-B", "io", "-d", "100"])
+        .succeeds()
+        .stdout_is("file1\nfile2\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "-B", "io", "-d", "100", "-x", "in"])
+        .succeeds()
+        .stdout_is("ls\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "-B", "io", "-d", "100", "--cm-in"])
+        .succeeds()
+        .stdout_is("ls\nfile1\nfile2\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "-x", "signal"])
+        .succeeds()
+        .stdout_is("SIGWINCH ROWS=24 COLS=80\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "-x", "info"])
+        .succeeds()
+        .stdout_is("TERM xterm\n");
+}
+
+#[test]
+fn test_separate_logs() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write("timing", "I 0.01 3\nO 0.01 6\n");
+    ts.fixtures.write("in", "ls\n");
+    ts.fixtures.write("out", "file1\n");
+
+    ts.ucmd()
+        .args(&["-t", "timing", "-I", "in", "-O", "out", "-c"])
+        .succeeds()
+        .stdout_is("ls\nfile1\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "-O", "out", "-x", "in"])
+        .fails()
+        .code_is(1)
+        .stderr_contains("log file for the input stream not specified");
+}
+
+#[test]
+fn test_maxdelay_and_target() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write("timing", "30 6\n30 6\n");
+    ts.fixtures.write("typescript", "hello\nworld\n");
+
+    let start = Instant::now();
+    ts.ucmd()
+        .args(&["-t", "timing", "-m", "0.01"])
+        .succeeds()
+        .stdout_is("hello\nworld\n");
+    ts.ucmd()
+        .args(&["-t", "timing", "--target", "60"])
+        .succeeds()
+        .stdout_is("hello\nworld\n");
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_huge_delays() {
+    let ts = TestScenario::new(util_name!());
+    ts.fixtures.write("timing", "0.5 6\n");
+    ts.fixtures.write("typescript", "hello\n");
+
+    for option in ["-m", "--target"] {
+        ts.ucmd()
+            .args(&["-t", "timing", option, "1e300"])
+            .fails()
+            .code_is(1)
+            .stderr_contains("invalid number of seconds: '1e300'");
+    }
+    // The stretched delay is still capped by --maxdelay.
+    ts.ucmd()
+        .args(&["-t", "timing", "-d", "1e-300", "-m", "0"])
+        .succeeds()
+        .stdout_is("hello\n");
+}
+
+#[test]
+fn test_invalid_timing_file() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("timing", "0.1 6\nO 0.1 abc\n");
+    at.write("typescript", "hello\n");
+
+    ucmd.args(&["-t", "timing"])
+        .fails()
+        .code_is(1)
+        .stdout_is("hello\n")
+        .stderr_is("scriptreplay: timing file timing: line 2: invalid length 'abc'\n");
+}
+
+#[test]
+fn test_truncated_typescript() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("timing", "0.01 6\n0.01 6\n");
+    at.write("typescript", "hello\n");
+
+    ucmd.args(&["-t", "timing"])
+        .fails()
+        .code_is(1)
+        .stderr_is("scriptreplay: typescript: unexpected end of file\n");
+}
+
+#[test]
+fn test_missing_typescript() {
+    let (at, mut ucmd) = at_and_ucmd!();
+    at.write("timing", "0.01 6\n");
+
+    ucmd.args(&["-t", "timing"])
+        .fails()
+        .code_is(1)
+        .stderr_is("scriptreplay: cannot open typescript: No such file or directory\n");
+}
@@ -0,0 +1,118 @@
+// This file is part of the uutils util-linux package.
+//
+// For the full copyright and license information, please view the LICENSE
+// file that was distributed with this source code.
+
+//! Loop devices backed by temporary files, for utilities which need a real block device.
+
+#![allow(dead_code)]
+
+use std::fs::{File, OpenOptions};
+use std::os::fd::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use tempfile::NamedTempFile;
+
+// From linux/loop.h
+const LOOP_SET_FD: libc::c_ulong = 0x4C00;
+const LOOP_CLR_FD: libc::c_ulong = 0x4C01;
+const LOOP_CTL_GET_FREE: libc::c_ulong = 0x4C82;
+
+/// A loop device attached to a zero-filled temporary file, detached on drop.
+pub struct LoopDevice {
+    path: PathBuf,
+    backing_file: Option<NamedTempFile>,
+}
+
+impl LoopDevice {
+    /// Attaches a free loop device to a new temporary file of `size` bytes.
+    ///
+    /// Returns `None` when not running as root or when loop devices are not available, in
+    /// which case the calling test should be skipped.
+    pub fn new(size: u64) -> Option<Self> {
+        if !Self::available() {
+            return None;
+        }
+
+        let backing_file = NamedTempFile::new().ok()?;
+        backing_file.as_file().set_len(size).ok()?;
+        let control = File::open("/dev/loop-control").ok()?;
+
+        // Another process may take the free device before we attach to it, so retry a few times.
+        for _ in 0..10 {
+            let index = unsafe { libc::ioctl(control.as_raw_fd(), LOOP_CTL_GET_FREE as _) };
+            if index < 0 {
+                return None;
+            }
+
+            let path = PathBuf::from(format!("/dev/loop{index}"));
+            let device = OpenOptions::new().read(true).write(true).open(&path).ok()?;
+
+            let backing_fd = backing_file.as_file().as_raw_fd();
+            if unsafe { libc::ioctl(device.as_raw_fd(), LOOP_SET_FD as _, backing_fd) } == 0 {
+                return Some(Self {
+                    path,
+                    backing_file: Some(backing_file),
+                });
+            }
+        }
+
+        None
+    }
+
+    /// Whether loop devices can be set up, i.e. we're root and the kernel supports them.
+    pub fn available() -> bool {
+        (unsafe { libc::geteuid() }) == 0 && Path::new("/dev/loop-control").exists()
+    }
+
+    /// Takes ownership of a loop device set up by the utility under test, so that it gets
+    /// detached even if the test fails.
+    pub fn adopt(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            backing_file: None,
+        }
+    }
+
+    pub fn backing_file(&self) -> &Path {
+        self.backing_file.as_ref().unwrap().path()
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn path_str(&self) -> &str {
+        self.path.to_str().unwrap()
+    }
+}
+
+impl Drop for LoopDevice {
+    // The device isn't kept open, as the kernel defers detaching it until the last close.
+    fn drop(&mut self) {
+        if let Ok(device) = File::open(&self.path) {
+            unsafe { libc::ioctl(device.as_raw_fd(), LOOP_CLR_FD as _, 0) };
+        }
+    }
+}
+
+/// Evaluates to a [`LoopDevice`] of the given size, or returns from the calling test when
+/// loop devices cannot be used. Without a size, only checks that loop devices can be used.
+#[macro_export]
+macro_rules! loop_device_or_skip {
+    () => {
+        if !$crate::common::loop_device::LoopDevice::available() {
+            println!("test skipped: loop devices require root and /dev/loop-control");
+            return;
+        }
+    };
+    ($size:expr) => {
+        match $crate::common::loop_device::LoopDevice::new($size) {
+            Some(device) => device,
+            None => {
+                println!("test skipped: loop devices require root and /dev/loop-control");
+                return;
+            }
+        }
+    };
+}
@@ -4,5 +4,8 @@
 // file that was distributed with this source code.
 #[macro_use]
 pub mod macros;
+#[cfg(target_os = "linux")]
+#[macro_use]
+pub mod loop_device;
 pub mod random;
 pub mod util;
@@ -64,3 +64,39 @@ mod test_mcookie;
 #[cfg(feature = "script")]
 #[path = "by-util/test_script.rs"]
 mod test_script;
+
+#[cfg(feature = "ipcmk")]
+#[path = "by-util/test_ipcmk.rs"]
+mod test_ipcmk;
+
+#[cfg(feature = "ipcrm")]
+#[path = "by-util/test_ipcrm.rs"]
+mod test_ipcrm;
+
+#[cfg(feature = "lsipc")]
+#[path = "by-util/test_lsipc.rs"]
+mod test_lsipc;
+
+#[cfg(feature = "flock")]
+#[path = "by-util/test_flock.rs"]
+mod test_flock;
+
+#[cfg(feature = "losetup")]
+#[path = "by-util/test_losetup.rs"]
+mod test_losetup;
+
+#[cfg(feature = "lsblk")]
+#[path = "by-util/test_lsblk.rs"]
+mod test_lsblk;
+
+#[cfg(feature = "findmnt")]
+#[path = "by-util/test_findmnt.rs"]
+mod test_findmnt;
+
+#[cfg(feature = "scriptreplay")]
+#[path = "by-util/test_scriptreplay.rs"]
+mod test_scriptreplay;
+
+#[cfg(feature = "scriptlive")]
+#[path = "by-util/test_scriptlive.rs"]
+mod test_scriptlive;